@@ -7,8 +7,20 @@ contract Config {
     uint public minNumber;
     uint public maxNumber;
 
+    // split of the revenue of the tickets, in percent (by default, all the revenue goes to the jackpot)
+    uint8 public jackpotShare = 100;
+    uint8 public treasuryShare;
+    uint8 public reserveShare;
+
+    // price of a ticket, in the token used to pay the tickets
+    uint public ticketPrice;
+
     event ConfigUpdated(uint8 nbNumbers, uint minNumber, uint maxNumber);
 
+    event RevenueSplitUpdated(uint8 jackpotShare, uint8 treasuryShare, uint8 reserveShare);
+
+    event TicketPriceUpdated(uint ticketPrice);
+
     function _setConfig(uint8 _nbNumbers, uint _minNumber, uint _maxNumber) internal {
         // check the provided config
         require(_nbNumbers > 0, "Nb Numbers must be greater than 0");
@@ -21,10 +33,69 @@ contract Config {
         emit ConfigUpdated(nbNumbers, minNumber, maxNumber);
     }
 
+    function _setRevenueSplit(uint8 _jackpotShare, uint8 _treasuryShare, uint8 _reserveShare) internal {
+        // the parts must cover all the revenue
+        require(uint(_jackpotShare) + uint(_treasuryShare) + uint(_reserveShare) == 100, "Incorrect revenue split");
+        // save the revenue split
+        jackpotShare = _jackpotShare;
+        treasuryShare = _treasuryShare;
+        reserveShare = _reserveShare;
+        // emit the event
+        emit RevenueSplitUpdated(jackpotShare, treasuryShare, reserveShare);
+    }
+
+    function _setTicketPrice(uint _ticketPrice) internal {
+        ticketPrice = _ticketPrice;
+        // emit the event
+        emit TicketPriceUpdated(ticketPrice);
+    }
+
+    // check the amount paid matches the price of the given number of tickets
+    function _checkAmount(uint _nbTickets, uint _amount) internal view {
+        require(_amount == ticketPrice * _nbTickets, "Incorrect amount");
+    }
+
     function _ensureConfig() internal view {
         require(nbNumbers > 0, "Config not set");
     }
 
+    // generate valid numbers without duplicate from the given seed (quick pick)
+    // the numbers are picked with the Floyd's algorithm to stop after nbNumbers iterations
+    function _quickPick(bytes32 _seed) internal view returns (uint[] memory) {
+        // check the config is set
+        _ensureConfig();
+        uint _range = maxNumber - minNumber + 1;
+        require(nbNumbers <= _range, "Range too small");
+
+        uint[] memory _numbers = new uint[](nbNumbers);
+        uint _index = 0;
+        for (uint j = _range - nbNumbers; j < _range; j++){
+            // random number between 0 and j
+            uint _number = minNumber + uint(keccak256(abi.encode(_seed, j))) % (j + 1);
+            for (uint k = 0; k < _index; k++){
+                if (_numbers[k] == _number){
+                    _number = minNumber + j;
+                    break;
+                }
+            }
+            _numbers[_index++] = _number;
+        }
+        return _numbers;
+    }
+
+    // return true if the numbers satisfy the config, without reverting
+    function _isValidNumbers(uint[] memory _numbers) internal view returns (bool) {
+        if (nbNumbers == 0 || _numbers.length != nbNumbers){
+            return false;
+        }
+        for (uint i=0; i<_numbers.length; i++){
+            if (_numbers[i] < minNumber || _numbers[i] > maxNumber){
+                return false;
+            }
+        }
+        return true;
+    }
+
     function _checkNumbers(uint[] memory _numbers) internal view {
         // check the config is set
         _ensureConfig();
@@ -5,14 +5,22 @@ import "hardhat/console.sol";
 
 import "@openzeppelin/contracts/access/Ownable.sol";
 import "@openzeppelin/contracts/access/AccessControl.sol";
+import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/extensions/IERC20Permit.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import "@openzeppelin/contracts/utils/cryptography/MessageHashUtils.sol";
 
 import "./Config.sol";
 import "./PhatRollupAnchor.sol";
+import "./RaffleTicket.sol";
 
 contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor {
 
+	using SafeERC20 for IERC20;
+
 	// workflow status
-	enum Status { NotStarted, Started, RegistrationsOpen, RegistrationsClosed, SaltGenerated, ResultsReceived }
+	enum Status { NotStarted, Started, RegistrationsOpen, RegistrationsClosed, SaltGenerated, ResultsReceived, Closed, DrawCancelled }
 
 	// Event emitted when the workflow starts
 	event Started(uint indexed registrationContractId);
@@ -29,12 +37,155 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 	// Event emitted when the results are received
 	event ResultsReceived(uint indexed registrationContractId, uint indexed drawNumber, uint[] numbers, bool hasWinner);
 
+	// Event emitted when a draw is cancelled
+	event DrawCancelled(uint indexed registrationContractId, uint indexed drawNumber);
+
+	// Event emitted when the lotto is closed
+	event LottoClosed(uint indexed registrationContractId, uint indexed drawNumber);
+
+	// Event emitted when the participations are paused
+	event Paused(uint indexed registrationContractId);
+
+	// Event emitted when the participations are unpaused
+	event Unpaused(uint indexed registrationContractId);
+
+	// Event emitted when the token used to pay the tickets is updated
+	event PaymentTokenUpdated(address token);
+
+	// Event emitted when a subscription is created
+	event SubscriptionCreated(uint indexed subscriptionId, address indexed owner, uint[] numbers, uint nbDraws, uint prepaid);
+
+	// Event emitted when a subscription is cancelled and the remaining draws are refunded
+	event SubscriptionCancelled(uint indexed subscriptionId, address indexed owner, uint refund);
+
+	// Event emitted when the ERC-721 contract used for the tickets is updated
+	event TicketNftUpdated(address ticketNft);
+
+	// Event emitted when the maximum number of tickets per account and per draw is updated
+	event MaxTicketsPerAccountUpdated(uint maxTickets);
+
+	// Event emitted when a winning ticket is paid to its holder
+	event TicketPaid(uint indexed tokenId, uint indexed drawNumber, address indexed owner, uint amount);
+
+	// Event emitted when the amount paid for a cancelled draw is refunded
+	event RefundClaimed(uint indexed drawNumber, address indexed owner, uint amount);
+
 	// Event emitted when the participation is registered
 	event ParticipationRegistered(uint indexed registrationContractId, uint indexed drawNumber, address indexed participant, uint[] numbers);
 
 	// registration contract id, must be unique in all similar contracts deployed on different chains
+	// layout (from the most significant bits): chain family (8 bits, 2 for Evm), reserved (24 bits), chain id (64 bits), local index (32 bits)
 	uint public registrationContractId;
 
+	// draws cancelled before the end
+	mapping(uint => bool) private cancelledDraws;
+
+	// role allowed to withdraw the treasury
+	bytes32 public constant TREASURY_ROLE = keccak256("TREASURY_ROLE");
+
+	// role allowed to pay the winning tickets
+	bytes32 public constant PAYOUT_ROLE = keccak256("PAYOUT_ROLE");
+
+	// amount kept by the operator, it is the only amount that can be withdrawn
+	uint public treasury;
+
+	// amount kept in reserve to seed the jackpot of the next draw
+	uint public reserve;
+
+	// parts of the revenue collected for the draw in progress, taken back if the draw is cancelled
+	uint private drawJackpotPart;
+	uint private drawTreasuryPart;
+	uint private drawReservePart;
+
+	// amount paid by each account for each draw, refunded if the draw is cancelled
+	mapping(uint => mapping(address => uint)) public stakes;
+
+	// ERC-20 token used to pay the tickets, address(0) for the native token
+	IERC20 public paymentToken;
+
+	// maximum number of tickets per account and per draw, 0 for no limit
+	uint public maxTicketsPerAccount;
+
+	// number of tickets registered by each account for each draw
+	mapping(uint => mapping(address => uint)) private ticketsPerAccount;
+
+	// nonce used to generate different numbers for each quick pick
+	uint private quickPickNonce;
+
+	// subscription to play the same ticket for several draws
+	struct Subscription {
+		address owner;
+		uint[] numbers;
+		uint remainingDraws;
+		uint prepaid;
+		// last draw where the subscription was played (or skipped)
+		uint lastPlayedDraw;
+	}
+
+	// maximum number of active subscriptions by account
+	uint public constant MAX_SUBSCRIPTIONS_PER_ACCOUNT = 10;
+
+	// maximum number of subscriptions played in one call of playSubscriptions
+	uint public constant MAX_SUBSCRIPTIONS_BATCH = 20;
+
+	// subscriptions by id
+	mapping(uint => Subscription) private subscriptions;
+
+	// ids of the subscriptions with remaining draws
+	uint[] private activeSubscriptions;
+
+	// index of the active subscriptions, by id
+	mapping(uint => uint) private activeSubscriptionIndexes;
+
+	// index of the next active subscription to play for the current draw,
+	// the subscriptions before this index are already played (or skipped)
+	uint private nextSubscriptionToPlay;
+
+	// number of active subscriptions by account
+	mapping(address => uint) public nbSubscriptions;
+
+	// id of the next subscription
+	uint public nextSubscriptionId;
+
+	// EIP-712 domain of the participations signed by the players, distinct from the domain of the meta transactions
+	bytes32 private constant EIP712_DOMAIN_TYPEHASH =
+	keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+	string public constant PARTICIPATION_DOMAIN_NAME = "RaffleRegistration";
+	string public constant PARTICIPATION_DOMAIN_VERSION = "1";
+
+	// EIP-712 type of the participation signed by the player
+	bytes32 private constant PARTICIPATION_TYPEHASH =
+	keccak256("Participation(address registrationContract,uint256 drawNumber,uint256[] numbers,uint256 nonce,uint256 deadline)");
+
+	// nonces used by the players for the signed participations
+	mapping(address => uint) public participationNonces;
+
+	// ERC-721 contract used to mint a ticket for each participation, address(0) for no ticket
+	RaffleTicket public ticketNft;
+
+	// winning numbers by draw
+	mapping(uint => uint[]) private winningNumbers;
+
+	// winning tickets already paid
+	mapping(uint => bool) private paidTickets;
+
+	// number of tickets minted by draw and by numbers (hash of the sorted numbers), used to count the winning tickets
+	mapping(uint => mapping(bytes32 => uint)) private nbTicketsByNumbers;
+
+	// jackpot of the previous draws without winning ticket in this contract
+	uint public carriedJackpot;
+
+	// payout of the winning tickets of a draw
+	struct DrawPayout {
+		// amount paid for each winning ticket (jackpot / number of winning tickets)
+		uint sharePerTicket;
+		// amount not paid yet
+		uint remaining;
+	}
+
+	// payout of the winning tickets by draw
+	mapping(uint => DrawPayout) public drawPayouts;
+
 	constructor(address _address)
 		Ownable(_address)
 	{
@@ -56,7 +207,20 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 	function _openRegistrations(uint _drawNumber) private {
 		// check the status
 		Status status = getStatus();
-		require(status == Status.Started || status == Status.ResultsReceived, "Incorrect Status");
+		if (status == Status.RegistrationsClosed){
+			// the registrations are extended for the same draw
+			require(getDrawNumber() == _drawNumber, "Incorrect Draw Number");
+		} else {
+			require(status == Status.Started || status == Status.ResultsReceived || status == Status.DrawCancelled, "Incorrect Status");
+			// reset the number of tickets for a new draw
+			_setNbTickets(0);
+			// seed the jackpot with the reserve
+			_setRevenue(reserve);
+			reserve = 0;
+			_resetDrawParts();
+			// the active subscriptions will be played for the new draw (see playSubscriptions)
+			nextSubscriptionToPlay = 0;
+		}
 		// save the data
 		_setDrawNumber(_drawNumber);
 		_setStatus(Status.RegistrationsOpen);
@@ -92,27 +256,572 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 		require(status == Status.RegistrationsClosed || status == Status.SaltGenerated, "Incorrect Status");
 		// check the draw number
 		require(getDrawNumber() == _drawNumber, "Incorrect Draw Number");
+		// save the winning numbers to check the tickets
+		winningNumbers[_drawNumber] = _numbers;
+		// split the jackpot between the winning tickets
+		_settlePayout(_drawNumber, _numbers, _hasWinner);
+		// the draw is settled, its part of the treasury can be withdrawn
+		_resetDrawParts();
 		// update the status
 		_setStatus(Status.ResultsReceived);
 		// emit the event
 		emit ResultsReceived(registrationContractId, _drawNumber, _numbers, _hasWinner);
 	}
 
+	// split the jackpot (revenue of the draw + jackpot carried over) between the winning tickets
+	// without winning ticket in this contract, the jackpot is carried over to the next draw
+	function _settlePayout(uint _drawNumber, uint[] memory _numbers, bool _hasWinner) private {
+		uint _jackpot = carriedJackpot + getRevenue();
+		uint _nbWinningTickets = _hasWinner ? nbTicketsByNumbers[_drawNumber][_numbersKey(_numbers)] : 0;
+		if (_nbWinningTickets == 0){
+			carriedJackpot = _jackpot;
+			return;
+		}
+		uint _sharePerTicket = _jackpot / _nbWinningTickets;
+		uint _total = _sharePerTicket * _nbWinningTickets;
+		// the rounding is carried over
+		carriedJackpot = _jackpot - _total;
+		drawPayouts[_drawNumber] = DrawPayout(_sharePerTicket, _total);
+	}
+
+	// return the hash of the numbers in ascending order, the tickets with the same numbers have the same key
+	function _numbersKey(uint[] memory _numbers) private pure returns (bytes32) {
+		uint[] memory _sorted = new uint[](_numbers.length);
+		for (uint i = 0; i < _numbers.length; i++){
+			uint _number = _numbers[i];
+			uint j = i;
+			while (j > 0 && _sorted[j - 1] > _number){
+				_sorted[j] = _sorted[j - 1];
+				j--;
+			}
+			_sorted[j] = _number;
+		}
+		return keccak256(abi.encode(_sorted));
+	}
+
+	function _stop(uint _drawNumber) private {
+		// check the status
+		Status status = getStatus();
+		require(status != Status.Closed, "Incorrect Status");
+		// cancel the draw in progress
+		if (status == Status.RegistrationsOpen || status == Status.RegistrationsClosed || status == Status.SaltGenerated){
+			uint _currentDrawNumber = getDrawNumber();
+			cancelledDraws[_currentDrawNumber] = true;
+			_refundDraw();
+			emit DrawCancelled(registrationContractId, _currentDrawNumber);
+		}
+		// save the data
+		_setDrawNumber(_drawNumber);
+		_setStatus(Status.Closed);
+		// emit the event
+		emit LottoClosed(registrationContractId, _drawNumber);
+	}
+
+	function _cancelDraw(uint _drawNumber) private {
+		// check the status
+		Status status = getStatus();
+		require(status != Status.NotStarted && status != Status.Closed && status != Status.DrawCancelled, "Incorrect Status");
+		// take back the amounts paid for the draw in progress
+		if (status == Status.RegistrationsOpen || status == Status.RegistrationsClosed || status == Status.SaltGenerated){
+			_refundDraw();
+		}
+		// save the data
+		cancelledDraws[_drawNumber] = true;
+		_setDrawNumber(_drawNumber);
+		_setStatus(Status.DrawCancelled);
+		// emit the event
+		emit DrawCancelled(registrationContractId, _drawNumber);
+	}
+
+	// update the config between two draws
+	function _updateConfig(uint8 _nbNumbers, uint _minNumber, uint _maxNumber) private {
+		// check the status
+		Status status = getStatus();
+		require(status == Status.Started || status == Status.ResultsReceived || status == Status.DrawCancelled, "Incorrect Status");
+		// save the config
+		_setConfig(_nbNumbers, _minNumber, _maxNumber);
+		_saveConfig();
+	}
+
+	bytes public constant CONFIG = "_config";
+
+	function _saveConfig() private {
+		// save the config in the kv store
+		kvStore[CONFIG] = abi.encode(nbNumbers, minNumber, maxNumber);
+	}
+
+	// take back the amounts paid for the draw in progress, the tickets are refunded to the participants
+	// the reserve used to seed the jackpot is kept for the next draw
+	function _refundDraw() private {
+		uint _seed = getRevenue() - drawJackpotPart;
+		reserve = reserve - drawReservePart + _seed;
+		treasury -= drawTreasuryPart;
+		_setRevenue(0);
+		_resetDrawParts();
+	}
+
+	function _resetDrawParts() private {
+		drawJackpotPart = 0;
+		drawTreasuryPart = 0;
+		drawReservePart = 0;
+	}
+
+	// refund the amount paid by the sender for the given draw when this draw is cancelled
+	function claimRefund(uint _drawNumber) external {
+		require(isDrawCancelled(_drawNumber), "Draw not cancelled");
+		uint _amount = stakes[_drawNumber][msg.sender];
+		require(_amount > 0, "Nothing to refund");
+		stakes[_drawNumber][msg.sender] = 0;
+		_transfer(msg.sender, _amount);
+		// emit the event
+		emit RefundClaimed(_drawNumber, msg.sender, _amount);
+	}
+
+	// return true if the draw has been cancelled
+	function isDrawCancelled(uint _drawNumber) public view returns (bool){
+		return cancelledDraws[_drawNumber];
+	}
+
+	// pause or unpause the participations, the workflow is not impacted
+	function _setPaused(bool _paused) private {
+		if (isPaused() == _paused){
+			// nothing to do
+			return;
+		}
+		// save the flag in the kv store
+		kvStore[PAUSED] = abi.encode(_paused);
+		// emit the event
+		if (_paused){
+			emit Paused(registrationContractId);
+		} else {
+			emit Unpaused(registrationContractId);
+		}
+	}
+
+	bytes public constant PAUSED = "_paused";
+
+	// return true if the participations are paused
+	function isPaused() public view returns (bool){
+		bytes memory _paused = kvStore[PAUSED];
+		if (_paused.length == 0){
+			return false;
+		}
+		return abi.decode(_paused, (bool));
+	}
+
 	// return true if the users can participate (ie register their numbers)
 	function canParticipate() public view returns (bool){
-		return getStatus() == Status.RegistrationsOpen;
+		return !isPaused() && getStatus() == Status.RegistrationsOpen;
+	}
+
+	// set the ERC-20 token used to pay the tickets, address(0) for the native token
+	// the token can be changed only before the workflow starts
+	function setPaymentToken(address _token) external onlyRole(DEFAULT_ADMIN_ROLE) {
+		require(getStatus() == Status.NotStarted, "Incorrect Status");
+		paymentToken = IERC20(_token);
+		emit PaymentTokenUpdated(_token);
+	}
+
+	// set the maximum number of tickets per account and per draw, 0 for no limit
+	// the limit cannot be changed while a draw is in progress, all the participants of a draw must have the same limit
+	function setMaxTicketsPerAccount(uint _maxTickets) external onlyRole(DEFAULT_ADMIN_ROLE) {
+		Status _status = getStatus();
+		require(
+			_status != Status.RegistrationsOpen && _status != Status.RegistrationsClosed && _status != Status.SaltGenerated,
+			"Incorrect Status"
+		);
+		maxTicketsPerAccount = _maxTickets;
+		emit MaxTicketsPerAccountUpdated(_maxTickets);
+	}
+
+	// return the number of tickets the given account can still register for the current draw
+	// (max uint if there is no limit)
+	function getRemainingTickets(address _account) public view returns (uint){
+		if (maxTicketsPerAccount == 0){
+			return type(uint).max;
+		}
+		uint _nbTickets = ticketsPerAccount[getDrawNumber()][_account];
+		if (_nbTickets >= maxTicketsPerAccount){
+			return 0;
+		}
+		return maxTicketsPerAccount - _nbTickets;
 	}
 
 	// participate, ie  register their numbers
-	function participate(uint[] memory _numbers) external {
+	// the value sent is split between the jackpot, the treasury and the reserve
+	function participate(uint[] memory _numbers) external payable {
+		// the tickets are paid with the native token
+		require(address(paymentToken) == address(0), "Incorrect payment asset");
+		_checkAmount(1, msg.value);
+		_participate(msg.sender, _numbers);
+		_addRevenue(msg.sender, msg.value);
+	}
+
+	// participate with the given number of tickets, the numbers are generated by the contract
+	// the value sent is split between the jackpot, the treasury and the reserve
+	function participateQuickPick(uint _count) external payable {
+		// the tickets are paid with the native token
+		require(address(paymentToken) == address(0), "Incorrect payment asset");
+		_checkAmount(_count, msg.value);
+		for (uint i = 0; i < _count; i++){
+			// generate the numbers from the block, the caller and the nonce
+			bytes32 _seed = keccak256(abi.encode(block.prevrandao, block.timestamp, msg.sender, quickPickNonce++));
+			_participate(msg.sender, _quickPick(_seed));
+		}
+		_addRevenue(msg.sender, msg.value);
+	}
+
+	// participate and pay the ticket with the ERC-20 token, the allowance must be given before
+	function participateWithToken(uint[] memory _numbers, uint _amount) external {
+		_participateWithToken(_numbers, _amount);
+	}
+
+	// participate and pay the ticket with the ERC-20 token, the allowance is given with the permit (EIP-2612)
+	function participateWithPermit(uint[] memory _numbers, uint _amount, uint _deadline, uint8 _v, bytes32 _r, bytes32 _s) external {
+		require(address(paymentToken) != address(0), "Incorrect payment asset");
+		IERC20Permit(address(paymentToken)).permit(msg.sender, address(this), _amount, _deadline, _v, _r, _s);
+		_participateWithToken(_numbers, _amount);
+	}
+
+	function _participateWithToken(uint[] memory _numbers, uint _amount) private {
+		require(address(paymentToken) != address(0), "Incorrect payment asset");
+		_checkAmount(1, _amount);
+		_participate(msg.sender, _numbers);
+		// transfer the tokens to this contract
+		paymentToken.safeTransferFrom(msg.sender, address(this), _amount);
+		_addRevenue(msg.sender, _amount);
+	}
+
+	// participate with the numbers signed by the player (EIP-712), the participation is recorded for the signer
+	// the relayer pays the gas and the value sent is split between the jackpot, the treasury and the reserve
+	function participateWithSignature(address _participant, uint _drawNumber, uint[] memory _numbers, uint _nonce, uint _deadline, bytes calldata _signature) external payable {
+		// the tickets are paid with the native token
+		require(address(paymentToken) == address(0), "Incorrect payment asset");
+		_checkAmount(1, msg.value);
+		require(block.timestamp <= _deadline, "Signature expired");
+		// check the player is the signer
+		bytes32 _hash = MessageHashUtils.toTypedDataHash(
+			participationDomainSeparator(),
+			keccak256(abi.encode(PARTICIPATION_TYPEHASH, address(this), _drawNumber, keccak256(abi.encodePacked(_numbers)), _nonce, _deadline))
+		);
+		require(ECDSA.recover(_hash, _signature) == _participant, "Invalid signature");
+		// the request cannot be used again
+		require(participationNonces[_participant] == _nonce, "Incorrect nonce");
+		participationNonces[_participant] = _nonce + 1;
+		// check the draw number signed by the player
+		require(getDrawNumber() == _drawNumber, "Incorrect Draw Number");
+		_participate(_participant, _numbers);
+		_addRevenue(_participant, msg.value);
+	}
+
+	// EIP-712 domain separator used to sign the participations
+	function participationDomainSeparator() public view returns (bytes32) {
+		return keccak256(abi.encode(
+			EIP712_DOMAIN_TYPEHASH,
+			keccak256(bytes(PARTICIPATION_DOMAIN_NAME)),
+			keccak256(bytes(PARTICIPATION_DOMAIN_VERSION)),
+			block.chainid,
+			address(this)
+		));
+	}
+
+	function _participate(address _participant, uint[] memory _numbers) private {
+		// check if the participations are paused
+		require(!isPaused(), "Paused");
 		// check is the user can participate
 		require(canParticipate(), "Incorrect Status");
 		// check if the numbers are correct
 		_checkNumbers(_numbers);
+		// check the limit for this account
+		require(getRemainingTickets(_participant) > 0, "Max tickets reached");
+		_registerTicket(_participant, _numbers);
+	}
+
+	function _registerTicket(address _participant, uint[] memory _numbers) private {
+		// record the ticket for this account
+		ticketsPerAccount[getDrawNumber()][_participant] += 1;
+
+		// record the ticket for the participation threshold
+		_setNbTickets(getNbTickets() + 1);
 
 		// save the participation with an event
-		address _participant = msg.sender;
 		emit ParticipationRegistered(registrationContractId, getDrawNumber(), _participant, _numbers);
+
+		// mint the ticket as receipt
+		if (address(ticketNft) != address(0)){
+			// count the tickets with the same numbers to know the number of winning tickets
+			nbTicketsByNumbers[getDrawNumber()][_numbersKey(_numbers)] += 1;
+			ticketNft.mint(_participant, registrationContractId, getDrawNumber(), _numbers);
+		}
+	}
+
+	// set the ERC-721 contract used to mint a ticket for each participation, address(0) for no ticket
+	// this contract must be the owner of the ERC-721 contract
+	// the ticket contract cannot be changed once started, all the tickets must be counted and paid with the same contract
+	function setTicketNft(address _ticketNft) external onlyRole(DEFAULT_ADMIN_ROLE) {
+		require(getStatus() == Status.NotStarted, "Incorrect Status");
+		ticketNft = RaffleTicket(_ticketNft);
+		emit TicketNftUpdated(_ticketNft);
+	}
+
+	// return true if the ticket contains all the winning numbers of its draw
+	function isWinningTicket(uint _tokenId) public view returns (bool){
+		RaffleTicket.Ticket memory _ticket = ticketNft.getTicket(_tokenId);
+		uint[] memory _winningNumbers = winningNumbers[_ticket.drawNumber];
+		if (_ticket.registrationContractId != registrationContractId || _winningNumbers.length == 0){
+			return false;
+		}
+		for (uint i = 0; i < _winningNumbers.length; i++){
+			bool _found = false;
+			for (uint j = 0; j < _ticket.numbers.length; j++){
+				if (_ticket.numbers[j] == _winningNumbers[i]){
+					_found = true;
+					break;
+				}
+			}
+			if (!_found){
+				return false;
+			}
+		}
+		return true;
+	}
+
+	// pay the share of the jackpot to the holder of the winning ticket
+	// the ticket can be transferred before the payout, only the current holder is paid
+	function payWinningTicket(uint _tokenId) external onlyRole(PAYOUT_ROLE) {
+		require(isWinningTicket(_tokenId), "Not winning ticket");
+		require(!paidTickets[_tokenId], "Ticket already paid");
+		uint _drawNumber = ticketNft.getTicket(_tokenId).drawNumber;
+		// the share is deducted from the payout of the draw
+		DrawPayout storage _payout = drawPayouts[_drawNumber];
+		require(_payout.sharePerTicket > 0, "No payout");
+		uint _amount = _payout.sharePerTicket;
+		_payout.remaining -= _amount;
+		paidTickets[_tokenId] = true;
+		address _owner = ticketNft.ownerOf(_tokenId);
+		_transfer(_owner, _amount);
+		emit TicketPaid(_tokenId, _drawNumber, _owner, _amount);
+	}
+
+	// subscribe to play the same ticket for the next draws, starting from the next opening of the registrations
+	// the value sent is the prepaid amount for all the draws
+	function subscribe(uint[] memory _numbers, uint _nbDraws) external payable returns (uint) {
+		// the subscriptions are paid with the native token
+		require(address(paymentToken) == address(0), "Incorrect payment asset");
+		return _subscribe(_numbers, _nbDraws, msg.value);
+	}
+
+	// subscribe to play the same ticket for the next draws and pay with the ERC-20 token
+	// the allowance must be given before
+	function subscribeWithToken(uint[] memory _numbers, uint _nbDraws, uint _amount) external returns (uint) {
+		require(address(paymentToken) != address(0), "Incorrect payment asset");
+		uint _subscriptionId = _subscribe(_numbers, _nbDraws, _amount);
+		// transfer the tokens to this contract
+		paymentToken.safeTransferFrom(msg.sender, address(this), _amount);
+		return _subscriptionId;
+	}
+
+	function _subscribe(uint[] memory _numbers, uint _nbDraws, uint _prepaid) private returns (uint) {
+		// check if the numbers are correct
+		_checkNumbers(_numbers);
+		require(_nbDraws > 0, "Incorrect nb draws");
+		// the ticket is prepaid for all the draws
+		_checkAmount(_nbDraws, _prepaid);
+		require(nbSubscriptions[msg.sender] < MAX_SUBSCRIPTIONS_PER_ACCOUNT, "Too many subscriptions");
+		nbSubscriptions[msg.sender] += 1;
+		// save the subscription
+		uint _subscriptionId = nextSubscriptionId++;
+		// the subscription is played from the next draw
+		subscriptions[_subscriptionId] = Subscription(msg.sender, _numbers, _nbDraws, _prepaid, getDrawNumber());
+		activeSubscriptionIndexes[_subscriptionId] = activeSubscriptions.length;
+		activeSubscriptions.push(_subscriptionId);
+		// emit the event
+		emit SubscriptionCreated(_subscriptionId, msg.sender, _numbers, _nbDraws, _prepaid);
+		return _subscriptionId;
+	}
+
+	// cancel the subscription and refund the amount paid for the remaining draws
+	function cancelSubscription(uint _subscriptionId) external {
+		Subscription memory _subscription = subscriptions[_subscriptionId];
+		require(_subscription.owner != address(0), "Unknown subscription");
+		require(_subscription.owner == msg.sender, "Not subscription owner");
+		_removeSubscription(_subscriptionId);
+		// refund the remaining draws
+		_transfer(msg.sender, _subscription.prepaid);
+		// emit the event
+		emit SubscriptionCancelled(_subscriptionId, msg.sender, _subscription.prepaid);
+	}
+
+	// return the subscription (owner is address(0) if the subscription doesn't exist)
+	function getSubscription(uint _subscriptionId) public view returns (Subscription memory){
+		return subscriptions[_subscriptionId];
+	}
+
+	// return the ids of the subscriptions with remaining draws
+	function getActiveSubscriptions() public view returns (uint[] memory){
+		return activeSubscriptions;
+	}
+
+	// return the ids of the next subscriptions not played yet for the current draw,
+	// ie the subscriptions played by the next call of playSubscriptions
+	function getSubscriptionsToPlay() public view returns (uint[] memory){
+		uint _drawNumber = getDrawNumber();
+		uint _to = nextSubscriptionToPlay + MAX_SUBSCRIPTIONS_BATCH;
+		if (_to > activeSubscriptions.length){
+			_to = activeSubscriptions.length;
+		}
+		uint _nb = 0;
+		for (uint i = nextSubscriptionToPlay; i < _to; i++){
+			if (subscriptions[activeSubscriptions[i]].lastPlayedDraw < _drawNumber){
+				_nb++;
+			}
+		}
+		uint[] memory _ids = new uint[](_nb);
+		uint j = 0;
+		for (uint i = nextSubscriptionToPlay; i < _to; i++){
+			if (subscriptions[activeSubscriptions[i]].lastPlayedDraw < _drawNumber){
+				_ids[j++] = activeSubscriptions[i];
+			}
+		}
+		return _ids;
+	}
+
+	// register a participation for the next subscriptions not played yet for the current draw,
+	// at most _max subscriptions (capped to MAX_SUBSCRIPTIONS_BATCH) are played
+	// anyone can call it while the registrations are open, return the number of subscriptions handled
+	// the participations are emitted like the other ones to be included in the winners lookup
+	// a subscription that cannot be played for this draw (ie: the numbers don't match the new config) is kept for the next draw
+	function playSubscriptions(uint _max) external returns (uint) {
+		// check if the participations are paused
+		require(!isPaused(), "Paused");
+		// check if the registrations are open
+		require(canParticipate(), "Incorrect Status");
+		if (_max > MAX_SUBSCRIPTIONS_BATCH){
+			_max = MAX_SUBSCRIPTIONS_BATCH;
+		}
+		uint _drawNumber = getDrawNumber();
+		uint _nb = 0;
+		while (nextSubscriptionToPlay < activeSubscriptions.length && _nb < _max){
+			uint _subscriptionId = activeSubscriptions[nextSubscriptionToPlay];
+			Subscription storage _subscription = subscriptions[_subscriptionId];
+			_nb++;
+			// the subscriptions created during this draw are played from the next one
+			if (_subscription.lastPlayedDraw >= _drawNumber){
+				nextSubscriptionToPlay++;
+				continue;
+			}
+			_subscription.lastPlayedDraw = _drawNumber;
+			if (!_isValidNumbers(_subscription.numbers) || getRemainingTickets(_subscription.owner) == 0){
+				nextSubscriptionToPlay++;
+				continue;
+			}
+			_registerTicket(_subscription.owner, _subscription.numbers);
+			// the prepaid amount is consumed draw by draw
+			uint _amount = _subscription.prepaid / _subscription.remainingDraws;
+			_subscription.prepaid -= _amount;
+			_subscription.remainingDraws -= 1;
+			_addRevenue(_subscription.owner, _amount);
+			if (_subscription.remainingDraws == 0){
+				// the last id is moved at this index
+				_removeSubscription(_subscriptionId);
+			} else {
+				nextSubscriptionToPlay++;
+			}
+		}
+		return _nb;
+	}
+
+	// remove the subscription from the active ones
+	// the subscriptions already played for the current draw are kept before nextSubscriptionToPlay
+	function _removeSubscription(uint _subscriptionId) private {
+		nbSubscriptions[subscriptions[_subscriptionId].owner] -= 1;
+		delete subscriptions[_subscriptionId];
+		uint _index = activeSubscriptionIndexes[_subscriptionId];
+		delete activeSubscriptionIndexes[_subscriptionId];
+		if (_index < nextSubscriptionToPlay){
+			// the last subscription played takes this index
+			nextSubscriptionToPlay--;
+			_moveActiveSubscription(nextSubscriptionToPlay, _index);
+			_index = nextSubscriptionToPlay;
+		}
+		// the last subscription takes this index
+		_moveActiveSubscription(activeSubscriptions.length - 1, _index);
+		activeSubscriptions.pop();
+	}
+
+	function _moveActiveSubscription(uint _from, uint _to) private {
+		if (_from == _to){
+			return;
+		}
+		uint _subscriptionId = activeSubscriptions[_from];
+		activeSubscriptions[_to] = _subscriptionId;
+		activeSubscriptionIndexes[_subscriptionId] = _to;
+	}
+
+	function _addRevenue(address _participant, uint _amount) private {
+		// record the amount paid by the participant, refunded if the draw is cancelled
+		stakes[getDrawNumber()][_participant] += _amount;
+		// split the amount paid, the rounding goes to the treasury
+		uint _jackpotPart = _amount * jackpotShare / 100;
+		uint _reservePart = _amount * reserveShare / 100;
+		uint _treasuryPart = _amount - _jackpotPart - _reservePart;
+		_setRevenue(getRevenue() + _jackpotPart);
+		reserve += _reservePart;
+		treasury += _treasuryPart;
+		// keep the parts of the draw in progress to refund them if the draw is cancelled
+		drawJackpotPart += _jackpotPart;
+		drawTreasuryPart += _treasuryPart;
+		drawReservePart += _reservePart;
+	}
+
+	// withdraw the given amount from the treasury, the jackpot and the reserve are never touched
+	// the part of the draw in progress is kept until the draw is settled because it is refunded if the draw is cancelled
+	function withdrawTreasury(uint _amount) external onlyRole(TREASURY_ROLE) {
+		require(_amount <= treasury - drawTreasuryPart, "Insufficient treasury");
+		treasury -= _amount;
+		_transfer(msg.sender, _amount);
+	}
+
+	// transfer the given amount with the token used to pay the tickets
+	function _transfer(address _to, uint _amount) private {
+		if (address(paymentToken) != address(0)){
+			paymentToken.safeTransfer(_to, _amount);
+			return;
+		}
+		(bool _sent, ) = payable(_to).call{value: _amount}("");
+		require(_sent, "Transfer failed");
+	}
+
+	bytes public constant NB_TICKETS = "_nbTickets";
+
+	// return the number of tickets recorded for the current draw
+	function getNbTickets() public view returns (uint){
+		bytes memory _nbTickets = kvStore[NB_TICKETS];
+		if (_nbTickets.length == 0){
+			return 0;
+		}
+		return abi.decode(_nbTickets, (uint));
+	}
+
+	function _setNbTickets(uint _nbTickets) private {
+		// save the number of tickets in the kv store
+		kvStore[NB_TICKETS] = abi.encode(_nbTickets);
+	}
+
+	bytes public constant REVENUE = "_revenue";
+
+	// return the amount paid for the tickets of the current draw
+	function getRevenue() public view returns (uint){
+		bytes memory _revenue = kvStore[REVENUE];
+		if (_revenue.length == 0){
+			return 0;
+		}
+		return abi.decode(_revenue, (uint));
+	}
+
+	function _setRevenue(uint _revenue) private {
+		// save the revenue in the kv store
+		kvStore[REVENUE] = abi.encode(_revenue);
 	}
 
 	bytes public constant STATUS = "_status";
@@ -146,7 +855,21 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 		grantRole(PhatRollupAnchor.ATTESTOR_ROLE, _attestor);
 	}
 
-	enum RequestType {SET_CONFIG_AND_START, OPEN_REGISTRATIONS, CLOSE_REGISTRATIONS, GENERATE_SALT, SET_RESULTS}
+	enum RequestType {SET_CONFIG_AND_START, OPEN_REGISTRATIONS, CLOSE_REGISTRATIONS, GENERATE_SALT, SET_RESULTS, STOP, CANCEL_DRAW, SET_PAUSED, UPDATE_CONFIG}
+
+	function _setConfigAndStart(bytes memory _request) private {
+		(uint8 _nbNumbers, uint _minNumber, uint _maxNumber, uint _registrationContractId, uint8 _jackpotShare, uint8 _treasuryShare, uint8 _reserveShare, uint _ticketPrice)
+			= abi.decode(_request, (uint8, uint, uint, uint, uint8, uint8, uint8, uint));
+		// save the config
+		_setConfig(_nbNumbers, _minNumber, _maxNumber);
+		_saveConfig();
+		// save the revenue split
+		_setRevenueSplit(_jackpotShare, _treasuryShare, _reserveShare);
+		// save the ticket price
+		_setTicketPrice(_ticketPrice);
+		// start the workflow
+		_start(_registrationContractId);
+	}
 
 	function _onMessageReceived(bytes calldata _action) internal override {
 
@@ -157,15 +880,16 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 		||  _requestType == RequestType.OPEN_REGISTRATIONS
 		||  _requestType == RequestType.CLOSE_REGISTRATIONS
 			||  _requestType == RequestType.GENERATE_SALT
-		||  _requestType == RequestType.SET_RESULTS,
+		||  _requestType == RequestType.SET_RESULTS
+		||  _requestType == RequestType.STOP
+		||  _requestType == RequestType.CANCEL_DRAW
+		||  _requestType == RequestType.SET_PAUSED
+		||  _requestType == RequestType.UPDATE_CONFIG,
 		"cannot parse action");
 
 		if (_requestType == RequestType.SET_CONFIG_AND_START){
-			(uint8 _nbNumbers, uint _minNumber, uint _maxNumber, uint _registrationContractId) = abi.decode(_request, (uint8, uint, uint, uint));
-			// save the config
-			_setConfig(_nbNumbers, _minNumber, _maxNumber);
-			// start the workflow
-			_start(_registrationContractId);
+			// set the config and start the workflow
+			_setConfigAndStart(_request);
 		} else if (_requestType == RequestType.OPEN_REGISTRATIONS){
 
 			(uint _drawNumber) = abi.decode(_request, (uint));
@@ -185,6 +909,22 @@ contract RaffleRegistration is Config, Ownable, AccessControl, PhatRollupAnchor
 			_checkNumbers(_numbers);
 			// set the results
 			_saveResults(_drawNumber, _numbers, _hasWinner);
+		} else if (_requestType == RequestType.STOP){
+			(uint _drawNumber) = abi.decode(_request, (uint));
+			// stop the workflow
+			_stop(_drawNumber);
+		} else if (_requestType == RequestType.CANCEL_DRAW){
+			(uint _drawNumber) = abi.decode(_request, (uint));
+			// cancel the draw
+			_cancelDraw(_drawNumber);
+		} else if (_requestType == RequestType.SET_PAUSED){
+			(bool _paused) = abi.decode(_request, (bool));
+			// pause or unpause the participations
+			_setPaused(_paused);
+		} else if (_requestType == RequestType.UPDATE_CONFIG){
+			(uint8 _nbNumbers, uint _minNumber, uint _maxNumber) = abi.decode(_request, (uint8, uint, uint));
+			// update the config
+			_updateConfig(_nbNumbers, _minNumber, _maxNumber);
 		}
 
 	}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts/access/Ownable.sol";
+import "@openzeppelin/contracts/token/ERC721/ERC721.sol";
+
+// ERC-721 token minted as receipt for each participation, the owner is the registration contract
+contract RaffleTicket is ERC721, Ownable {
+
+	// data encoded in the ticket
+	struct Ticket {
+		uint registrationContractId;
+		uint drawNumber;
+		uint[] numbers;
+	}
+
+	// tickets by token id
+	mapping(uint => Ticket) private tickets;
+
+	// id of the next ticket
+	uint public nextTokenId;
+
+	constructor(address _registrationContract)
+		ERC721("Lotto Ticket", "LOTTO")
+		Ownable(_registrationContract)
+	{}
+
+	// mint a ticket for the given participation and return the token id
+	function mint(address _to, uint _registrationContractId, uint _drawNumber, uint[] memory _numbers) external onlyOwner returns (uint) {
+		uint _tokenId = nextTokenId++;
+		tickets[_tokenId] = Ticket(_registrationContractId, _drawNumber, _numbers);
+		_mint(_to, _tokenId);
+		return _tokenId;
+	}
+
+	// return the data encoded in the ticket
+	function getTicket(uint _tokenId) public view returns (Ticket memory){
+		_requireOwned(_tokenId);
+		return tickets[_tokenId];
+	}
+
+}
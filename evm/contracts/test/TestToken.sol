@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/extensions/ERC20Permit.sol";
+
+// ERC-20 token with permit, only used in the tests
+contract TestToken is ERC20, ERC20Permit {
+
+    constructor() ERC20("Test Token", "TT") ERC20Permit("Test Token") {
+        _mint(msg.sender, 1_000_000 * 10 ** decimals());
+    }
+
+}
@@ -52,6 +52,13 @@ task("cancel", "Cancel pending transactions", async (_taskArgs, hre) => {
 const config: HardhatUserConfig = {
   solidity: {
     version:  "0.8.24",
+    settings: {
+      // keep the registration contract under the contract size limit
+      optimizer: {
+        enabled: true,
+        runs: 200,
+      },
+    },
   },
   ignition: {
     disableFeeBumping: false,
@@ -10,8 +10,9 @@ async function main() {
   const lottoInstance = await ethers.getContractAt("RaffleRegistration", contractAddress);
 
   const request_bytes = abiCoder.encode(
-    ['uint8', 'uint', 'uint', 'uint'],
-    [4, 1, 50, registrationContractId]
+    // nb numbers, min, max, registration contract id, revenue split (jackpot, treasury, reserve), ticket price
+    ['uint8', 'uint', 'uint', 'uint', 'uint8', 'uint8', 'uint8', 'uint'],
+    [4, 1, 50, registrationContractId, 100, 0, 0, ethers.parseEther("0.001")]
   );
   const action = abiCoder.encode(
     ['uint8', 'bytes'],
@@ -11,8 +11,10 @@ async function main() {
 
   const lottoInstance = await ethers.getContractAt("RaffleRegistration", contractAddress);
 
-  await lottoInstance.connect(owner).participate([2, 17, 31, 45]);
-  await lottoInstance.connect(owner).participate([15, 30, 28, 49]);
+  // the value must match the ticket price
+  const ticketPrice = await lottoInstance.ticketPrice();
+  await lottoInstance.connect(owner).participate([2, 17, 31, 45], {value: ticketPrice});
+  await lottoInstance.connect(owner).participate([15, 30, 28, 49], {value: ticketPrice});
 
   console.log("Participation done");
 }
@@ -11,8 +11,17 @@ export function hex(str: string): string {
   return ethers.hexlify(ethers.toUtf8Bytes(str));
 }
 
+// family of the chain encoded in the registration contract id
+export enum ChainFamily { Legacy, Substrate, Evm }
+
+// registration contract id: chain family (8 bits), reserved (24 bits), chain id (64 bits), local index (32 bits)
+export function shardedContractId(chainFamily: ChainFamily, chainId: bigint, index: bigint): bigint {
+  return (BigInt(chainFamily) << 120n) | (chainId << 32n) | index;
+}
+
 export const contractAddress = "0x29621E6F2b7DBf256Ff0028dc04986C5E14Db50c";
-export const registrationContractId = 31;
+// contract deployed on moonbase (chain id 1287)
+export const registrationContractId = shardedContractId(ChainFamily.Evm, 1287n, 31n);
 export const drawNumber = 1;
 
 export const phatAttestorAddress = "0x01e38f9e010ea0ad5808531f2722e2985f79a7c3";
@@ -6,13 +6,14 @@ import {Signer} from "ethers";
 
 
 // workflow status
-enum Status { NotStarted, Started, RegistrationsOpen, RegistrationsClosed, SaltGenerated, ResultsReceived }
+enum Status { NotStarted, Started, RegistrationsOpen, RegistrationsClosed, SaltGenerated, ResultsReceived, Closed, DrawCancelled }
 // request type
-enum RequestType {SET_CONFIG_AND_START, OPEN_REGISTRATIONS, CLOSE_REGISTRATIONS, GENERATE_SALT, SET_RESULTS}
+enum RequestType {SET_CONFIG_AND_START, OPEN_REGISTRATIONS, CLOSE_REGISTRATIONS, GENERATE_SALT, SET_RESULTS, STOP, CANCEL_DRAW, SET_PAUSED, UPDATE_CONFIG}
 
 const abiCoder = ethers.AbiCoder.defaultAbiCoder();
 
-const registrationContractId = 33;
+// registration contract id: chain family Evm (2), chain id of hardhat (31337), local index 33
+const registrationContractId = (2n << 120n) | (31337n << 32n) | 33n;
 
 describe('Test raffle life cycle', () => {
 
@@ -34,7 +35,11 @@ describe('Test raffle life cycle', () => {
       attestor : Signer,
       nbNumber: number,
       min: number,
-      max : number
+      max : number,
+      jackpotShare: number = 100,
+      treasuryShare: number = 0,
+      reserveShare: number = 0,
+      ticketPrice: number = 0
   ) {
 
     // preconditions
@@ -48,8 +53,8 @@ describe('Test raffle life cycle', () => {
     expect (await contract.canParticipate()).to.equal(false);
 
     const request_bytes = abiCoder.encode(
-        ['uint8', 'uint', 'uint', 'uint'],
-        [nbNumber, min, max, registrationContractId]
+        ['uint8', 'uint', 'uint', 'uint', 'uint8', 'uint8', 'uint8', 'uint'],
+        [nbNumber, min, max, registrationContractId, jackpotShare, treasuryShare, reserveShare, ticketPrice]
     );
     const action = abiCoder.encode(
         ['uint8', 'bytes'],
@@ -57,13 +62,19 @@ describe('Test raffle life cycle', () => {
     );
     const reply = '0x00' + action.substring(2);
     await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
-      .to.emit(contract, 'ConfigUpdated').withArgs(nbNumber, min, max);
+      .to.emit(contract, 'ConfigUpdated').withArgs(nbNumber, min, max)
+      .to.emit(contract, 'RevenueSplitUpdated').withArgs(jackpotShare, treasuryShare, reserveShare)
+      .to.emit(contract, 'TicketPriceUpdated').withArgs(ticketPrice);
 
     // check post conditions
     expect (await contract.nbNumbers()).to.equal(nbNumber);
     expect (await contract.minNumber()).to.equal(min);
     expect (await contract.maxNumber()).to.equal(max);
     expect (await contract.registrationContractId()).to.equal(registrationContractId);
+    expect (await contract.jackpotShare()).to.equal(jackpotShare);
+    expect (await contract.treasuryShare()).to.equal(treasuryShare);
+    expect (await contract.reserveShare()).to.equal(reserveShare);
+    expect (await contract.ticketPrice()).to.equal(ticketPrice);
     expect (await contract.getStatus()).to.equal(Status.Started);
     expect (await contract.getDrawNumber()).to.equal(0);
     expect (await contract.canParticipate()).to.equal(false);
@@ -200,6 +211,112 @@ describe('Test raffle life cycle', () => {
 
   }
 
+  async function stop(
+      contract: RaffleRegistration,
+      attestor : Signer,
+      drawNumber: number
+  ) {
+
+    const request_bytes = abiCoder.encode(
+        ['uint'],
+        [drawNumber]
+    );
+    const action = abiCoder.encode(
+        ['uint', 'bytes'],
+        [RequestType.STOP, request_bytes]
+    );
+    const reply = '0x00' + action.substring(2);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
+      .to.emit(contract, 'LottoClosed')
+      .withArgs(registrationContractId, drawNumber);
+
+    // check post conditions
+    expect (await contract.getStatus()).to.equal(Status.Closed);
+    expect (await contract.getDrawNumber()).to.equal(drawNumber);
+    expect (await contract.canParticipate()).to.equal(false);
+  }
+
+  async function cancelDraw(
+      contract: RaffleRegistration,
+      attestor : Signer,
+      drawNumber: number
+  ) {
+
+    const request_bytes = abiCoder.encode(
+        ['uint'],
+        [drawNumber]
+    );
+    const action = abiCoder.encode(
+        ['uint', 'bytes'],
+        [RequestType.CANCEL_DRAW, request_bytes]
+    );
+    const reply = '0x00' + action.substring(2);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
+      .to.emit(contract, 'DrawCancelled')
+      .withArgs(registrationContractId, drawNumber);
+
+    // check post conditions
+    expect (await contract.getStatus()).to.equal(Status.DrawCancelled);
+    expect (await contract.getDrawNumber()).to.equal(drawNumber);
+    expect (await contract.isDrawCancelled(drawNumber)).to.equal(true);
+    expect (await contract.canParticipate()).to.equal(false);
+  }
+
+  async function setPaused(
+      contract: RaffleRegistration,
+      attestor : Signer,
+      paused: boolean
+  ) {
+
+    const request_bytes = abiCoder.encode(
+        ['bool'],
+        [paused]
+    );
+    const action = abiCoder.encode(
+        ['uint', 'bytes'],
+        [RequestType.SET_PAUSED, request_bytes]
+    );
+    const reply = '0x00' + action.substring(2);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
+      .to.emit(contract, paused ? 'Paused' : 'Unpaused')
+      .withArgs(registrationContractId);
+
+    // check post conditions
+    expect (await contract.isPaused()).to.equal(paused);
+  }
+
+  function encodeUpdateConfig(nbNumber: number, min: number, max: number) : string {
+    const request_bytes = abiCoder.encode(
+        ['uint8', 'uint', 'uint'],
+        [nbNumber, min, max]
+    );
+    const action = abiCoder.encode(
+        ['uint', 'bytes'],
+        [RequestType.UPDATE_CONFIG, request_bytes]
+    );
+    return '0x00' + action.substring(2);
+  }
+
+  async function updateConfig(
+      contract: RaffleRegistration,
+      attestor : Signer,
+      nbNumber: number,
+      min: number,
+      max: number
+  ) {
+    const reply = encodeUpdateConfig(nbNumber, min, max);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
+      .to.emit(contract, 'ConfigUpdated')
+      .withArgs(nbNumber, min, max);
+
+    // check post conditions
+    expect (await contract.nbNumbers()).to.equal(nbNumber);
+    expect (await contract.minNumber()).to.equal(min);
+    expect (await contract.maxNumber()).to.equal(max);
+    expect (await contract.getStorage(ethers.toUtf8Bytes("_config")))
+      .to.equal(abiCoder.encode(['uint8', 'uint', 'uint'], [nbNumber, min, max]));
+  }
+
   async function deployContractFixture(){
     const [owner, attestor, addr1, addr2] = await ethers.getSigners();
 
@@ -235,6 +352,18 @@ describe('Test raffle life cycle', () => {
     return {contract, owner, attestor, addr1, addr2};
   }
 
+  async function openRegistrationsWithPriceFixture(){
+    const {contract, owner, attestor, addr1, addr2} = await deployContractFixture();
+
+    // config and start the raffle, a ticket costs 100
+    await setConfigAndStart(contract, attestor, 4, 1, 50, 100, 0, 0, 100);
+
+    // open the registrations for the draw number 11
+    await openRegistrations(contract, attestor, 11);
+
+    return {contract, owner, attestor, addr1, addr2};
+  }
+
 
   it('participate', async () => {
     const {contract, owner, attestor, addr1, addr2} = await loadFixture(openRegistrationsFixture);
@@ -297,6 +426,506 @@ describe('Test raffle life cycle', () => {
 
   });
 
+  it('Stop after the results', async () => {
+    const {contract, attestor, addr1} = await loadFixture(openRegistrationsFixture);
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // stop the lotto
+    await stop(contract, attestor, 11);
+    expect (await contract.isDrawCancelled(11)).to.equal(false);
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Incorrect Status');
+  });
+
+  it('Stop when the registrations are open', async () => {
+    const {contract, attestor, addr1} = await loadFixture(openRegistrationsFixture);
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).not.to.be.reverted;
+
+    // stop the lotto, the current draw is cancelled
+    await stop(contract, attestor, 11);
+    expect (await contract.isDrawCancelled(11)).to.equal(true);
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Incorrect Status');
+  });
+
+  it('Cancel the draw and open the registrations for a new draw', async () => {
+    const {contract, attestor, addr1} = await loadFixture(openRegistrationsFixture);
+
+    await closeRegistrations(contract, attestor, 11);
+    await generateSalt(contract, attestor, 11);
+
+    // cancel the draw
+    await cancelDraw(contract, attestor, 11);
+
+    // the results cannot be saved for a cancelled draw
+    await setResultsMustBeReverted(contract, attestor, 11, [33, 47, 5, 6], false);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Incorrect Status');
+
+    // open the registrations for the next draw
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.isDrawCancelled(12)).to.equal(false);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).not.to.be.reverted;
+  });
+
+  it('Refund the tickets of a cancelled draw', async () => {
+    const {contract, attestor, addr1, addr2} = await loadFixture(openRegistrationsWithPriceFixture);
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 100})).not.to.be.reverted;
+    await expect(contract.connect(addr1).participateQuickPick(2, {value: 200})).not.to.be.reverted;
+    expect (await contract.stakes(11, addr1.address)).to.equal(300);
+
+    // the draw is not cancelled
+    await expect(contract.connect(addr1).claimRefund(11)).to.be.revertedWith('Draw not cancelled');
+
+    await closeRegistrations(contract, attestor, 11);
+    await cancelDraw(contract, attestor, 11);
+
+    // the amounts paid for the draw are taken back
+    expect (await contract.getRevenue()).to.equal(0);
+    expect (await contract.treasury()).to.equal(0);
+    expect (await contract.reserve()).to.equal(0);
+
+    await expect(contract.connect(addr2).claimRefund(11)).to.be.revertedWith('Nothing to refund');
+    await expect(contract.connect(addr1).claimRefund(11))
+      .to.emit(contract, 'RefundClaimed').withArgs(11, addr1.address, 300)
+      .and.to.changeEtherBalances([contract, addr1], [-300, 300]);
+    await expect(contract.connect(addr1).claimRefund(11)).to.be.revertedWith('Nothing to refund');
+    expect (await ethers.provider.getBalance(await contract.getAddress())).to.equal(0);
+  });
+
+  it('Pause and unpause the participations', async () => {
+    const {contract, attestor, addr1} = await loadFixture(openRegistrationsFixture);
+
+    expect (await contract.isPaused()).to.equal(false);
+
+    // pause the participations
+    await setPaused(contract, attestor, true);
+    expect (await contract.canParticipate()).to.equal(false);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Paused');
+
+    // unpause the participations
+    await setPaused(contract, attestor, false);
+    expect (await contract.canParticipate()).to.equal(true);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).not.to.be.reverted;
+
+    // the workflow is not impacted
+    await setPaused(contract, attestor, true);
+    const request_bytes = abiCoder.encode(['uint'], [11]);
+    const action = abiCoder.encode(['uint', 'bytes'], [RequestType.CLOSE_REGISTRATIONS, request_bytes]);
+    const reply = '0x00' + action.substring(2);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply]))
+      .to.emit(contract, 'RegistrationsClosed')
+      .withArgs(registrationContractId, 11);
+    expect (await contract.getStatus()).to.equal(Status.RegistrationsClosed);
+  });
+
+  it('Count the tickets and extend the registrations for the same draw', async () => {
+    const {contract, attestor, addr1, addr2} = await loadFixture(openRegistrationsFixture);
+
+    expect (await contract.getNbTickets()).to.equal(0);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).not.to.be.reverted;
+    await expect(contract.connect(addr2).participate([1, 2, 3, 50])).not.to.be.reverted;
+    expect (await contract.getNbTickets()).to.equal(2);
+
+    await closeRegistrations(contract, attestor, 11);
+
+    // the registrations cannot be open again for another draw
+    const request_bytes = abiCoder.encode(['uint'], [12]);
+    const action = abiCoder.encode(['uint', 'bytes'], [RequestType.OPEN_REGISTRATIONS, request_bytes]);
+    const reply = '0x00' + action.substring(2);
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [reply])).to.be.reverted;
+
+    // the registrations are extended, the tickets are kept
+    await openRegistrations(contract, attestor, 11);
+    expect (await contract.getNbTickets()).to.equal(2);
+    await expect(contract.connect(addr1).participate([10, 20, 30, 50])).not.to.be.reverted;
+    expect (await contract.getNbTickets()).to.equal(3);
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // new draw, the tickets are reset
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.getNbTickets()).to.equal(0);
+  });
+
+  it('Record the revenue of the tickets for the jackpot', async () => {
+    const {contract, attestor, addr1, addr2} = await loadFixture(openRegistrationsWithPriceFixture);
+
+    expect (await contract.getRevenue()).to.equal(0);
+    // the value must match the ticket price
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 50})).to.be.revertedWith('Incorrect amount');
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Incorrect amount');
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 100})).not.to.be.reverted;
+    await expect(contract.connect(addr2).participate([1, 2, 3, 50], {value: 100})).not.to.be.reverted;
+    expect (await contract.getRevenue()).to.equal(200);
+    expect (await ethers.provider.getBalance(await contract.getAddress())).to.equal(200);
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // new draw, the revenue is reset
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.getRevenue()).to.equal(0);
+  });
+
+  it('Split the revenue between the jackpot, the treasury and the reserve', async () => {
+    const {contract, owner, attestor, addr1, addr2} = await loadFixture(deployContractFixture);
+
+    await setConfigAndStart(contract, attestor, 4, 1, 50, 70, 20, 10, 1015);
+    await openRegistrations(contract, attestor, 11);
+
+    // the rounding goes to the treasury
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 1015})).not.to.be.reverted;
+    expect (await contract.getRevenue()).to.equal(710);
+    expect (await contract.treasury()).to.equal(204);
+    expect (await contract.reserve()).to.equal(101);
+
+    // only the treasury role can withdraw
+    const TREASURY_ROLE = await contract.TREASURY_ROLE.staticCall();
+    await expect(contract.connect(owner).withdrawTreasury(100)).to.be.reverted;
+    await contract.connect(owner).grantRole(TREASURY_ROLE, addr1);
+    // the treasury of the draw in progress cannot be withdrawn
+    await expect(contract.connect(addr1).withdrawTreasury(1)).to.be.revertedWith('Insufficient treasury');
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // only the treasury can be withdrawn
+    await expect(contract.connect(addr1).withdrawTreasury(205)).to.be.revertedWith('Insufficient treasury');
+    await expect(contract.connect(addr1).withdrawTreasury(200)).to.changeEtherBalance(addr1, 200);
+    expect (await contract.treasury()).to.equal(4);
+    expect (await ethers.provider.getBalance(await contract.getAddress())).to.equal(815);
+
+    // new draw, the jackpot is seeded with the reserve
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.getRevenue()).to.equal(101);
+    expect (await contract.reserve()).to.equal(0);
+  });
+
+  it('Pay the tickets with an ERC-20 token', async () => {
+    const {contract, owner, attestor, addr1, addr2} = await loadFixture(deployContractFixture);
+
+    const token = await ethers.deployContract("TestToken");
+    await token.transfer(addr1, 1000);
+    await token.transfer(addr2, 1000);
+
+    // only the admin can set the token
+    await expect(contract.connect(addr1).setPaymentToken(await token.getAddress())).to.be.reverted;
+    await expect(contract.connect(owner).setPaymentToken(await token.getAddress()))
+      .to.emit(contract, 'PaymentTokenUpdated').withArgs(await token.getAddress());
+
+    await setConfigAndStart(contract, attestor, 4, 1, 50, 70, 20, 10, 100);
+    await openRegistrations(contract, attestor, 11);
+
+    // the token cannot be changed once started
+    await expect(contract.connect(owner).setPaymentToken(ethers.ZeroAddress)).to.be.revertedWith('Incorrect Status');
+    // the native token is not accepted anymore
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 100})).to.be.revertedWith('Incorrect payment asset');
+
+    // pay with the allowance
+    await expect(contract.connect(addr1).participateWithToken([1, 2, 3, 50], 100)).to.be.reverted;
+    await token.connect(addr1).approve(await contract.getAddress(), 100);
+    // the amount must match the ticket price
+    await expect(contract.connect(addr1).participateWithToken([1, 2, 3, 50], 0)).to.be.revertedWith('Incorrect amount');
+    await expect(contract.connect(addr1).participateWithToken([1, 2, 3, 50], 50)).to.be.revertedWith('Incorrect amount');
+    await expect(contract.connect(addr1).participateWithToken([1, 2, 3, 50], 100)).not.to.be.reverted;
+
+    // pay with the permit
+    const deadline = ethers.MaxUint256;
+    const signature = ethers.Signature.from(await addr2.signTypedData(
+      {
+        name: "Test Token",
+        version: "1",
+        chainId: (await ethers.provider.getNetwork()).chainId,
+        verifyingContract: await token.getAddress(),
+      },
+      {
+        Permit: [
+          {name: "owner", type: "address"},
+          {name: "spender", type: "address"},
+          {name: "value", type: "uint256"},
+          {name: "nonce", type: "uint256"},
+          {name: "deadline", type: "uint256"},
+        ],
+      },
+      {
+        owner: await addr2.getAddress(),
+        spender: await contract.getAddress(),
+        value: 100,
+        nonce: await token.nonces(addr2),
+        deadline: deadline,
+      }
+    ));
+    await expect(contract.connect(addr2).participateWithPermit([1, 2, 3, 50], 100, deadline, signature.v, signature.r, signature.s))
+      .not.to.be.reverted;
+
+    expect (await token.balanceOf(await contract.getAddress())).to.equal(200);
+    expect (await contract.getRevenue()).to.equal(140);
+    expect (await contract.treasury()).to.equal(40);
+    expect (await contract.reserve()).to.equal(20);
+
+    // the treasury is withdrawn in tokens
+    const TREASURY_ROLE = await contract.TREASURY_ROLE.staticCall();
+    await contract.connect(owner).grantRole(TREASURY_ROLE, owner);
+    await expect(contract.connect(owner).withdrawTreasury(40)).to.changeTokenBalance(token, owner, 40);
+    expect (await token.balanceOf(await contract.getAddress())).to.equal(160);
+  });
+
+  it('Limit the number of tickets per account and per draw', async () => {
+    const {contract, owner, attestor, addr1, addr2} = await loadFixture(deployContractFixture);
+
+    // no limit by default
+    expect (await contract.getRemainingTickets(addr1)).to.equal(ethers.MaxUint256);
+
+    // only the admin can set the limit
+    await expect(contract.connect(addr1).setMaxTicketsPerAccount(2)).to.be.reverted;
+    await expect(contract.connect(owner).setMaxTicketsPerAccount(2))
+      .to.emit(contract, 'MaxTicketsPerAccountUpdated').withArgs(2);
+    expect (await contract.getRemainingTickets(addr1)).to.equal(2);
+
+    // config and start the raffle and open the registrations for the draw number 11
+    await setConfigAndStart(contract, attestor, 4, 1, 50);
+    await openRegistrations(contract, attestor, 11);
+
+    // the limit cannot be changed while the draw is in progress
+    await expect(contract.connect(owner).setMaxTicketsPerAccount(3)).to.be.revertedWith('Incorrect Status');
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).not.to.be.reverted;
+    expect (await contract.getRemainingTickets(addr1)).to.equal(1);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 49])).not.to.be.reverted;
+    expect (await contract.getRemainingTickets(addr1)).to.equal(0);
+    await expect(contract.connect(addr1).participate([1, 2, 3, 48])).to.be.revertedWith('Max tickets reached');
+    // another account can still participate
+    await expect(contract.connect(addr2).participate([1, 2, 3, 50])).not.to.be.reverted;
+
+    await closeRegistrations(contract, attestor, 11);
+    await expect(contract.connect(owner).setMaxTicketsPerAccount(3)).to.be.revertedWith('Incorrect Status');
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // the draw is finished, the limit can be changed for the next one
+    await expect(contract.connect(owner).setMaxTicketsPerAccount(3))
+      .to.emit(contract, 'MaxTicketsPerAccountUpdated').withArgs(3);
+
+    // new draw, the tickets are counted again
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.getRemainingTickets(addr1)).to.equal(3);
+  });
+
+  it('Participate with a quick pick', async () => {
+    const {contract, addr1} = await loadFixture(openRegistrationsWithPriceFixture);
+
+    await expect(contract.connect(addr1).participateQuickPick(3, {value: 100})).to.be.revertedWith('Incorrect amount');
+    const tx = await contract.connect(addr1).participateQuickPick(3, {value: 300});
+    const receipt = await tx.wait();
+    const events = receipt.logs
+      .map(log => contract.interface.parseLog(log))
+      .filter(event => event?.name == 'ParticipationRegistered');
+    expect(events.length).to.equal(3);
+
+    for (const event of events){
+      const numbers : bigint[] = event.args.numbers;
+      // valid numbers without duplicate
+      expect(numbers.length).to.equal(4);
+      expect(new Set(numbers).size).to.equal(4);
+      for (const n of numbers){
+        expect(n >= 1n && n <= 50n).to.equal(true);
+      }
+    }
+    expect (await contract.getNbTickets()).to.equal(3);
+    expect (await contract.getRevenue()).to.equal(300);
+  });
+
+  it('Mint a ticket for each participation', async () => {
+    const {contract, owner, attestor, addr1, addr2} = await loadFixture(deployContractFixture);
+
+    const ticketNft = await ethers.deployContract("RaffleTicket", [await contract.getAddress()]);
+    // only the admin can set the ticket contract
+    await expect(contract.connect(addr1).setTicketNft(await ticketNft.getAddress())).to.be.reverted;
+    await expect(contract.connect(owner).setTicketNft(await ticketNft.getAddress()))
+      .to.emit(contract, 'TicketNftUpdated').withArgs(await ticketNft.getAddress());
+
+    // config and start the raffle, a ticket costs 100
+    await setConfigAndStart(contract, attestor, 4, 1, 50, 100, 0, 0, 100);
+    await openRegistrations(contract, attestor, 11);
+
+    // the ticket contract cannot be changed once started
+    await expect(contract.connect(owner).setTicketNft(ethers.ZeroAddress)).to.be.revertedWith('Incorrect Status');
+
+    // addr1 participates => a ticket is minted
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50], {value: 100}))
+      .to.emit(ticketNft, 'Transfer').withArgs(ethers.ZeroAddress, addr1.address, 0);
+    const ticket = await ticketNft.getTicket(0);
+    expect(ticket.registrationContractId).to.equal(registrationContractId);
+    expect(ticket.drawNumber).to.equal(11);
+    expect(ticket.numbers).to.deep.equal([1n, 2n, 3n, 50n]);
+
+    // only the registration contract can mint the tickets
+    await expect(ticketNft.connect(addr1).mint(addr1.address, registrationContractId, 11, [1, 2, 3, 50])).to.be.reverted;
+
+    // addr1 sells the ticket to addr2 before the draw
+    await ticketNft.connect(addr1).transferFrom(addr1.address, addr2.address, 0);
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [50, 3, 2, 1], true);
+    expect (await contract.isWinningTicket(0)).to.equal(true);
+
+    // the only winning ticket gets the whole jackpot
+    const payout = await contract.drawPayouts(11);
+    expect(payout.sharePerTicket).to.equal(100);
+    expect(payout.remaining).to.equal(100);
+    expect (await contract.carriedJackpot()).to.equal(0);
+
+    // only the payout role can pay the winning tickets
+    await expect(contract.connect(owner).payWinningTicket(0)).to.be.reverted;
+    await contract.connect(owner).grantRole(await contract.PAYOUT_ROLE(), owner.address);
+
+    // the holder (addr2) is paid
+    await expect(contract.connect(owner).payWinningTicket(0))
+      .to.emit(contract, 'TicketPaid').withArgs(0, 11, addr2.address, 100)
+      .and.to.changeEtherBalances([contract, addr2], [-100, 100]);
+    expect ((await contract.drawPayouts(11)).remaining).to.equal(0);
+    await expect(contract.connect(owner).payWinningTicket(0)).to.be.revertedWith('Ticket already paid');
+  });
+
+  it('Participate with a signature', async () => {
+    const {contract, owner, addr1, addr2} = await loadFixture(openRegistrationsWithPriceFixture);
+
+    // addr1 signs the participation
+    const nonce = await contract.participationNonces(addr1.address);
+    expect(nonce).to.equal(0);
+    const deadline = ethers.MaxUint256;
+    const domain = {
+      name: "RaffleRegistration",
+      version: "1",
+      chainId: (await ethers.provider.getNetwork()).chainId,
+      verifyingContract: await contract.getAddress(),
+    };
+    const types = {
+      Participation: [
+        {name: "registrationContract", type: "address"},
+        {name: "drawNumber", type: "uint256"},
+        {name: "numbers", type: "uint256[]"},
+        {name: "nonce", type: "uint256"},
+        {name: "deadline", type: "uint256"},
+      ],
+    };
+    const participation = {
+      registrationContract: await contract.getAddress(),
+      drawNumber: 11,
+      numbers: [1, 2, 3, 50],
+      nonce: nonce,
+      deadline: deadline,
+    };
+    expect(await contract.participationDomainSeparator()).to.equal(ethers.TypedDataEncoder.hashDomain(domain));
+    const signature = await addr1.signTypedData(domain, types, participation);
+
+    // the participation signed in the domain of the meta transactions is rejected
+    const metaTxSignature = await addr1.signTypedData(
+      {...domain, name: "PhatRollupMetaTxReceiver", version: "0.0.1"},
+      types,
+      participation
+    );
+    await expect(contract.connect(addr2).participateWithSignature(addr1.address, 11, [1, 2, 3, 50], nonce, deadline, metaTxSignature, {value: 100}))
+      .to.be.revertedWith('Invalid signature');
+
+    // the numbers cannot be changed by the relayer
+    await expect(contract.connect(addr2).participateWithSignature(addr1.address, 11, [1, 2, 3, 49], nonce, deadline, signature, {value: 100}))
+      .to.be.revertedWith('Invalid signature');
+
+    // addr2 submits the participation, it is recorded for addr1
+    await expect(contract.connect(addr2).participateWithSignature(addr1.address, 11, [1, 2, 3, 50], nonce, deadline, signature, {value: 100}))
+      .to.emit(contract, 'ParticipationRegistered')
+      .withArgs(registrationContractId, 11, addr1.address, [1, 2, 3, 50]);
+    expect (await contract.participationNonces(addr1.address)).to.equal(1);
+    expect (await contract.getNbTickets()).to.equal(1);
+    expect (await contract.getRevenue()).to.equal(100);
+
+    // the same signature cannot be used twice
+    await expect(contract.connect(owner).participateWithSignature(addr1.address, 11, [1, 2, 3, 50], nonce, deadline, signature, {value: 100}))
+      .to.be.revertedWith('Incorrect nonce');
+  });
+
+  it('Play the same ticket for several draws', async () => {
+    const {contract, attestor, addr1, addr2} = await loadFixture(openRegistrationsWithPriceFixture);
+
+    // addr1 subscribes for 3 draws
+    await expect(contract.connect(addr1).subscribe([1, 2, 3, 4], 3, {value: 300}))
+      .to.emit(contract, 'SubscriptionCreated')
+      .withArgs(0, addr1.address, [1, 2, 3, 4], 3, 300);
+    await expect(contract.connect(addr1).subscribe([1, 2, 3, 4], 0, {value: 300})).to.be.revertedWith('Incorrect nb draws');
+    // the ticket must be prepaid for all the draws
+    await expect(contract.connect(addr1).subscribe([1, 2, 3, 4], 3, {value: 200})).to.be.revertedWith('Incorrect amount');
+    await expect(contract.connect(addr1).subscribe([1, 2, 3, 4], 3)).to.be.revertedWith('Incorrect amount');
+
+    // addr2 subscribes for 2 draws and cancels the subscription
+    await contract.connect(addr2).subscribe([5, 6, 7, 8], 2, {value: 200});
+    await expect(contract.connect(addr1).cancelSubscription(1)).to.be.revertedWith('Not subscription owner');
+    await expect(contract.connect(addr2).cancelSubscription(1))
+      .to.emit(contract, 'SubscriptionCancelled')
+      .withArgs(1, addr2.address, 200)
+      .and.to.changeEtherBalances([contract, addr2], [-200, 200]);
+    expect (await contract.getActiveSubscriptions()).to.deep.equal([0n]);
+
+    // the subscription is played from the next draw
+    expect (await contract.getSubscriptionsToPlay()).to.deep.equal([]);
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+    await openRegistrations(contract, attestor, 12);
+    expect (await contract.getNbTickets()).to.equal(0);
+    expect (await contract.getSubscriptionsToPlay()).to.deep.equal([0n]);
+
+    // anyone can play the subscriptions by batch
+    await expect(contract.connect(addr2).playSubscriptions(10))
+      .to.emit(contract, 'ParticipationRegistered')
+      .withArgs(registrationContractId, 12, addr1.address, [1, 2, 3, 4]);
+    expect (await contract.getSubscriptionsToPlay()).to.deep.equal([]);
+    // the subscription is played only once by draw
+    await expect(contract.connect(addr2).playSubscriptions(10))
+      .not.to.emit(contract, 'ParticipationRegistered');
+
+    expect (await contract.getNbTickets()).to.equal(1);
+    expect (await contract.getRevenue()).to.equal(100);
+    const subscription = await contract.getSubscription(0);
+    expect (subscription.remainingDraws).to.equal(2);
+    expect (subscription.prepaid).to.equal(200);
+
+    // addr1 cancels the subscription => only the remaining draws are refunded
+    await expect(contract.connect(addr1).cancelSubscription(0))
+      .to.changeEtherBalances([contract, addr1], [-200, 200]);
+    expect (await contract.getActiveSubscriptions()).to.deep.equal([]);
+    expect (await contract.nbSubscriptions(addr1.address)).to.equal(0);
+
+    // the number of active subscriptions is limited by account
+    for (let i = 0; i < 10; i++){
+      await contract.connect(addr1).subscribe([1, 2, 3, 4], 1, {value: 100});
+    }
+    expect (await contract.nbSubscriptions(addr1.address)).to.equal(10);
+    await expect(contract.connect(addr1).subscribe([1, 2, 3, 4], 1, {value: 100})).to.be.revertedWith('Too many subscriptions');
+    // the other accounts can still subscribe
+    await expect(contract.connect(addr2).subscribe([1, 2, 3, 4], 1, {value: 100})).not.to.be.reverted;
+  });
+
+  it('Update the config between two draws', async () => {
+    const {contract, attestor, addr1} = await loadFixture(openRegistrationsFixture);
+
+    // the config cannot be updated during a draw
+    await expect(contract.connect(attestor).rollupU256CondEq([], [], [], [], [encodeUpdateConfig(5, 1, 60)])).to.be.reverted;
+
+    await closeRegistrations(contract, attestor, 11);
+    await setResults(contract, attestor, 11, [33, 47, 5, 6], false);
+
+    // the config is updated before opening the registrations for the next draw
+    await updateConfig(contract, attestor, 5, 1, 60);
+    await openRegistrations(contract, attestor, 12);
+
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50])).to.be.revertedWith('Incorrect nb numbers');
+    await expect(contract.connect(addr1).participate([1, 2, 3, 50, 60])).not.to.be.reverted;
+  });
+
   async function setResultsMustBeReverted(
       contract: RaffleRegistration,
       attestor : Signer,
@@ -327,13 +956,16 @@ describe('Test raffle life cycle', () => {
   it('check hex - kv store', async () => {
     assert.equal(ethers.hexlify(ethers.toUtf8Bytes("_status")), "0x5f737461747573", "status key doesn't match");
     assert.equal(ethers.hexlify(ethers.toUtf8Bytes("_drawNumber")), "0x5f647261774e756d626572", "draw number key doesn't match");
+    assert.equal(ethers.hexlify(ethers.toUtf8Bytes("_nbTickets")), "0x5f6e625469636b657473", "number of tickets key doesn't match");
+    assert.equal(ethers.hexlify(ethers.toUtf8Bytes("_config")), "0x5f636f6e666967", "config key doesn't match");
+    assert.equal(ethers.hexlify(ethers.toUtf8Bytes("_revenue")), "0x5f726576656e7565", "revenue key doesn't match");
   });
 
   it('check hex - config and start request', async () => {
 
     const request_bytes = abiCoder.encode(
-      ['uint8', 'uint', 'uint', 'uint'],
-      [4, 1, 50, 33]
+      ['uint8', 'uint', 'uint', 'uint', 'uint8', 'uint8', 'uint8', 'uint'],
+      [4, 1, 50, 33, 80, 15, 5, 100]
     );
     const action = abiCoder.encode(
       ['uint8', 'bytes'],
@@ -343,7 +975,7 @@ describe('Test raffle life cycle', () => {
 
     assert.equal(
       reply,
-      "0x000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000800000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000320000000000000000000000000000000000000000000000000000000000000021",
+      "0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000003200000000000000000000000000000000000000000000000000000000000000210000000000000000000000000000000000000000000000000000000000000050000000000000000000000000000000000000000000000000000000000000000f00000000000000000000000000000000000000000000000000000000000000050000000000000000000000000000000000000000000000000000000000000064",
       "reply doesn't match"
     );
   });
@@ -6,8 +6,8 @@ pub mod lotto_registration_manager_contract {
     use ink::codegen::{EmitEvent, Env};
     use ink::prelude::vec::Vec;
     use lotto::{
-        config, config::*, error::*, raffle_manager, raffle_manager::*,
-        AccountId20, AccountId32, DrawNumber, Number,
+        config, config::*, error::*, raffle_manager, raffle_manager::*, schedule::Schedule,
+        DrawNumber, MultiAddress, Number,
         RegistrationContractId, Salt,
     };
     use openbrush::contracts::access_control::*;
@@ -20,12 +20,33 @@ pub mod lotto_registration_manager_contract {
 
     const LOTTO_MANAGER_ROLE: RoleType = ink::selector_id!("LOTTO_MANAGER");
 
+    const NEXT_CLOSING_REGISTRATIONS: u32 = ink::selector_id!("NEXT_CLOSING_REGISTRATIONS");
+    const NEXT_CLOSING_REGISTRATIONS_TIMESTAMP: u32 =
+        ink::selector_id!("NEXT_CLOSING_REGISTRATIONS_TIMESTAMP");
+
+    /// prefix used by the rollup anchor to store the messages in the queue
+    const QUEUE_PREFIX: &[u8] = b"q/";
+    /// maximum number of messages read in one call of `get_queue_view`
+    const MAX_QUEUE_PAGE: u32 = 50;
+
     /// Event emitted when the lotto is started
     #[ink(event)]
     pub struct LottoStarted {
         config: Config,
     }
 
+    /// Event emitted when a config change is scheduled for the next draw
+    #[ink(event)]
+    pub struct ConfigChangeScheduled {
+        config: Config,
+    }
+
+    /// Event emitted when the config scheduled is applied
+    #[ink(event)]
+    pub struct ConfigUpdated {
+        config: Config,
+    }
+
     /// Event emitted when the registrations are open
     #[ink(event)]
     pub struct RegistrationsOpen {
@@ -40,6 +61,14 @@ pub mod lotto_registration_manager_contract {
         draw_number: DrawNumber,
     }
 
+    /// Event emitted when the registrations are open again because the participation threshold is not met
+    #[ink(event)]
+    pub struct RegistrationsExtended {
+        #[ink(topic)]
+        draw_number: DrawNumber,
+        nb_tickets: u32,
+    }
+
     /// Event emitted when the winning numbers are received
     #[ink(event)]
     pub struct NumbersDrawn {
@@ -56,10 +85,48 @@ pub mod lotto_registration_manager_contract {
         winners: Winners,
     }
 
+    /// Event emitted when a draw is cancelled
+    #[ink(event)]
+    pub struct DrawCancelled {
+        #[ink(topic)]
+        draw_number: DrawNumber,
+    }
+
+    /// Event emitted when there is no winner and the jackpot is rolled over to the next draw
+    #[ink(event)]
+    pub struct JackpotRolledOver {
+        #[ink(topic)]
+        draw_number: DrawNumber,
+        amount: Balance,
+    }
+
     /// Event emitted when the lotto is closed
     #[ink(event)]
     pub struct LottoClosed {}
 
+    /// Event emitted when the participations are paused in a registration contract
+    #[ink(event)]
+    pub struct RegistrationContractPaused {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+    }
+
+    /// Event emitted when the participations are unpaused in a registration contract
+    #[ink(event)]
+    pub struct RegistrationContractUnpaused {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+    }
+
+    /// Event emitted when the offchain rollup fails to synchronize a registration contract
+    #[ink(event)]
+    pub struct RegistrationContractFailed {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+        failure: ActionFailure,
+        nb_failures: u32,
+    }
+
     /// Errors occurred in the contract
     #[derive(Debug, Eq, PartialEq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -73,6 +140,9 @@ pub mod lotto_registration_manager_contract {
         SaltNotGenerated,
         IncorrectInputHash,
         TransferError,
+        RequestAlreadyProcessed,
+        UnknownRequest,
+        MissingRequestIndex,
     }
 
     /// convertor from AccessControlError to ContractError
@@ -107,9 +177,10 @@ pub mod lotto_registration_manager_contract {
     /// Message to synchronize the contracts, to request the lotto draw and get the list of winners.
     /// message pushed in the queue by this contract and read by the offchain rollup
     #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum LottoManagerRequestMessage {
-        /// request to propagate the config to all given contracts
-        PropagateConfig(Config, Vec<RegistrationContractId>),
+        /// request to propagate the config, the revenue split and the ticket price to all given contracts
+        PropagateConfig(Config, RevenueSplit, Balance, Vec<RegistrationContractId>),
         /// request to open the registrations to all given contracts
         OpenRegistrations(DrawNumber, Vec<RegistrationContractId>),
         /// request to close the registrations to all given contracts
@@ -127,6 +198,35 @@ pub mod lotto_registration_manager_contract {
             bool,
             Vec<RegistrationContractId>,
         ),
+        /// request to stop the workflow in all given contracts
+        Stop(DrawNumber, Vec<RegistrationContractId>),
+        /// request to cancel the draw in all given contracts
+        CancelDraw(DrawNumber, Vec<RegistrationContractId>),
+        /// request to pause (true) or unpause (false) the participations in all given contracts
+        SetPaused(bool, Vec<RegistrationContractId>),
+        /// request to update the config in all given contracts before opening the next draw
+        UpdateConfig(Config, Vec<RegistrationContractId>),
+    }
+
+    /// Messages in the queue not processed yet by the offchain rollup
+    #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Clone, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct QueueView {
+        /// index of the first message not processed yet
+        pub head: u32,
+        /// index of the next message pushed in the queue
+        pub tail: u32,
+        /// messages of the requested page (between the head and the tail) with their index
+        pub messages: Vec<(u32, LottoManagerRequestMessage)>,
+    }
+
+    /// Reply sent by the offchain rollup: the response with the index in the queue of the request answered
+    #[derive(scale::Encode, scale::Decode)]
+    pub struct LottoManagerReply {
+        /// index in the queue of the (last) request answered, None if the response does not answer a request
+        pub request_index: Option<u32>,
+        /// response
+        pub response: LottoManagerResponseMessage,
     }
 
     /// Offchain rollup response
@@ -142,8 +242,8 @@ pub mod lotto_registration_manager_contract {
         RegistrationsOpen(DrawNumber, Vec<RegistrationContractId>),
         /// The registration is closed for the given contract ids.
         /// arg1: draw number
-        /// arg2: list of contracts where the registration is closed
-        RegistrationsClosed(DrawNumber, Vec<RegistrationContractId>),
+        /// arg2: list of contracts where the registration is closed with the number of tickets recorded
+        RegistrationsClosed(DrawNumber, Vec<(RegistrationContractId, u32, Balance)>),
         /// The salt is generated for the given contract ids.
         /// arg1: draw number
         /// arg2: list of contracts where the salt is generated
@@ -155,10 +255,9 @@ pub mod lotto_registration_manager_contract {
         WinningNumbers(DrawNumber, Vec<Number>, Hash),
         /// Return the list of winners
         /// arg1: draw number
-        /// arg2: winners substrate
-        /// arg3: winners evm
-        /// arg4: hash of winning numbers
-        Winners(DrawNumber, Vec<AccountId32>, Vec<AccountId20>, Hash),
+        /// arg2: winners
+        /// arg3: hash of winning numbers
+        Winners(DrawNumber, Vec<MultiAddress>, Hash),
         /// The results are propagated to the given contract ids.
         /// arg1: draw number
         /// arg2: list of contracts where the results are propagated
@@ -166,6 +265,46 @@ pub mod lotto_registration_manager_contract {
         ResultsPropagated(DrawNumber, Vec<RegistrationContractId>, Hash),
         /// Request to close the registrations
         CloseRegistrations(),
+        /// The workflow is stopped for the given contract ids.
+        /// arg1: draw number
+        /// arg2: list of contracts where the workflow is stopped
+        Stopped(DrawNumber, Vec<RegistrationContractId>),
+        /// The draw is cancelled for the given contract ids.
+        /// arg1: draw number
+        /// arg2: list of contracts where the draw is cancelled
+        DrawCancelled(DrawNumber, Vec<RegistrationContractId>),
+        /// The participations are paused (or unpaused) for the given contract ids.
+        /// arg1: true if paused, false if unpaused
+        /// arg2: list of contracts where the flag is propagated
+        PausePropagated(bool, Vec<RegistrationContractId>),
+        /// The action failed for the given contract ids.
+        /// arg1: list of contracts where the action failed with the kind of error
+        ActionFailed(Vec<(RegistrationContractId, ActionFailure)>),
+        /// The new config is propagated to the given contract ids.
+        /// arg1: list of contracts where the config is updated
+        /// arg2: hash of config
+        ConfigUpdated(Vec<RegistrationContractId>, Hash),
+    }
+
+    impl LottoManagerResponseMessage {
+        /// Return the draw number linked to this response
+        fn get_draw_number(&self) -> Option<DrawNumber> {
+            match self {
+                LottoManagerResponseMessage::ConfigPropagated(_, _)
+                | LottoManagerResponseMessage::CloseRegistrations()
+                | LottoManagerResponseMessage::PausePropagated(_, _)
+                | LottoManagerResponseMessage::ActionFailed(_)
+                | LottoManagerResponseMessage::ConfigUpdated(_, _) => None,
+                LottoManagerResponseMessage::RegistrationsOpen(draw_number, _)
+                | LottoManagerResponseMessage::RegistrationsClosed(draw_number, _)
+                | LottoManagerResponseMessage::SaltGenerated(draw_number, _)
+                | LottoManagerResponseMessage::WinningNumbers(draw_number, _, _)
+                | LottoManagerResponseMessage::Winners(draw_number, _, _)
+                | LottoManagerResponseMessage::ResultsPropagated(draw_number, _, _)
+                | LottoManagerResponseMessage::Stopped(draw_number, _)
+                | LottoManagerResponseMessage::DrawCancelled(draw_number, _) => Some(*draw_number),
+            }
+        }
     }
 
     // Contract storage
@@ -186,6 +325,10 @@ pub mod lotto_registration_manager_contract {
         raffle_manager: raffle_manager::Data,
         number_of_blocks_for_participation: BlockNumber,
         next_closing_registrations: BlockNumber,
+        registration_schedule: Option<Schedule>,
+        next_closing_registrations_timestamp: Timestamp,
+        /// index in the queue of the last request answered
+        last_answered_request: Option<u32>,
     }
 
     impl RaffleConfig for Contract {}
@@ -212,15 +355,57 @@ pub mod lotto_registration_manager_contract {
         #[ink(message)]
         #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
         pub fn set_config(&mut self, config: Config) -> Result<(), ContractError> {
-            // check the status, we can set the config only when the raffle is not started yet
-            let status = RaffleManager::get_status(self)?;
-            if status != Status::NotStarted {
+            match RaffleManager::get_status(self)? {
+                // the raffle is not started yet, update the config
+                Status::NotStarted => RaffleConfig::set_config(self, config)?,
+                Status::Closed => {
+                    return Err(ContractError::RaffleError(RaffleError::IncorrectStatus))
+                }
+                // the config will be updated when the registrations are open for the next draw
+                _ => {
+                    RaffleConfig::set_pending_config(self, config)?;
+                    // emmit the event
+                    self.env().emit_event(ConfigChangeScheduled { config });
+                }
+            }
+
+            Ok(())
+        }
+
+        /// set how the revenue of the tickets is split between the jackpot, the treasury and the reserve.
+        /// The revenue split is propagated to the registration contracts when the lotto starts
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_revenue_split(&mut self, revenue_split: RevenueSplit) -> Result<(), ContractError> {
+            if RaffleManager::get_status(self)? != Status::NotStarted {
                 return Err(ContractError::RaffleError(RaffleError::IncorrectStatus));
             }
+            RaffleConfig::set_revenue_split(self, revenue_split)?;
+            Ok(())
+        }
 
-            // update the config
-            RaffleConfig::set_config(self, config)?;
+        /// set the price of a ticket.
+        /// The price is propagated to the registration contracts when the lotto starts
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_ticket_price(&mut self, ticket_price: Balance) -> Result<(), ContractError> {
+            if RaffleManager::get_status(self)? != Status::NotStarted {
+                return Err(ContractError::RaffleError(RaffleError::IncorrectStatus));
+            }
+            RaffleConfig::set_ticket_price(self, ticket_price)?;
+            Ok(())
+        }
 
+        /// set the asset in which the given registration contract settles the tickets
+        /// and the exchange rate used to convert its revenue in the unit of the jackpot
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_settlement_asset(
+            &mut self,
+            registration_contract_id: RegistrationContractId,
+            settlement_asset: SettlementAsset,
+        ) -> Result<(), ContractError> {
+            RaffleManager::set_settlement_asset(self, registration_contract_id, settlement_asset)?;
             Ok(())
         }
 
@@ -264,6 +449,60 @@ pub mod lotto_registration_manager_contract {
             Ok(())
         }
 
+        /// get the schedule used to close the participation
+        #[ink(message)]
+        pub fn get_registration_schedule(&self) -> Option<Schedule> {
+            self.registration_schedule
+        }
+
+        /// set the schedule used to close the participation (UTC).
+        /// When a schedule is set, the registrations are closed at the next occurrence of the schedule
+        /// and the number of blocks is ignored (except when the registrations are extended).
+        /// The closing of the registrations in progress is updated with the new schedule.
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_registration_schedule(
+            &mut self,
+            registration_schedule: Option<Schedule>,
+        ) -> Result<(), ContractError> {
+            // check the schedule
+            if let Some(schedule) = registration_schedule {
+                schedule.check()?;
+            }
+            self.registration_schedule = registration_schedule;
+
+            // update the closing of the registrations in progress (if they are not extended)
+            if RaffleManager::get_status(self)? == Status::RegistrationsOpen
+                && !self.is_registrations_extended()?
+            {
+                let next_closing_registrations_timestamp =
+                    self.get_next_occurrence_of_schedule()?;
+                self.set_next_closing_registrations(
+                    self.next_closing_registrations,
+                    next_closing_registrations_timestamp,
+                );
+            }
+            Ok(())
+        }
+
+        /// get the minimum participation threshold
+        #[ink(message)]
+        pub fn get_participation_threshold(&self) -> Option<ParticipationThreshold> {
+            RaffleManager::get_participation_threshold(self)
+        }
+
+        /// set the minimum participation threshold (None to remove it).
+        /// When the threshold is not met, the registrations are open again for the same draw.
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn set_participation_threshold(
+            &mut self,
+            participation_threshold: Option<ParticipationThreshold>,
+        ) -> Result<(), ContractError> {
+            RaffleManager::set_participation_threshold(self, participation_threshold)?;
+            Ok(())
+        }
+
         #[ink(message)]
         #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
         pub fn start(
@@ -279,10 +518,41 @@ pub mod lotto_registration_manager_contract {
             self.env().emit_event(LottoStarted { config });
 
             let registration_contracts = RaffleManager::get_registration_contracts(self);
-            let message =
-                LottoManagerRequestMessage::PropagateConfig(config, registration_contracts);
-            RollupAnchor::push_message(self, &message)?;
+            self.push_propagate_config(config, registration_contracts)?;
+
+            Ok(())
+        }
+
+        /// push the messages to propagate the config, the revenue split and the ticket price.
+        /// The ticket price is converted in the asset of each contract,
+        /// one message is pushed by price
+        fn push_propagate_config(
+            &mut self,
+            config: Config,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            let revenue_split = RaffleConfig::get_revenue_split(self);
+            let ticket_price = RaffleConfig::get_ticket_price(self);
+
+            let mut contracts_by_price: Vec<(Balance, Vec<RegistrationContractId>)> = Vec::new();
+            for contract_id in registration_contracts {
+                let price = RaffleManager::get_settlement_asset(self, contract_id)
+                    .convert_back(ticket_price)?;
+                match contracts_by_price.iter_mut().find(|(p, _)| *p == price) {
+                    Some((_, contract_ids)) => contract_ids.push(contract_id),
+                    None => contracts_by_price.push((price, ink::prelude::vec![contract_id])),
+                }
+            }
 
+            for (price, contract_ids) in contracts_by_price {
+                let message = LottoManagerRequestMessage::PropagateConfig(
+                    config,
+                    revenue_split,
+                    price,
+                    contract_ids,
+                );
+                RollupAnchor::push_message(self, &message)?;
+            }
             Ok(())
         }
 
@@ -293,7 +563,13 @@ pub mod lotto_registration_manager_contract {
                 return false;
             }
 
-            // check the block number
+            // when a schedule is set, check only the timestamp
+            if self.next_closing_registrations_timestamp > 0 {
+                let timestamp = self.env().block_timestamp();
+                return timestamp >= self.next_closing_registrations_timestamp;
+            }
+
+            // otherwise check the block number
             let block_number = self.env().block_number();
             block_number >= self.next_closing_registrations
         }
@@ -303,6 +579,11 @@ pub mod lotto_registration_manager_contract {
             self.next_closing_registrations
         }
 
+        #[ink(message)]
+        pub fn get_next_closing_registrations_timestamp(&self) -> Timestamp {
+            self.next_closing_registrations_timestamp
+        }
+
         #[ink(message)]
         pub fn close_registrations(&mut self) -> Result<(), ContractError> {
             // check if we can close the registrations
@@ -311,6 +592,8 @@ pub mod lotto_registration_manager_contract {
             }
             // close the registrations in the manager
             let draw_number = RaffleManager::close_registrations(self)?;
+            // archive the block when the registrations are closed
+            RaffleManager::save_draw_closed(self, self.env().block_number())?;
 
             // emmit the event
             self.env().emit_event(RegistrationsClosed { draw_number });
@@ -324,6 +607,247 @@ pub mod lotto_registration_manager_contract {
             Ok(())
         }
 
+        /// Stop the lotto: no new draw will be open.
+        /// If the registrations are open, the current draw is cancelled,
+        /// otherwise the lotto is closed when the current draw is finished
+        /// (i.e. when the results are propagated in all registration contracts).
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn stop(&mut self) -> Result<(), ContractError> {
+            RaffleManager::request_stop(self)?;
+
+            match RaffleManager::get_status(self)? {
+                Status::Started | Status::RegistrationsOpen => self.inner_close()?,
+                Status::DrawFinished => {
+                    // the results may still be propagated, in this case the lotto is closed
+                    // when all contracts are synchronized (see handle_results_propagated)
+                    if RaffleManager::check_registration_contracts_status(
+                        self,
+                        Status::DrawFinished,
+                    )
+                    .is_ok()
+                    {
+                        self.inner_close()?
+                    }
+                }
+                // wait the end of the current draw
+                _ => {}
+            }
+
+            Ok(())
+        }
+
+        fn inner_close(&mut self) -> Result<(), ContractError> {
+            let status = RaffleManager::get_status(self)?;
+
+            // close the lotto in the manager
+            let draw_number = RaffleManager::close(self)?;
+
+            // emmit the events
+            if status == Status::RegistrationsOpen {
+                self.env().emit_event(DrawCancelled { draw_number });
+                // the tickets are refunded, only the amount carried over is rolled over
+                self.inner_cancel_jackpot(draw_number)?;
+            }
+            self.env().emit_event(LottoClosed {});
+
+            // stop the workflow in all contracts
+            let registration_contracts = RaffleManager::get_registration_contracts(self);
+            let message = LottoManagerRequestMessage::Stop(draw_number, registration_contracts);
+            RollupAnchor::push_message(self, &message)?;
+
+            Ok(())
+        }
+
+        fn handle_stopped(
+            &mut self,
+            draw_number: DrawNumber,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            let not_synchronized_contracts = RaffleManager::save_registration_contracts_status(
+                self,
+                draw_number,
+                Status::Closed,
+                registration_contracts,
+            )?;
+
+            if !not_synchronized_contracts.is_empty() {
+                // synchronized missing contracts and wait
+                let message =
+                    LottoManagerRequestMessage::Stop(draw_number, not_synchronized_contracts);
+                RollupAnchor::push_message(self, &message)?;
+            }
+
+            Ok(())
+        }
+
+        /// Cancel the current draw when it is stuck (salt cannot be generated, incorrect hash, ...).
+        /// The salts, the generated salt and the results of this draw are discarded
+        /// and the participants claim the refund of their tickets in the registration contracts.
+        /// The registrations are open again for a new draw when all contracts are synchronized.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn cancel_draw(&mut self, draw_number: DrawNumber) -> Result<(), ContractError> {
+            // cancel the draw in the manager
+            RaffleManager::cancel_draw(self, draw_number)?;
+
+            // emmit the event
+            self.env().emit_event(DrawCancelled { draw_number });
+
+            // the tickets are refunded, only the amount carried over is rolled over
+            self.inner_cancel_jackpot(draw_number)?;
+
+            // cancel the draw in all contracts
+            let registration_contracts = RaffleManager::get_registration_contracts(self);
+            let message =
+                LottoManagerRequestMessage::CancelDraw(draw_number, registration_contracts);
+            RollupAnchor::push_message(self, &message)?;
+
+            Ok(())
+        }
+
+        fn handle_draw_cancelled(
+            &mut self,
+            draw_number: DrawNumber,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            let not_synchronized_contracts = RaffleManager::save_registration_contracts_status(
+                self,
+                draw_number,
+                Status::DrawCancelled,
+                registration_contracts,
+            )?;
+
+            if !not_synchronized_contracts.is_empty() {
+                // synchronized missing contracts and wait
+                let message =
+                    LottoManagerRequestMessage::CancelDraw(draw_number, not_synchronized_contracts);
+                RollupAnchor::push_message(self, &message)?;
+                return Ok(());
+            }
+
+            // if all contracts are synchronized, we can continue
+            if RaffleManager::is_stop_requested(self) {
+                // the stop was requested during the cancellation, we can close the lotto
+                self.inner_close()?;
+            } else {
+                // run a new draw, the tickets of the cancelled draw are refunded
+                self.inner_open_registrations()?;
+            }
+
+            Ok(())
+        }
+
+        /// Pause the participations in the given registration contracts, the workflow is not impacted
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn pause_registration_contracts(
+            &mut self,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            self.inner_set_paused(registration_contracts, true)
+        }
+
+        /// Unpause the participations in the given registration contracts
+        #[ink(message)]
+        #[openbrush::modifiers(access_control::only_role(LOTTO_MANAGER_ROLE))]
+        pub fn unpause_registration_contracts(
+            &mut self,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            self.inner_set_paused(registration_contracts, false)
+        }
+
+        fn inner_set_paused(
+            &mut self,
+            registration_contracts: Vec<RegistrationContractId>,
+            paused: bool,
+        ) -> Result<(), ContractError> {
+            RaffleManager::request_paused(self, &registration_contracts, paused)?;
+
+            // propagate the flag in all given contracts
+            let message = LottoManagerRequestMessage::SetPaused(paused, registration_contracts);
+            RollupAnchor::push_message(self, &message)?;
+
+            Ok(())
+        }
+
+        fn handle_pause_propagated(
+            &mut self,
+            paused: bool,
+            registration_contracts: Vec<RegistrationContractId>,
+        ) -> Result<(), ContractError> {
+            let (updated_contracts, not_synchronized_contracts) =
+                RaffleManager::save_paused(self, paused, registration_contracts);
+
+            // emmit the events
+            for registration_contract_id in updated_contracts {
+                if paused {
+                    self.env().emit_event(RegistrationContractPaused {
+                        registration_contract_id,
+                    });
+                } else {
+                    self.env().emit_event(RegistrationContractUnpaused {
+                        registration_contract_id,
+                    });
+                }
+            }
+
+            if !not_synchronized_contracts.is_empty() {
+                // synchronized missing contracts
+                let message =
+                    LottoManagerRequestMessage::SetPaused(paused, not_synchronized_contracts);
+                RollupAnchor::push_message(self, &message)?;
+            }
+
+            Ok(())
+        }
+
+        fn handle_action_failed(
+            &mut self,
+            failures: Vec<(RegistrationContractId, ActionFailure)>,
+        ) -> Result<(), ContractError> {
+            // the request is retried by the offchain rollup for the contracts not synchronized,
+            // the consecutive failures are recorded so that the operators can pause a faulty contract
+            let nb_failures = RaffleManager::save_failures(self, &failures);
+
+            // emmit the events
+            for (registration_contract_id, nb_failures) in nb_failures {
+                if let Some((_, failure)) = failures
+                    .iter()
+                    .find(|(contract_id, _)| *contract_id == registration_contract_id)
+                {
+                    self.env().emit_event(RegistrationContractFailed {
+                        registration_contract_id,
+                        failure: *failure,
+                        nb_failures,
+                    });
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Check the reply answers a request not processed yet and save the index of this request
+        fn check_request_index(&mut self, request_index: u32) -> Result<(), ContractError> {
+            // the requests are answered in the order of the queue,
+            // so the requests before the last one answered are already processed
+            if matches!(self.last_answered_request, Some(last) if request_index <= last) {
+                return Err(ContractError::RequestAlreadyProcessed);
+            }
+            let tail = RollupAnchor::get_queue_tail(self)?;
+            if request_index >= tail {
+                return Err(ContractError::UnknownRequest);
+            }
+            self.last_answered_request = Some(request_index);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_last_answered_request(&self) -> Option<u32> {
+            self.last_answered_request
+        }
+
         #[ink(message)]
         pub fn has_pending_message(&self) -> bool {
             let tail = RollupAnchor::get_queue_tail(self).unwrap_or_default();
@@ -331,6 +855,34 @@ pub mod lotto_registration_manager_contract {
             tail > head
         }
 
+        /// Return the messages in the queue not processed yet by the offchain rollup,
+        /// from the given index (or the head of the queue if greater).
+        /// At most `limit` messages are read, the limit is capped to `MAX_QUEUE_PAGE`.
+        #[ink(message)]
+        pub fn get_queue_view(&self, from: u32, limit: u32) -> QueueView {
+            let head = RollupAnchor::get_queue_head(self).unwrap_or_default();
+            let tail = RollupAnchor::get_queue_tail(self).unwrap_or_default();
+
+            let from = from.max(head);
+            let to = from.saturating_add(limit.min(MAX_QUEUE_PAGE)).min(tail);
+
+            let mut messages = Vec::new();
+            for index in from..to {
+                let key = [QUEUE_PREFIX, &index.encode()].concat();
+                if let Some(message) = RollupAnchor::get_value(self, key)
+                    .and_then(|v| scale::Decode::decode(&mut v.as_slice()).ok())
+                {
+                    messages.push((index, message));
+                }
+            }
+
+            QueueView {
+                head,
+                tail,
+                messages,
+            }
+        }
+
         fn handle_started(
             &mut self,
             registration_contracts: Vec<RegistrationContractId>,
@@ -350,9 +902,7 @@ pub mod lotto_registration_manager_contract {
 
             if !not_synchronized_contracts.is_empty() {
                 // synchronized missing contracts and wait
-                let message =
-                    LottoManagerRequestMessage::PropagateConfig(config, not_synchronized_contracts);
-                RollupAnchor::push_message(self, &message)?;
+                self.push_propagate_config(config, not_synchronized_contracts)?;
                 return Ok(());
             }
 
@@ -363,8 +913,24 @@ pub mod lotto_registration_manager_contract {
         }
 
         fn inner_open_registrations(&mut self) -> Result<(), ContractError> {
+            // apply the config change scheduled since the last draw
+            if let Some(config) = RaffleConfig::apply_pending_config(self) {
+                // emmit the event
+                self.env().emit_event(ConfigUpdated { config });
+
+                // update the config in all contracts and wait before opening the registrations
+                let registration_contracts = RaffleManager::request_config_update(self)?;
+                let message =
+                    LottoManagerRequestMessage::UpdateConfig(config, registration_contracts);
+                RollupAnchor::push_message(self, &message)?;
+                return Ok(());
+            }
+
             // open the registrations in the manager
             let draw_number = RaffleManager::open_registrations(self)?;
+            // archive the block and the config used for this draw
+            let config = RaffleConfig::ensure_config(self)?;
+            RaffleManager::save_draw_opened(self, self.env().block_number(), config)?;
 
             // emmit the event
             self.env().emit_event(RegistrationsOpen { draw_number });
@@ -378,6 +944,37 @@ pub mod lotto_registration_manager_contract {
             Ok(())
         }
 
+        fn handle_config_updated(
+            &mut self,
+            registration_contracts: Vec<RegistrationContractId>,
+            config_hash: &[u8],
+        ) -> Result<(), ContractError> {
+            // check the config propagated to other contracts
+            let config = RaffleConfig::ensure_config(self)?;
+            verify_hash(&config, config_hash)?;
+
+            let not_synchronized_contracts =
+                RaffleManager::save_config_updated(self, registration_contracts);
+
+            if !not_synchronized_contracts.is_empty() {
+                // synchronized missing contracts and wait
+                let message =
+                    LottoManagerRequestMessage::UpdateConfig(config, not_synchronized_contracts);
+                RollupAnchor::push_message(self, &message)?;
+                return Ok(());
+            }
+
+            // if all contracts are synchronized, we can open the registrations
+            if RaffleManager::is_stop_requested(self) {
+                // the stop was requested during the update, we can close the lotto
+                self.inner_close()?;
+            } else {
+                self.inner_open_registrations()?;
+            }
+
+            Ok(())
+        }
+
         fn handle_registrations_open(
             &mut self,
             draw_number: DrawNumber,
@@ -401,20 +998,98 @@ pub mod lotto_registration_manager_contract {
             }
 
             // all contracts are synchronized
-            // we can close the registration in X block
             let block_number = self.env().block_number();
-            self.next_closing_registrations = block_number
+            let extension = match RaffleManager::get_participation_threshold(self) {
+                Some(threshold) if RaffleManager::get_nb_extensions(self, draw_number) > 0 => {
+                    Some(threshold.extension_blocks)
+                }
+                _ => None,
+            };
+
+            if let Some(extension_blocks) = extension {
+                // the schedule is ignored when the registrations are extended
+                // the registrations are extended, we can close them in X blocks
+                let next_closing_registrations = block_number
+                    .checked_add(extension_blocks)
+                    .ok_or(RaffleError::AddOverFlow)?;
+                self.set_next_closing_registrations(next_closing_registrations, 0);
+                return Ok(());
+            }
+
+            // we can close the registration in X block
+            let next_closing_registrations = block_number
                 .checked_add(self.number_of_blocks_for_participation)
                 .ok_or(RaffleError::AddOverFlow)?;
+            // or at the next occurrence of the schedule
+            let next_closing_registrations_timestamp = self.get_next_occurrence_of_schedule()?;
+            self.set_next_closing_registrations(
+                next_closing_registrations,
+                next_closing_registrations_timestamp,
+            );
 
             Ok(())
         }
 
+        /// return true if the registrations of the current draw are extended
+        fn is_registrations_extended(&self) -> Result<bool, ContractError> {
+            let draw_number = RaffleManager::get_draw_number(self)?;
+            Ok(RaffleManager::get_participation_threshold(self).is_some()
+                && RaffleManager::get_nb_extensions(self, draw_number) > 0)
+        }
+
+        /// return the next occurrence of the schedule, 0 if no schedule is set
+        fn get_next_occurrence_of_schedule(&self) -> Result<Timestamp, ContractError> {
+            let timestamp = match self.registration_schedule {
+                Some(schedule) => schedule.next_occurrence(self.env().block_timestamp())?,
+                None => 0,
+            };
+            Ok(timestamp)
+        }
+
+        fn set_next_closing_registrations(
+            &mut self,
+            block_number: BlockNumber,
+            timestamp: Timestamp,
+        ) {
+            self.next_closing_registrations = block_number;
+            self.next_closing_registrations_timestamp = timestamp;
+
+            // save in the kv store the values used by the offchain rollup to schedule the closing
+            RollupAnchor::set_value(
+                self,
+                &NEXT_CLOSING_REGISTRATIONS.encode(),
+                Some(&block_number.encode()),
+            );
+            RollupAnchor::set_value(
+                self,
+                &NEXT_CLOSING_REGISTRATIONS_TIMESTAMP.encode(),
+                Some(&timestamp.encode()),
+            );
+        }
+
         fn handle_registrations_closed(
             &mut self,
             draw_number: DrawNumber,
-            registration_contracts: Vec<RegistrationContractId>,
+            contracts_tickets: Vec<(RegistrationContractId, u32, Balance)>,
         ) -> Result<(), ContractError> {
+            // save the number of tickets recorded by the registration contracts
+            let nb_tickets: Vec<(RegistrationContractId, u32)> = contracts_tickets
+                .iter()
+                .map(|(contract_id, nb_tickets, _)| (*contract_id, *nb_tickets))
+                .collect();
+            RaffleManager::save_nb_tickets(self, draw_number, &nb_tickets)?;
+
+            // save the amount paid for these tickets
+            let contributions: Vec<(RegistrationContractId, Balance)> = contracts_tickets
+                .iter()
+                .map(|(contract_id, _, revenue)| (*contract_id, *revenue))
+                .collect();
+            RaffleManager::save_jackpot_contributions(self, draw_number, &contributions)?;
+
+            let registration_contracts = contracts_tickets
+                .into_iter()
+                .map(|(contract_id, _, _)| contract_id)
+                .collect();
             let not_synchronized_contracts = RaffleManager::save_registration_contracts_status(
                 self,
                 draw_number,
@@ -432,7 +1107,25 @@ pub mod lotto_registration_manager_contract {
                 return Ok(());
             }
 
-            // if all contracts are synchronized, we can start the draw - generate salt in first
+            // if all contracts are synchronized, check the participation threshold
+            if RaffleManager::try_to_extend_registrations(self)? {
+                // the threshold is not met, open again the registrations for the same draw
+                let nb_tickets = RaffleManager::get_nb_tickets(self, draw_number);
+                self.env().emit_event(RegistrationsExtended {
+                    draw_number,
+                    nb_tickets,
+                });
+
+                let registration_contracts = RaffleManager::get_registration_contracts(self);
+                let message = LottoManagerRequestMessage::OpenRegistrations(
+                    draw_number,
+                    registration_contracts,
+                );
+                RollupAnchor::push_message(self, &message)?;
+                return Ok(());
+            }
+
+            // we can start the draw - generate salt in first
             self.inner_try_to_generate_salt(draw_number)?;
 
             Ok(())
@@ -459,8 +1152,8 @@ pub mod lotto_registration_manager_contract {
                     Ok(())
                 }
                 (Some(salt), _) => {
-                    // the salt is generated, request the draw numbers
-                    let config = RaffleConfig::ensure_config(self)?;
+                    // the salt is generated, request the draw numbers with the config used for this draw
+                    let config = self.get_draw_config_or_current(draw_number)?;
                     let message = LottoManagerRequestMessage::DrawNumbers(draw_number, config, salt);
                     RollupAnchor::push_message(self, &message)?;
                     Ok(())
@@ -469,6 +1162,14 @@ pub mod lotto_registration_manager_contract {
         }
 
 
+        /// return the config saved for the given draw, or the current config for the draws open before the snapshot
+        fn get_draw_config_or_current(&self, draw_number: DrawNumber) -> Result<Config, ContractError> {
+            match RaffleManager::get_draw_config(self, draw_number) {
+                Some(config) => Ok(config),
+                None => Ok(RaffleConfig::ensure_config(self)?),
+            }
+        }
+
         fn handle_salt_generated(
             &mut self,
             draw_number: DrawNumber,
@@ -493,8 +1194,8 @@ pub mod lotto_registration_manager_contract {
             config_hash: &[u8],
         ) -> Result<(), ContractError> {
 
-            // check the config used is correct
-            let config = RaffleConfig::ensure_config(self)?;
+            // check the config used for this draw is correct
+            let config = self.get_draw_config_or_current(draw_number)?;
             // check the salt used by the VRF
             let generated_salt = RaffleManager::get_generated_salt(self, draw_number).ok_or(ContractError::SaltNotGenerated)?;
             // check the config and salt used are correct
@@ -526,8 +1227,7 @@ pub mod lotto_registration_manager_contract {
         fn handle_winners(
             &mut self,
             draw_number: DrawNumber,
-            winners_substrate: Vec<AccountId32>,
-            winners_evm: Vec<AccountId20>,
+            winners: Vec<MultiAddress>,
             results_hash: &[u8],
         ) -> Result<(), ContractError> {
 
@@ -536,14 +1236,17 @@ pub mod lotto_registration_manager_contract {
             verify_hash(&results, results_hash)?;
 
             // set the winners in the raffle
-            RaffleManager::set_winners(self, draw_number, (winners_substrate.clone(), winners_evm.clone()))?;
+            RaffleManager::set_winners(self, draw_number, winners.clone())?;
 
             // emmit the event
             self.env().emit_event(WinnersRevealed {
                 draw_number,
-                winners: (winners_substrate.clone(), winners_evm.clone()),
+                winners: winners.clone(),
             });
 
+            // pay the jackpot or roll it over to the next draw
+            self.inner_settle_jackpot(draw_number, !winners.is_empty())?;
+
             // propagate the results in all contracts
             let numbers =
                 RaffleManager::get_results(self, draw_number).ok_or(ContractError::NoResult)?;
@@ -551,7 +1254,7 @@ pub mod lotto_registration_manager_contract {
             let message = LottoManagerRequestMessage::PropagateResults(
                 draw_number,
                 numbers,
-                !winners_substrate.is_empty() || !winners_evm.is_empty(),
+                !winners.is_empty(),
                 registration_contracts,
             );
             RollupAnchor::push_message(self, &message)?;
@@ -559,6 +1262,33 @@ pub mod lotto_registration_manager_contract {
             Ok(())
         }
 
+        fn inner_settle_jackpot(
+            &mut self,
+            draw_number: DrawNumber,
+            has_winner: bool,
+        ) -> Result<(), ContractError> {
+            let jackpot = RaffleManager::settle_jackpot(self, draw_number, has_winner)?;
+            if let Some(jackpot) = jackpot {
+                if !has_winner {
+                    self.env().emit_event(JackpotRolledOver {
+                        draw_number,
+                        amount: jackpot.rolled_over,
+                    });
+                }
+            }
+            Ok(())
+        }
+
+        fn inner_cancel_jackpot(&mut self, draw_number: DrawNumber) -> Result<(), ContractError> {
+            if let Some(jackpot) = RaffleManager::cancel_jackpot(self, draw_number)? {
+                self.env().emit_event(JackpotRolledOver {
+                    draw_number,
+                    amount: jackpot.rolled_over,
+                });
+            }
+            Ok(())
+        }
+
         fn handle_results_propagated(
             &mut self,
             draw_number: DrawNumber,
@@ -578,7 +1308,7 @@ pub mod lotto_registration_manager_contract {
             )?;
 
             let has_winner = if let Some(winners) = RaffleManager::get_winners(self, draw_number) {
-                !winners.0.is_empty() || !winners.1.is_empty()
+                !winners.is_empty()
             } else {
                 false
             };
@@ -598,7 +1328,10 @@ pub mod lotto_registration_manager_contract {
             }
 
             // if all contracts are synchronized, we can continue
-            if !has_winner {
+            if RaffleManager::is_stop_requested(self) {
+                // the stop was requested during the draw, we can close the lotto
+                self.inner_close()?;
+            } else if !has_winner {
                 // if there is no winner, we can open the registrations for the next draw number
                 self.inner_open_registrations()?;
             }
@@ -663,9 +1396,46 @@ pub mod lotto_registration_manager_contract {
 
     impl rollup_anchor::MessageHandler for Contract {
         fn on_message_received(&mut self, action: Vec<u8>) -> Result<(), RollupAnchorError> {
-            // parse the response
-            let response: LottoManagerResponseMessage = scale::Decode::decode(&mut &action[..])
-                .or(Err(RollupAnchorError::FailedToDecode))?;
+            // parse the reply
+            let LottoManagerReply {
+                request_index,
+                response,
+            } = scale::Decode::decode(&mut &action[..]).or(Err(RollupAnchorError::FailedToDecode))?;
+
+            // reject the replies for the requests already processed (ie when a tx is resubmitted)
+            match request_index {
+                Some(request_index) => self.check_request_index(request_index)?,
+                // only the responses not answering a request can be sent without index
+                None if !matches!(
+                    response,
+                    LottoManagerResponseMessage::CloseRegistrations()
+                        | LottoManagerResponseMessage::ActionFailed(_)
+                ) =>
+                {
+                    return Err(ContractError::MissingRequestIndex.into())
+                }
+                None => {}
+            }
+
+            // when the lotto is closed, the responses for the previous requests still in the queue are ignored
+            if RaffleManager::get_status(self) == Ok(Status::Closed)
+                && !matches!(response, LottoManagerResponseMessage::Stopped(_, _))
+            {
+                return Ok(());
+            }
+
+            // the responses for a cancelled draw still in the queue are ignored
+            if let Some(draw_number) = response.get_draw_number() {
+                if RaffleManager::is_draw_cancelled(self, draw_number)
+                    && !matches!(
+                        response,
+                        LottoManagerResponseMessage::DrawCancelled(_, _)
+                            | LottoManagerResponseMessage::Stopped(_, _)
+                    )
+                {
+                    return Ok(());
+                }
+            }
 
             match response {
                 LottoManagerResponseMessage::ConfigPropagated(contract_ids, ref hash) => {
@@ -674,8 +1444,8 @@ pub mod lotto_registration_manager_contract {
                 LottoManagerResponseMessage::RegistrationsOpen(draw_number, contract_ids) => {
                     self.handle_registrations_open(draw_number, contract_ids)?
                 }
-                LottoManagerResponseMessage::RegistrationsClosed(draw_number, contract_ids) => {
-                    self.handle_registrations_closed(draw_number, contract_ids)?
+                LottoManagerResponseMessage::RegistrationsClosed(draw_number, contracts_tickets) => {
+                    self.handle_registrations_closed(draw_number, contracts_tickets)?
                 }
                 LottoManagerResponseMessage::SaltGenerated(draw_number, contracts_salts) => {
                     self.handle_salt_generated(draw_number, contracts_salts)?
@@ -688,14 +1458,29 @@ pub mod lotto_registration_manager_contract {
                 LottoManagerResponseMessage::WinningNumbers(draw_number, numbers, ref hash) => {
                     self.handle_winning_numbers(draw_number, numbers, hash.as_ref())?
                 }
-                LottoManagerResponseMessage::Winners(draw_number, winners_substrate, winners_evm , ref hash) => {
-                    self.handle_winners(draw_number, winners_substrate, winners_evm, hash.as_ref())?
+                LottoManagerResponseMessage::Winners(draw_number, winners, ref hash) => {
+                    self.handle_winners(draw_number, winners, hash.as_ref())?
                 }
                 LottoManagerResponseMessage::CloseRegistrations() => {
                     if self.can_close_registrations() {
                         self.close_registrations()?
                     }
                 }
+                LottoManagerResponseMessage::Stopped(draw_number, contract_ids) => {
+                    self.handle_stopped(draw_number, contract_ids)?
+                }
+                LottoManagerResponseMessage::DrawCancelled(draw_number, contract_ids) => {
+                    self.handle_draw_cancelled(draw_number, contract_ids)?
+                }
+                LottoManagerResponseMessage::PausePropagated(paused, contract_ids) => {
+                    self.handle_pause_propagated(paused, contract_ids)?
+                }
+                LottoManagerResponseMessage::ActionFailed(failures) => {
+                    self.handle_action_failed(failures)?
+                }
+                LottoManagerResponseMessage::ConfigUpdated(contract_ids, ref hash) => {
+                    self.handle_config_updated(contract_ids, hash.as_ref())?
+                }
             }
 
             Ok(())
@@ -737,6 +1522,322 @@ pub mod lotto_registration_manager_contract {
     mod tests {
         use super::*;
 
+        #[ink::test]
+        fn test_queue_view() {
+            let mut contract = Contract::new();
+
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(queue_view.head, 0);
+            assert_eq!(queue_view.tail, 0);
+            assert!(queue_view.messages.is_empty());
+
+            let message_1 = LottoManagerRequestMessage::OpenRegistrations(1, vec![101, 102]);
+            let message_2 = LottoManagerRequestMessage::SetPaused(true, vec![101]);
+            RollupAnchor::push_message(&mut contract, &message_1).expect("Fail to push message");
+            RollupAnchor::push_message(&mut contract, &message_2).expect("Fail to push message");
+
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(queue_view.head, 0);
+            assert_eq!(queue_view.tail, 2);
+            assert_eq!(
+                queue_view.messages,
+                vec![(0, message_1.clone()), (1, message_2.clone())]
+            );
+
+            // paging
+            let queue_view = contract.get_queue_view(0, 1);
+            assert_eq!(queue_view.tail, 2);
+            assert_eq!(queue_view.messages, vec![(0, message_1)]);
+            let queue_view = contract.get_queue_view(1, 10);
+            assert_eq!(queue_view.messages, vec![(1, message_2)]);
+            let queue_view = contract.get_queue_view(2, 10);
+            assert!(queue_view.messages.is_empty());
+
+            // at most MAX_QUEUE_PAGE messages are read
+            for _ in 0..MAX_QUEUE_PAGE {
+                let message = LottoManagerRequestMessage::SetPaused(false, vec![101]);
+                RollupAnchor::push_message(&mut contract, &message).expect("Fail to push message");
+            }
+            let queue_view = contract.get_queue_view(0, u32::MAX);
+            assert_eq!(queue_view.messages.len(), MAX_QUEUE_PAGE as usize);
+            assert_eq!(queue_view.messages.last().map(|(index, _)| *index), Some(49));
+        }
+
+        #[ink::test]
+        fn test_schedule_config_change() {
+            let mut contract = Contract::new();
+            let config = Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            };
+            let new_config = Config {
+                nb_numbers: 5,
+                min_number: 1,
+                max_number: 60,
+            };
+
+            // the config is updated when the lotto is not started
+            contract.set_config(config).expect("Fail to set the config");
+            assert_eq!(RaffleConfig::get_config(&contract), Some(config));
+            assert_eq!(RaffleConfig::get_pending_config(&contract), None);
+
+            contract
+                .set_registration_contracts(vec![101, 102])
+                .expect("Fail to set the registration contracts");
+            contract.start(None).expect("Fail to start");
+
+            // the config change is scheduled for the next draw
+            contract.set_config(new_config).expect("Fail to set the config");
+            assert_eq!(RaffleConfig::get_config(&contract), Some(config));
+            assert_eq!(RaffleConfig::get_pending_config(&contract), Some(new_config));
+
+            // the new config is propagated before opening the registrations
+            contract
+                .inner_open_registrations()
+                .expect("Fail to open the registrations");
+            assert_eq!(RaffleConfig::get_config(&contract), Some(new_config));
+            assert_eq!(RaffleConfig::get_pending_config(&contract), None);
+            assert_eq!(RaffleManager::get_status(&contract), Ok(Status::Started));
+            assert!(RaffleManager::is_config_update_in_progress(&contract));
+
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(
+                queue_view.messages.last().map(|(_, message)| message.clone()),
+                Some(LottoManagerRequestMessage::UpdateConfig(new_config, vec![101, 102]))
+            );
+        }
+
+        #[ink::test]
+        fn test_registration_schedule() {
+            type Env = ink::env::DefaultEnvironment;
+            // Friday 2 January 1970, 10:00 UTC
+            ink::env::test::set_block_timestamp::<Env>(122_400_000);
+
+            let mut contract = Contract::new();
+            let config = Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            };
+            contract.set_config(config).expect("Fail to set the config");
+            contract
+                .set_registration_contracts(vec![101, 102])
+                .expect("Fail to set the registration contracts");
+            contract
+                .set_number_of_blocks_for_participation(10)
+                .expect("Fail to set the number of blocks");
+            contract.start(None).expect("Fail to start");
+            let config_hash: Vec<u8> =
+                hex::decode("1af688b7e4ccbd51529a15d28753270a04adf361d4eb1cbd9553ef19d353c656")
+                    .expect("hex decode failed");
+            // the config is propagated, the registrations are open
+            contract
+                .handle_started(vec![101, 102], &config_hash)
+                .expect("Fail to handle the started contracts");
+            contract
+                .handle_registrations_open(1, vec![101, 102])
+                .expect("Fail to handle the registrations open");
+
+            // without schedule, the registrations are closed after the number of blocks
+            let next_closing_registrations = contract.get_next_closing_registrations();
+            assert_eq!(contract.get_next_closing_registrations_timestamp(), 0);
+            assert!(!contract.can_close_registrations());
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Env>();
+            }
+            assert!(contract.can_close_registrations());
+
+            // the schedule is applied on the registrations in progress
+            contract
+                .set_registration_schedule(Some(Schedule::Daily { hour: 12, minute: 0 }))
+                .expect("Fail to set the schedule");
+            assert_eq!(
+                contract.get_next_closing_registrations(),
+                next_closing_registrations
+            );
+            // Friday 2 January 1970, 12:00 UTC
+            assert_eq!(
+                contract.get_next_closing_registrations_timestamp(),
+                129_600_000
+            );
+            assert_eq!(
+                RollupAnchor::get_value(&contract, NEXT_CLOSING_REGISTRATIONS_TIMESTAMP.encode()),
+                Some(129_600_000u64.encode())
+            );
+            // the number of blocks is reached but not the schedule
+            assert!(!contract.can_close_registrations());
+
+            // the schedule is reached, the number of blocks is ignored
+            ink::env::test::set_block_timestamp::<Env>(129_600_000);
+            assert!(contract.can_close_registrations());
+
+            // without schedule, only the number of blocks is checked
+            contract
+                .set_registration_schedule(None)
+                .expect("Fail to remove the schedule");
+            assert_eq!(contract.get_next_closing_registrations_timestamp(), 0);
+            assert_eq!(
+                RollupAnchor::get_value(&contract, NEXT_CLOSING_REGISTRATIONS_TIMESTAMP.encode()),
+                Some(0u64.encode())
+            );
+            assert!(contract.can_close_registrations());
+
+            // an incorrect schedule is rejected
+            assert_eq!(
+                contract.set_registration_schedule(Some(Schedule::Daily { hour: 24, minute: 0 })),
+                Err(ContractError::RaffleError(RaffleError::IncorrectSchedule))
+            );
+        }
+
+        #[ink::test]
+        fn test_propagate_ticket_price() {
+            let mut contract = Contract::new();
+            let config = Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            };
+            contract.set_config(config).expect("Fail to set the config");
+            contract
+                .set_registration_contracts(vec![101, 102, 103])
+                .expect("Fail to set the registration contracts");
+            contract
+                .set_ticket_price(100)
+                .expect("Fail to set the ticket price");
+            // the contract 102 settles the tickets with a token (1 token = 2.5 units of the jackpot)
+            contract
+                .set_settlement_asset(
+                    102,
+                    SettlementAsset {
+                        asset: lotto::PaymentAsset::Token(MultiAddress::AccountId20([1u8; 20])),
+                        rate_numerator: 5,
+                        rate_denominator: 2,
+                    },
+                )
+                .expect("Fail to set the settlement asset");
+
+            contract.start(None).expect("Fail to start");
+
+            // the ticket price cannot be updated once started
+            assert_eq!(
+                contract.set_ticket_price(200),
+                Err(ContractError::RaffleError(RaffleError::IncorrectStatus))
+            );
+
+            // the price is converted in the asset of each contract
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(
+                queue_view.messages,
+                vec![
+                    (
+                        0,
+                        LottoManagerRequestMessage::PropagateConfig(
+                            config,
+                            RevenueSplit::default(),
+                            100,
+                            vec![101, 103]
+                        )
+                    ),
+                    (
+                        1,
+                        LottoManagerRequestMessage::PropagateConfig(
+                            config,
+                            RevenueSplit::default(),
+                            40,
+                            vec![102]
+                        )
+                    ),
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn test_stop_during_results_propagation() {
+            let mut contract = Contract::new();
+            let config = Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            };
+            contract.set_config(config).expect("Fail to set the config");
+            contract
+                .set_registration_contracts(vec![101, 102])
+                .expect("Fail to set the registration contracts");
+            contract.start(None).expect("Fail to start");
+            let config_hash: Vec<u8> =
+                hex::decode("1af688b7e4ccbd51529a15d28753270a04adf361d4eb1cbd9553ef19d353c656")
+                    .expect("hex decode failed");
+            // the config is propagated, the registrations are open
+            contract
+                .handle_started(vec![101, 102], &config_hash)
+                .expect("Fail to handle the started contracts");
+            contract
+                .handle_registrations_open(1, vec![101, 102])
+                .expect("Fail to handle the registrations open");
+            RaffleManager::close_registrations(&mut contract)
+                .expect("Fail to close the registrations");
+            RaffleManager::try_to_generate_salt(&mut contract).expect("Fail to generate salt");
+
+            let numbers: Vec<Number> = vec![5, 40, 8, 2];
+            let hash: Vec<u8> =
+                hex::decode("0c70b0cb9b2d87768d1efacd6ca6a89be08a4c8c70855b54455f7f46caeeb155")
+                    .expect("hex decode failed");
+            RaffleManager::set_results(&mut contract, 1, numbers.clone())
+                .expect("Fail to save the results");
+            contract
+                .handle_winners(1, vec![], &hash)
+                .expect("Fail to handle the winners");
+            assert_eq!(
+                RaffleManager::get_status(&contract),
+                Ok(Status::DrawFinished)
+            );
+
+            // the results are still propagated, the lotto is not closed yet
+            contract.stop().expect("Fail to stop");
+            assert_eq!(
+                RaffleManager::get_status(&contract),
+                Ok(Status::DrawFinished)
+            );
+            assert!(RaffleManager::is_stop_requested(&contract));
+
+            contract
+                .handle_results_propagated(1, vec![101], &hash)
+                .expect("Fail to handle the results propagated");
+            assert_eq!(
+                RaffleManager::get_status(&contract),
+                Ok(Status::DrawFinished)
+            );
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(
+                queue_view
+                    .messages
+                    .last()
+                    .map(|(_, message)| message.clone()),
+                Some(LottoManagerRequestMessage::PropagateResults(
+                    1,
+                    numbers,
+                    false,
+                    vec![102]
+                ))
+            );
+
+            // the results are propagated in all contracts, the lotto is closed
+            contract
+                .handle_results_propagated(1, vec![102], &hash)
+                .expect("Fail to handle the results propagated");
+            assert_eq!(RaffleManager::get_status(&contract), Ok(Status::Closed));
+            assert!(!RaffleManager::is_draw_cancelled(&contract, 1));
+            let queue_view = contract.get_queue_view(0, MAX_QUEUE_PAGE);
+            assert_eq!(
+                queue_view
+                    .messages
+                    .last()
+                    .map(|(_, message)| message.clone()),
+                Some(LottoManagerRequestMessage::Stop(1, vec![101, 102]))
+            );
+        }
+
         #[ink::test]
         fn test_verify_config_hash() {
             let config = Config {
@@ -1,26 +1,57 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-#[openbrush::implementation(Ownable, AccessControl, Upgradeable)]
+#[openbrush::implementation(Ownable, AccessControl, Upgradeable, PSP34)]
 #[openbrush::contract]
 pub mod lotto_registration_contract {
     use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
     use lotto::{
         config, config::*, error::*, raffle_registration::*, DrawNumber, Number,
         RegistrationContractId,
     };
     use openbrush::contracts::access_control::*;
     use openbrush::contracts::ownable::*;
+    use openbrush::contracts::psp34::*;
+    use openbrush::contracts::traits::psp22::PSP22Ref;
     use openbrush::{modifiers, traits::Storage};
     use phat_rollup_anchor_ink::traits::{
         meta_transaction, meta_transaction::*, rollup_anchor, rollup_anchor::*,
     };
 
+    const TREASURY_ROLE: RoleType = ink::selector_id!("TREASURY");
+    const PAYOUT_ROLE: RoleType = ink::selector_id!("PAYOUT");
+
+    /// maximum number of active subscriptions by account
+    const MAX_SUBSCRIPTIONS_PER_ACCOUNT: u32 = 10;
+    /// maximum number of subscriptions played in one call of `play_subscriptions`
+    const MAX_SUBSCRIPTIONS_BATCH: u32 = 20;
+    /// maximum number of subscriptions read by `get_active_subscriptions`
+    pub const MAX_SUBSCRIPTIONS_PAGE: u32 = 50;
+
     /// Event emitted when the config is updated
     #[ink(event)]
     pub struct ConfigUpdated {
         config: Config,
     }
 
+    /// Event emitted when the revenue split is updated
+    #[ink(event)]
+    pub struct RevenueSplitUpdated {
+        revenue_split: RevenueSplit,
+    }
+
+    /// Event emitted when the ticket price is updated
+    #[ink(event)]
+    pub struct TicketPriceUpdated {
+        ticket_price: Balance,
+    }
+
+    /// Event emitted when the maximum number of tickets per account and per draw is updated
+    #[ink(event)]
+    pub struct MaxTicketsPerAccountUpdated {
+        max_tickets: Option<u32>,
+    }
+
     /// Event emitted when the workflow starts
     #[ink(event)]
     pub struct Started {
@@ -28,6 +59,61 @@ pub mod lotto_registration_contract {
         registration_contract_id: RegistrationContractId,
     }
 
+    /// Event emitted when a subscription is created
+    #[ink(event)]
+    pub struct SubscriptionCreated {
+        #[ink(topic)]
+        subscription_id: u32,
+        #[ink(topic)]
+        owner: AccountId,
+        numbers: Vec<Number>,
+        nb_draws: u32,
+        prepaid: Balance,
+    }
+
+    /// Event emitted when a subscription is cancelled and the remaining draws are refunded
+    #[ink(event)]
+    pub struct SubscriptionCancelled {
+        #[ink(topic)]
+        subscription_id: u32,
+        #[ink(topic)]
+        owner: AccountId,
+        refund: Balance,
+    }
+
+    /// Event emitted when a ticket (PSP34 token) is minted for a participation
+    #[ink(event)]
+    pub struct TicketMinted {
+        #[ink(topic)]
+        token_id: u128,
+        #[ink(topic)]
+        draw_number: DrawNumber,
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    /// Event emitted when a winning ticket is paid to its holder
+    #[ink(event)]
+    pub struct TicketPaid {
+        #[ink(topic)]
+        token_id: u128,
+        #[ink(topic)]
+        draw_number: DrawNumber,
+        #[ink(topic)]
+        owner: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the amount paid for a cancelled draw is refunded
+    #[ink(event)]
+    pub struct RefundClaimed {
+        #[ink(topic)]
+        draw_number: DrawNumber,
+        #[ink(topic)]
+        owner: AccountId,
+        amount: Balance,
+    }
+
     /// Event emitted when the registrations are open
     #[ink(event)]
     pub struct RegistrationsOpen {
@@ -66,6 +152,38 @@ pub mod lotto_registration_contract {
         has_winner: bool,
     }
 
+    /// Event emitted when a draw is cancelled
+    #[ink(event)]
+    pub struct DrawCancelled {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+        #[ink(topic)]
+        draw_number: DrawNumber,
+    }
+
+    /// Event emitted when the lotto is closed
+    #[ink(event)]
+    pub struct LottoClosed {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+        #[ink(topic)]
+        draw_number: DrawNumber,
+    }
+
+    /// Event emitted when the participations are paused
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+    }
+
+    /// Event emitted when the participations are unpaused
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        registration_contract_id: RegistrationContractId,
+    }
+
     /// Event emitted when the participation is registered
     #[ink(event)]
     pub struct ParticipationRegistered {
@@ -86,6 +204,88 @@ pub mod lotto_registration_contract {
         RaffleError(RaffleError),
         RollupAnchorError(RollupAnchorError),
         TransferError,
+        IncorrectPaymentAsset,
+        TokenTransferError,
+        MaxTicketsReached,
+        IncorrectNbDraws,
+        TooManySubscriptions,
+        UnknownSubscription,
+        NotSubscriptionOwner,
+        IncorrectRegistrationContract,
+        IncorrectNonce,
+        SignatureExpired,
+        InvalidSignature,
+        PSP34Error(PSP34Error),
+        UnknownTicket,
+        NotWinningTicket,
+        TicketAlreadyPaid,
+        IncorrectAmount,
+        DrawNotCancelled,
+        NothingToRefund,
+        NoPayout,
+    }
+
+    /// Ticket minted as PSP34 token for a participation
+    #[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Ticket {
+        /// registration contract where the participation is registered
+        pub registration_contract_id: RegistrationContractId,
+        /// draw number for which the participation is registered
+        pub draw_number: DrawNumber,
+        /// numbers of the ticket
+        pub numbers: Vec<Number>,
+    }
+
+    /// Payout of the winning tickets of a draw
+    #[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct DrawPayout {
+        /// amount paid for each winning ticket (jackpot / number of winning tickets)
+        pub share_per_ticket: Balance,
+        /// amount not paid yet
+        pub remaining: Balance,
+    }
+
+    /// Participation signed by the player and submitted by a relayer
+    #[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ParticipationRequest {
+        /// registration contract where the participation is registered
+        pub registration_contract: AccountId,
+        /// draw number for which the participation is registered
+        pub draw_number: DrawNumber,
+        /// numbers of the ticket
+        pub numbers: Vec<Number>,
+        /// nonce of the player, a request can be used only once
+        pub nonce: u64,
+        /// timestamp (in ms) after which the request is no longer valid
+        pub deadline: u64,
+    }
+
+    /// Subscription to play the same ticket for several draws
+    #[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Subscription {
+        /// account playing the ticket
+        pub owner: AccountId,
+        /// numbers of the ticket
+        pub numbers: Vec<Number>,
+        /// number of draws still to play
+        pub remaining_draws: u32,
+        /// amount paid for the remaining draws
+        pub prepaid: Balance,
+        /// last draw where the subscription was played (or skipped)
+        pub last_played_draw: DrawNumber,
     }
 
     /// convertor from AccessControlError to ContractError
@@ -109,6 +309,13 @@ pub mod lotto_registration_contract {
         }
     }
 
+    /// convertor from PSP34Error to ContractError
+    impl From<PSP34Error> for ContractError {
+        fn from(error: PSP34Error) -> Self {
+            ContractError::PSP34Error(error)
+        }
+    }
+
     /// convertor from ContractError to RollupAnchorError
     impl From<ContractError> for RollupAnchorError {
         fn from(error: ContractError) -> Self {
@@ -117,12 +324,20 @@ pub mod lotto_registration_contract {
         }
     }
 
+    /// return the numbers in ascending order, the tickets with the same numbers have the same key
+    fn sorted_numbers(numbers: &[Number]) -> Vec<Number> {
+        let mut numbers = numbers.to_vec();
+        numbers.sort_unstable();
+        numbers
+    }
+
     /// Message to request for action
     /// Message sent by the offchain rollup to the Ink! smart contract
     #[derive(scale::Encode, scale::Decode)]
     pub enum RequestForAction {
-        /// update the config, set the registration contract id for this contract and start the workflow
-        SetConfigAndStart(Config, RegistrationContractId),
+        /// update the config, set the registration contract id for this contract,
+        /// set the revenue split, the ticket price and start the workflow
+        SetConfigAndStart(Config, RegistrationContractId, RevenueSplit, Balance),
         /// open the registrations for the given draw number
         OpenRegistrations(DrawNumber),
         /// close the registrations for the given draw number
@@ -131,6 +346,14 @@ pub mod lotto_registration_contract {
         GenerateSalt(DrawNumber),
         /// set the results (winning numbers + true or false if we have a winner) for the given draw number
         SetResults(DrawNumber, Vec<Number>, bool),
+        /// stop the workflow, the draw in progress is cancelled
+        Stop(DrawNumber),
+        /// cancel the given draw
+        CancelDraw(DrawNumber),
+        /// pause (true) or unpause (false) the participations
+        SetPaused(bool),
+        /// update the config between two draws
+        UpdateConfig(Config),
     }
 
     // Contract storage
@@ -147,7 +370,52 @@ pub mod lotto_registration_contract {
         meta_transaction: meta_transaction::Data,
         #[storage_field]
         config: config::Data,
+        #[storage_field]
+        psp34: psp34::Data,
         registration_contract_id: RegistrationContractId,
+        /// PSP22 token used to pay the tickets, None for the native token
+        payment_token: Option<AccountId>,
+        /// maximum number of tickets per account and per draw, None for no limit
+        max_tickets_per_account: Option<u32>,
+        /// number of tickets registered by each account for each draw
+        tickets_per_account: Mapping<(DrawNumber, AccountId), u32>,
+        /// nonce used to generate different numbers for each quick pick
+        quick_pick_nonce: u64,
+        /// subscriptions by id
+        subscriptions: Mapping<u32, Subscription>,
+        /// ids of the subscriptions with remaining draws, by index
+        active_subscriptions: Mapping<u32, u32>,
+        /// index of the active subscriptions, by id
+        active_subscription_indexes: Mapping<u32, u32>,
+        /// number of subscriptions with remaining draws
+        nb_active_subscriptions: u32,
+        /// id of the next subscription
+        next_subscription_id: u32,
+        /// index of the next active subscription to play for the current draw,
+        /// the subscriptions before this index are already played (or skipped)
+        next_subscription_to_play: u32,
+        /// number of active subscriptions by account
+        nb_subscriptions: Mapping<AccountId, u32>,
+        /// nonces used by the players for the signed participations
+        participation_nonces: Mapping<AccountId, u64>,
+        /// mint a ticket (PSP34 token) for each participation
+        ticket_receipts: bool,
+        /// tickets by token id
+        tickets: Mapping<u128, Ticket>,
+        /// id of the next ticket
+        next_ticket_id: u128,
+        /// winning numbers by draw
+        winning_numbers: Mapping<DrawNumber, Vec<Number>>,
+        /// winning tickets already paid
+        paid_tickets: Mapping<u128, ()>,
+        /// number of tickets minted by draw and by numbers (sorted), used to count the winning tickets
+        nb_tickets_by_numbers: Mapping<(DrawNumber, Vec<Number>), u32>,
+        /// jackpot of the previous draws without winning ticket in this contract
+        carried_jackpot: Balance,
+        /// payout of the winning tickets by draw
+        draw_payouts: Mapping<DrawNumber, DrawPayout>,
+        /// amount paid by each account for each draw, refunded if the draw is cancelled
+        stakes: Mapping<(DrawNumber, AccountId), Balance>,
     }
 
     impl RaffleConfig for Contract {}
@@ -168,47 +436,702 @@ pub mod lotto_registration_contract {
             instance
         }
 
-        #[ink(message)]
+        /// Register the numbers for the current draw.
+        /// The value transferred must be the ticket price, it is split between the jackpot, the treasury and the reserve
+        #[ink(message, payable)]
         pub fn participate(&mut self, numbers: Vec<Number>) -> Result<(), ContractError> {
+            // the tickets are paid with the native token
+            if self.payment_token.is_some() {
+                return Err(ContractError::IncorrectPaymentAsset);
+            }
+            self.check_amount(1, self.env().transferred_value())?;
+            self.inner_participate(numbers)?;
+            self.inner_add_revenue(self.env().caller(), self.env().transferred_value())
+        }
+
+        /// Register the numbers for the current draw and pay the ticket with the PSP22 token.
+        /// The allowance must be given to this contract before.
+        /// The amount must be the ticket price, it is split between the jackpot, the treasury and the reserve
+        #[ink(message)]
+        pub fn participate_with_token(
+            &mut self,
+            numbers: Vec<Number>,
+            amount: Balance,
+        ) -> Result<(), ContractError> {
+            let token = self
+                .payment_token
+                .ok_or(ContractError::IncorrectPaymentAsset)?;
+            self.check_amount(1, amount)?;
+            self.inner_participate(numbers)?;
+            // transfer the tokens to this contract
+            PSP22Ref::transfer_from(
+                &token,
+                self.env().caller(),
+                self.env().account_id(),
+                amount,
+                Vec::new(),
+            )
+            .map_err(|_| ContractError::TokenTransferError)?;
+            self.inner_add_revenue(self.env().caller(), amount)
+        }
+
+        /// Register several tickets for the current draw.
+        /// The value transferred must be the price of all tickets, it is split between the jackpot, the treasury and the reserve
+        #[ink(message, payable)]
+        pub fn participate_batch(
+            &mut self,
+            numbers: Vec<Vec<Number>>,
+        ) -> Result<(), ContractError> {
+            // the tickets are paid with the native token
+            if self.payment_token.is_some() {
+                return Err(ContractError::IncorrectPaymentAsset);
+            }
+            let nb_tickets = u32::try_from(numbers.len()).map_err(|_| ContractError::IncorrectAmount)?;
+            self.check_amount(nb_tickets, self.env().transferred_value())?;
+            // check if the numbers are correct
+            for n in numbers {
+                self.inner_participate(n)?;
+            }
+            self.inner_add_revenue(self.env().caller(), self.env().transferred_value())
+        }
+
+        /// Register the numbers signed by the player (ecdsa) for the current draw.
+        /// The participation is recorded for the signer and the relayer pays the fees.
+        /// The value transferred by the relayer must be the ticket price, it is split between the jackpot, the treasury and the reserve
+        #[ink(message, payable)]
+        pub fn participate_with_signature(
+            &mut self,
+            participant: AccountId,
+            request: ParticipationRequest,
+            signature: [u8; 65],
+        ) -> Result<(), ContractError> {
+            // the tickets are paid with the native token
+            if self.payment_token.is_some() {
+                return Err(ContractError::IncorrectPaymentAsset);
+            }
+            self.check_amount(1, self.env().transferred_value())?;
+            self.verify_participation(participant, &request, &signature)?;
+            // the request cannot be used again
+            let nonce = request.nonce.checked_add(1).ok_or(RaffleError::AddOverFlow)?;
+            self.participation_nonces.insert(participant, &nonce);
+
+            // check if the numbers are correct
+            RaffleConfig::check_numbers(self, &request.numbers)?;
+            // check if the user can participate (raffle is open)
+            Raffle::check_can_participate(self)?;
+            // check the draw number signed by the player
+            if Raffle::get_draw_number(self)? != request.draw_number {
+                return Err(RaffleError::IncorrectDrawNumber.into());
+            }
+            self.register_ticket(participant, request.numbers)?;
+            self.inner_add_revenue(participant, self.env().transferred_value())
+        }
+
+        /// Verify the request is signed by the player
+        fn verify_participation(
+            &self,
+            participant: AccountId,
+            request: &ParticipationRequest,
+            signature: &[u8; 65],
+        ) -> Result<(), ContractError> {
+            if request.registration_contract != self.env().account_id() {
+                return Err(ContractError::IncorrectRegistrationContract);
+            }
+            if request.deadline < self.env().block_timestamp() {
+                return Err(ContractError::SignatureExpired);
+            }
+
+            // the message is hashed with blake2 before being signed
+            let mut message_hash = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(request, &mut message_hash);
+            let mut public_key = [0u8; 33];
+            ink::env::ecdsa_recover(signature, &message_hash, &mut public_key)
+                .map_err(|_| ContractError::InvalidSignature)?;
+            // the account id is the hash of the public key
+            let mut signer = [0u8; 32];
+            ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&public_key, &mut signer);
+            if AccountId::from(signer) != participant {
+                return Err(ContractError::InvalidSignature);
+            }
+
+            if request.nonce != self.get_participation_nonce(participant) {
+                return Err(ContractError::IncorrectNonce);
+            }
+            Ok(())
+        }
+
+        /// Return the nonce to use for the next participation signed by the given account
+        #[ink(message)]
+        pub fn get_participation_nonce(&self, account: AccountId) -> u64 {
+            self.participation_nonces.get(account).unwrap_or_default()
+        }
+
+        /// Register the given number of tickets with numbers generated by the contract.
+        /// The value transferred must be the price of all tickets, it is split between the jackpot, the treasury and the reserve
+        #[ink(message, payable)]
+        pub fn participate_quick_pick(&mut self, count: u32) -> Result<(), ContractError> {
+            // the tickets are paid with the native token
+            if self.payment_token.is_some() {
+                return Err(ContractError::IncorrectPaymentAsset);
+            }
+            self.check_amount(count, self.env().transferred_value())?;
+            for _ in 0..count {
+                // generate the numbers from the block, the caller and the nonce
+                let seed = scale::Encode::encode(&(
+                    self.env().block_number(),
+                    self.env().block_timestamp(),
+                    self.env().caller(),
+                    self.quick_pick_nonce,
+                ));
+                self.quick_pick_nonce = self.quick_pick_nonce.wrapping_add(1);
+                let numbers = RaffleConfig::quick_pick(self, &seed)?;
+                self.inner_participate(numbers)?;
+            }
+            self.inner_add_revenue(self.env().caller(), self.env().transferred_value())
+        }
+
+        /// Subscribe to play the same ticket for the next draws, starting from the next opening of the registrations.
+        /// The value transferred is the prepaid amount, ie the ticket price for all the draws
+        #[ink(message, payable)]
+        pub fn subscribe(
+            &mut self,
+            numbers: Vec<Number>,
+            nb_draws: u32,
+        ) -> Result<u32, ContractError> {
+            // the subscriptions are paid with the native token
+            if self.payment_token.is_some() {
+                return Err(ContractError::IncorrectPaymentAsset);
+            }
+            self.inner_subscribe(numbers, nb_draws, self.env().transferred_value())
+        }
+
+        /// Subscribe to play the same ticket for the next draws and pay with the PSP22 token.
+        /// The amount must be the ticket price for all the draws and the allowance must be given to this contract before
+        #[ink(message)]
+        pub fn subscribe_with_token(
+            &mut self,
+            numbers: Vec<Number>,
+            nb_draws: u32,
+            amount: Balance,
+        ) -> Result<u32, ContractError> {
+            let token = self
+                .payment_token
+                .ok_or(ContractError::IncorrectPaymentAsset)?;
+            let subscription_id = self.inner_subscribe(numbers, nb_draws, amount)?;
+            // transfer the tokens to this contract
+            PSP22Ref::transfer_from(
+                &token,
+                self.env().caller(),
+                self.env().account_id(),
+                amount,
+                Vec::new(),
+            )
+            .map_err(|_| ContractError::TokenTransferError)?;
+            Ok(subscription_id)
+        }
+
+        fn inner_subscribe(
+            &mut self,
+            numbers: Vec<Number>,
+            nb_draws: u32,
+            prepaid: Balance,
+        ) -> Result<u32, ContractError> {
+            // check if the numbers are correct
+            RaffleConfig::check_numbers(self, &numbers)?;
+            if nb_draws == 0 {
+                return Err(ContractError::IncorrectNbDraws);
+            }
+            // the ticket is prepaid for all the draws
+            self.check_amount(nb_draws, prepaid)?;
+            let owner = self.env().caller();
+            let nb_subscriptions = self.nb_subscriptions.get(owner).unwrap_or_default();
+            if nb_subscriptions >= MAX_SUBSCRIPTIONS_PER_ACCOUNT {
+                return Err(ContractError::TooManySubscriptions);
+            }
+            self.nb_subscriptions.insert(owner, &(nb_subscriptions + 1));
+
+            let subscription_id = self.next_subscription_id;
+            self.next_subscription_id = subscription_id
+                .checked_add(1)
+                .ok_or(RaffleError::AddOverFlow)?;
+
+            // the subscription is played from the next draw
+            let subscription = Subscription {
+                owner,
+                numbers: numbers.clone(),
+                remaining_draws: nb_draws,
+                prepaid,
+                last_played_draw: Raffle::get_draw_number(self)?,
+            };
+            self.subscriptions.insert(subscription_id, &subscription);
+            let index = self.nb_active_subscriptions;
+            self.active_subscriptions.insert(index, &subscription_id);
+            self.active_subscription_indexes
+                .insert(subscription_id, &index);
+            self.nb_active_subscriptions = index.checked_add(1).ok_or(RaffleError::AddOverFlow)?;
+
+            // emit the event
+            self.env().emit_event(SubscriptionCreated {
+                subscription_id,
+                owner,
+                numbers,
+                nb_draws,
+                prepaid,
+            });
+
+            Ok(subscription_id)
+        }
+
+        /// Cancel the subscription and refund the amount paid for the remaining draws
+        #[ink(message)]
+        pub fn cancel_subscription(&mut self, subscription_id: u32) -> Result<(), ContractError> {
+            let subscription = self
+                .subscriptions
+                .get(subscription_id)
+                .ok_or(ContractError::UnknownSubscription)?;
+            let owner = self.env().caller();
+            if subscription.owner != owner {
+                return Err(ContractError::NotSubscriptionOwner);
+            }
+
+            self.remove_subscription(subscription_id, owner)?;
+
+            // refund the remaining draws
+            let refund = subscription.prepaid;
+            self.inner_transfer(owner, refund)?;
+
+            // emit the event
+            self.env().emit_event(SubscriptionCancelled {
+                subscription_id,
+                owner,
+                refund,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_subscription(&self, subscription_id: u32) -> Option<Subscription> {
+            self.subscriptions.get(subscription_id)
+        }
+
+        /// Return the ids of the subscriptions with remaining draws, from the given index.
+        /// At most `limit` ids are read, the limit is capped to `MAX_SUBSCRIPTIONS_PAGE`.
+        #[ink(message)]
+        pub fn get_active_subscriptions(&self, from: u32, limit: u32) -> Vec<u32> {
+            let to = from
+                .saturating_add(limit.min(MAX_SUBSCRIPTIONS_PAGE))
+                .min(self.nb_active_subscriptions);
+            (from..to)
+                .filter_map(|index| self.active_subscriptions.get(index))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_nb_active_subscriptions(&self) -> u32 {
+            self.nb_active_subscriptions
+        }
+
+        /// Return the ids of the next subscriptions not played yet for the current draw,
+        /// ie the subscriptions played by the next call of `play_subscriptions`
+        #[ink(message)]
+        pub fn get_subscriptions_to_play(&self) -> Vec<u32> {
+            let draw_number = Raffle::get_draw_number(self).unwrap_or_default();
+            let to = self
+                .next_subscription_to_play
+                .saturating_add(MAX_SUBSCRIPTIONS_BATCH)
+                .min(self.nb_active_subscriptions);
+            (self.next_subscription_to_play..to)
+                .filter_map(|index| self.active_subscriptions.get(index))
+                .filter(|id| {
+                    self.subscriptions
+                        .get(id)
+                        .is_some_and(|subscription| subscription.last_played_draw < draw_number)
+                })
+                .collect()
+        }
+
+        /// Register a participation for the next subscriptions not played yet for the current draw,
+        /// at most `max` subscriptions (capped to `MAX_SUBSCRIPTIONS_BATCH`) are played.
+        /// Anyone can call it while the registrations are open, return the number of subscriptions handled.
+        /// The participations are emitted like the other ones to be included in the winners lookup.
+        /// A subscription that cannot be played for this draw (ie: the numbers don't match the new config)
+        /// is kept for the next draw
+        #[ink(message)]
+        pub fn play_subscriptions(&mut self, max: u32) -> Result<u32, ContractError> {
+            // check if the registrations are open
+            Raffle::check_can_participate(self)?;
+
+            let draw_number = Raffle::get_draw_number(self)?;
+            let max = max.min(MAX_SUBSCRIPTIONS_BATCH);
+            let mut nb_subscriptions = 0;
+            while self.next_subscription_to_play < self.nb_active_subscriptions
+                && nb_subscriptions < max
+            {
+                let subscription_id = self
+                    .active_subscriptions
+                    .get(self.next_subscription_to_play)
+                    .ok_or(ContractError::UnknownSubscription)?;
+                let mut subscription = self
+                    .subscriptions
+                    .get(subscription_id)
+                    .ok_or(ContractError::UnknownSubscription)?;
+                nb_subscriptions += 1;
+
+                // the subscriptions created during this draw are played from the next one
+                if subscription.last_played_draw >= draw_number {
+                    self.next_subscription_to_play += 1;
+                    continue;
+                }
+                subscription.last_played_draw = draw_number;
+
+                if RaffleConfig::check_numbers(self, &subscription.numbers).is_err()
+                    || self
+                        .register_ticket(subscription.owner, subscription.numbers.clone())
+                        .is_err()
+                {
+                    self.subscriptions.insert(subscription_id, &subscription);
+                    self.next_subscription_to_play += 1;
+                    continue;
+                }
+
+                // the prepaid amount is consumed draw by draw
+                let amount = subscription.prepaid / subscription.remaining_draws as Balance;
+                subscription.prepaid -= amount;
+                subscription.remaining_draws -= 1;
+                self.inner_add_revenue(subscription.owner, amount)?;
+
+                if subscription.remaining_draws == 0 {
+                    // the last active subscription is moved at this index
+                    self.remove_subscription(subscription_id, subscription.owner)?;
+                } else {
+                    self.subscriptions.insert(subscription_id, &subscription);
+                    self.next_subscription_to_play += 1;
+                }
+            }
+            Ok(nb_subscriptions)
+        }
+
+        /// Remove the subscription from the active ones.
+        /// The subscriptions already played for the current draw are kept before `next_subscription_to_play`
+        fn remove_subscription(
+            &mut self,
+            subscription_id: u32,
+            owner: AccountId,
+        ) -> Result<(), ContractError> {
+            let mut index = self
+                .active_subscription_indexes
+                .get(subscription_id)
+                .ok_or(ContractError::UnknownSubscription)?;
+            let last = self
+                .nb_active_subscriptions
+                .checked_sub(1)
+                .ok_or(RaffleError::SubOverFlow)?;
+            if index < self.next_subscription_to_play {
+                // the last subscription played takes this index
+                self.next_subscription_to_play -= 1;
+                self.move_active_subscription(self.next_subscription_to_play, index)?;
+                index = self.next_subscription_to_play;
+            }
+            // the last subscription takes this index
+            self.move_active_subscription(last, index)?;
+            self.active_subscriptions.remove(last);
+            self.active_subscription_indexes.remove(subscription_id);
+            self.nb_active_subscriptions = last;
+
+            self.subscriptions.remove(subscription_id);
+            let nb_subscriptions = self.nb_subscriptions.get(owner).unwrap_or_default();
+            self.nb_subscriptions
+                .insert(owner, &nb_subscriptions.saturating_sub(1));
+            Ok(())
+        }
+
+        fn move_active_subscription(&mut self, from: u32, to: u32) -> Result<(), ContractError> {
+            if from == to {
+                return Ok(());
+            }
+            let subscription_id = self
+                .active_subscriptions
+                .get(from)
+                .ok_or(ContractError::UnknownSubscription)?;
+            self.active_subscriptions.insert(to, &subscription_id);
+            self.active_subscription_indexes
+                .insert(subscription_id, &to);
+            Ok(())
+        }
+
+        /// Check the amount paid matches the price of the given number of tickets
+        fn check_amount(&self, nb_tickets: u32, amount: Balance) -> Result<(), ContractError> {
+            if RaffleConfig::get_tickets_amount(self, nb_tickets)? != amount {
+                return Err(ContractError::IncorrectAmount);
+            }
+            Ok(())
+        }
+
+        fn inner_add_revenue(
+            &mut self,
+            participant: AccountId,
+            amount: Balance,
+        ) -> Result<(), ContractError> {
+            // record the amount paid by the participant, refunded if the draw is cancelled
+            let draw_number = Raffle::get_draw_number(self)?;
+            let stake = self
+                .stakes
+                .get((draw_number, participant))
+                .unwrap_or_default()
+                .checked_add(amount)
+                .ok_or(RaffleError::AddOverFlow)?;
+            self.stakes.insert((draw_number, participant), &stake);
+            // split the amount paid
+            let revenue_split = RaffleConfig::get_revenue_split(self);
+            Raffle::add_revenue(self, amount, &revenue_split)?;
+            Ok(())
+        }
+
+        fn inner_participate(&mut self, numbers: Vec<Number>) -> Result<(), ContractError> {
             // check if the numbers are correct
             RaffleConfig::check_numbers(self, &numbers)?;
             // check if the user can participate (raffle is open)
             Raffle::check_can_participate(self)?;
+            self.register_ticket(Self::env().caller(), numbers)
+        }
+
+        fn register_ticket(
+            &mut self,
+            participant: AccountId,
+            numbers: Vec<Number>,
+        ) -> Result<(), ContractError> {
+            // check and record the ticket for this account
+            let draw_number = Raffle::get_draw_number(self)?;
+            let nb_tickets = self
+                .tickets_per_account
+                .get((draw_number, participant))
+                .unwrap_or_default()
+                .saturating_add(1);
+            if let Some(max_tickets) = self.max_tickets_per_account {
+                if nb_tickets > max_tickets {
+                    return Err(ContractError::MaxTicketsReached);
+                }
+            }
+            self.tickets_per_account
+                .insert((draw_number, participant), &nb_tickets);
+            // record the ticket for the participation threshold
+            Raffle::add_ticket(self)?;
             // save the participation with an event
-            let participant = Self::env().caller();
             let registration_contract_id = self.registration_contract_id;
-            let draw_number = Raffle::get_draw_number(self)?;
             self.env().emit_event(ParticipationRegistered {
                 registration_contract_id,
                 draw_number,
                 participant,
-                numbers,
+                numbers: numbers.clone(),
             });
+            // mint the ticket as receipt
+            if self.ticket_receipts {
+                self.mint_ticket(participant, draw_number, numbers)?;
+            }
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn participate_batch(
+        fn mint_ticket(
             &mut self,
-            numbers: Vec<Vec<Number>>,
+            owner: AccountId,
+            draw_number: DrawNumber,
+            numbers: Vec<Number>,
         ) -> Result<(), ContractError> {
-            // check if the numbers are correct
-            for n in numbers {
-                self.participate(n)?;
+            let token_id = self.next_ticket_id;
+            self.next_ticket_id = token_id.checked_add(1).ok_or(RaffleError::AddOverFlow)?;
+
+            let ticket = Ticket {
+                registration_contract_id: self.registration_contract_id,
+                draw_number,
+                numbers,
+            };
+            // count the tickets with the same numbers to know the number of winning tickets
+            let key = (draw_number, sorted_numbers(&ticket.numbers));
+            let nb_tickets = self
+                .nb_tickets_by_numbers
+                .get(&key)
+                .unwrap_or_default()
+                .checked_add(1)
+                .ok_or(RaffleError::AddOverFlow)?;
+            self.nb_tickets_by_numbers.insert(&key, &nb_tickets);
+
+            self.tickets.insert(token_id, &ticket);
+            psp34::Internal::_mint_to(self, owner, Id::U128(token_id))?;
+
+            // emit the event
+            self.env().emit_event(TicketMinted {
+                token_id,
+                draw_number,
+                owner,
+            });
+            Ok(())
+        }
+
+        /// Enable (true) or disable (false) the minting of a ticket (PSP34 token) for each participation.
+        /// It cannot be changed once started, all the winning tickets must be counted to split the jackpot
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_ticket_receipts(&mut self, enabled: bool) -> Result<(), ContractError> {
+            if Raffle::get_status(self)? != Status::NotStarted {
+                return Err(ContractError::RaffleError(RaffleError::IncorrectStatus));
+            }
+            self.ticket_receipts = enabled;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_ticket_receipts(&self) -> bool {
+            self.ticket_receipts
+        }
+
+        #[ink(message)]
+        pub fn get_ticket(&self, token_id: u128) -> Option<Ticket> {
+            self.tickets.get(token_id)
+        }
+
+        /// Return true if the ticket contains all the winning numbers of its draw
+        #[ink(message)]
+        pub fn is_winning_ticket(&self, token_id: u128) -> bool {
+            let Some(ticket) = self.tickets.get(token_id) else {
+                return false;
+            };
+            match self.winning_numbers.get(ticket.draw_number) {
+                Some(winning_numbers) => winning_numbers
+                    .iter()
+                    .all(|n| ticket.numbers.contains(n)),
+                None => false,
+            }
+        }
+
+        /// Return the payout of the winning tickets for the given draw
+        #[ink(message)]
+        pub fn get_draw_payout(&self, draw_number: DrawNumber) -> Option<DrawPayout> {
+            self.draw_payouts.get(draw_number)
+        }
+
+        /// Return the jackpot of the previous draws without winning ticket in this contract
+        #[ink(message)]
+        pub fn get_carried_jackpot(&self) -> Balance {
+            self.carried_jackpot
+        }
+
+        /// Pay the share of the jackpot to the holder of the winning ticket.
+        /// The ticket can be transferred before the payout, only the current holder is paid
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(PAYOUT_ROLE))]
+        pub fn pay_winning_ticket(&mut self, token_id: u128) -> Result<(), ContractError> {
+            let ticket = self
+                .tickets
+                .get(token_id)
+                .ok_or(ContractError::UnknownTicket)?;
+            if !self.is_winning_ticket(token_id) {
+                return Err(ContractError::NotWinningTicket);
+            }
+            if self.paid_tickets.contains(token_id) {
+                return Err(ContractError::TicketAlreadyPaid);
             }
+            let owner = psp34::Internal::_owner_of(self, &Id::U128(token_id))
+                .ok_or(ContractError::UnknownTicket)?;
+
+            // the share is deducted from the payout of the draw
+            let mut payout = self
+                .draw_payouts
+                .get(ticket.draw_number)
+                .ok_or(ContractError::NoPayout)?;
+            let amount = payout.share_per_ticket;
+            payout.remaining = payout
+                .remaining
+                .checked_sub(amount)
+                .ok_or(RaffleError::SubOverFlow)?;
+            self.draw_payouts.insert(ticket.draw_number, &payout);
 
+            self.paid_tickets.insert(token_id, &());
+            self.inner_transfer(owner, amount)?;
+
+            // emit the event
+            self.env().emit_event(TicketPaid {
+                token_id,
+                draw_number: ticket.draw_number,
+                owner,
+                amount,
+            });
             Ok(())
         }
 
+        #[ink(message)]
+        pub fn get_payout_role(&self) -> RoleType {
+            PAYOUT_ROLE
+        }
+
         #[ink(message)]
         pub fn get_registration_contract_id(&self) -> RegistrationContractId {
             self.registration_contract_id
         }
 
+        /// Set the PSP22 token used to pay the tickets (None for the native token).
+        /// The token can be changed only before the workflow starts
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_payment_token(&mut self, token: Option<AccountId>) -> Result<(), ContractError> {
+            if Raffle::get_status(self)? != Status::NotStarted {
+                return Err(ContractError::RaffleError(RaffleError::IncorrectStatus));
+            }
+            self.payment_token = token;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_payment_token(&self) -> Option<AccountId> {
+            self.payment_token
+        }
+
+        /// Set the maximum number of tickets per account and per draw (None for no limit).
+        /// It cannot be changed while a draw is in progress, all the participants of a draw must have the same limit
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_max_tickets_per_account(
+            &mut self,
+            max_tickets: Option<u32>,
+        ) -> Result<(), ContractError> {
+            if matches!(
+                Raffle::get_status(self)?,
+                Status::RegistrationsOpen | Status::RegistrationsClosed | Status::SaltGenerated
+            ) {
+                return Err(ContractError::RaffleError(RaffleError::IncorrectStatus));
+            }
+            self.max_tickets_per_account = max_tickets;
+
+            // emit the event
+            self.env()
+                .emit_event(MaxTicketsPerAccountUpdated { max_tickets });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_max_tickets_per_account(&self) -> Option<u32> {
+            self.max_tickets_per_account
+        }
+
+        /// Return the number of tickets the given account can still register for the current draw
+        /// (None if there is no limit)
+        #[ink(message)]
+        pub fn get_remaining_tickets(&self, account: AccountId) -> Option<u32> {
+            let max_tickets = self.max_tickets_per_account?;
+            let draw_number = Raffle::get_draw_number(self).unwrap_or_default();
+            let nb_tickets = self
+                .tickets_per_account
+                .get((draw_number, account))
+                .unwrap_or_default();
+            Some(max_tickets.saturating_sub(nb_tickets))
+        }
+
         fn inner_set_config_and_start(
             &mut self,
             config: Config,
             registration_contract_id: RegistrationContractId,
+            revenue_split: RevenueSplit,
+            ticket_price: Balance,
         ) -> Result<(), ContractError> {
             // check the status, we can set the config only when the raffle is not started yet
             let status = Raffle::get_status(self)?;
@@ -220,12 +1143,25 @@ pub mod lotto_registration_contract {
 
             // update the config
             RaffleConfig::set_config(self, config)?;
+            Raffle::set_config_in_kv_store(self, config);
 
             // emit the event
             self.env().emit_event(ConfigUpdated {
                 config,
             });
 
+            // update the revenue split
+            RaffleConfig::set_revenue_split(self, revenue_split)?;
+
+            // emit the event
+            self.env().emit_event(RevenueSplitUpdated { revenue_split });
+
+            // update the ticket price
+            RaffleConfig::set_ticket_price(self, ticket_price)?;
+
+            // emit the event
+            self.env().emit_event(TicketPriceUpdated { ticket_price });
+
             // start the workflow
             Raffle::start(self)?;
 
@@ -237,10 +1173,27 @@ pub mod lotto_registration_contract {
             Ok(())
         }
 
+        fn inner_update_config(&mut self, config: Config) -> Result<(), ContractError> {
+            // check the status, we can update the config only between two draws
+            Raffle::check_can_update_config(self)?;
+
+            // update the config
+            RaffleConfig::set_config(self, config)?;
+            Raffle::set_config_in_kv_store(self, config);
+
+            // emit the event
+            self.env().emit_event(ConfigUpdated { config });
+
+            Ok(())
+        }
+
         fn inner_open_registrations(
             &mut self,
             draw_number: DrawNumber,
         ) -> Result<(), ContractError> {
+            // the registrations are extended when they are open again for the same draw
+            let extension = Raffle::get_status(self)? == Status::RegistrationsClosed;
+
             // Open the registrations
             Raffle::open_registrations(self, draw_number)?;
 
@@ -251,6 +1204,11 @@ pub mod lotto_registration_contract {
                 draw_number,
             });
 
+            // the active subscriptions will be played for the new draw (see play_subscriptions)
+            if !extension {
+                self.next_subscription_to_play = 0;
+            }
+
             Ok(())
         }
 
@@ -299,6 +1257,10 @@ pub mod lotto_registration_contract {
 
             // save the results
             Raffle::save_results(self, draw_number, numbers.clone(), has_winner)?;
+            // save the winning numbers to check the tickets
+            self.winning_numbers.insert(draw_number, &numbers);
+            // split the jackpot between the winning tickets
+            self.settle_payout(draw_number, &numbers, has_winner)?;
 
             // emmit the event
             let registration_contract_id = self.registration_contract_id;
@@ -312,6 +1274,106 @@ pub mod lotto_registration_contract {
             Ok(())
         }
 
+        /// Split the jackpot (revenue of the draw + jackpot carried over) between the winning tickets.
+        /// Without winning ticket in this contract, the jackpot is carried over to the next draw
+        fn settle_payout(
+            &mut self,
+            draw_number: DrawNumber,
+            numbers: &[Number],
+            has_winner: bool,
+        ) -> Result<(), ContractError> {
+            let jackpot = self
+                .carried_jackpot
+                .checked_add(Raffle::get_revenue(self))
+                .ok_or(RaffleError::AddOverFlow)?;
+            let nb_winning_tickets = if has_winner {
+                self.nb_tickets_by_numbers
+                    .get((draw_number, sorted_numbers(numbers)))
+                    .unwrap_or_default()
+            } else {
+                0
+            };
+            if nb_winning_tickets == 0 {
+                self.carried_jackpot = jackpot;
+                return Ok(());
+            }
+
+            let share_per_ticket = jackpot / nb_winning_tickets as Balance;
+            let total = share_per_ticket
+                .checked_mul(nb_winning_tickets as Balance)
+                .ok_or(RaffleError::MulOverFlow)?;
+            // the rounding is carried over
+            self.carried_jackpot = jackpot - total;
+            self.draw_payouts.insert(
+                draw_number,
+                &DrawPayout {
+                    share_per_ticket,
+                    remaining: total,
+                },
+            );
+            Ok(())
+        }
+
+        fn inner_stop(&mut self, draw_number: DrawNumber) -> Result<(), ContractError> {
+            let current_draw_number = Raffle::get_draw_number(self)?;
+
+            // stop the workflow
+            let cancelled = Raffle::stop(self, draw_number)?;
+
+            // emit the events
+            let registration_contract_id = self.registration_contract_id;
+            if cancelled {
+                self.env().emit_event(DrawCancelled {
+                    registration_contract_id,
+                    draw_number: current_draw_number,
+                });
+            }
+            self.env().emit_event(LottoClosed {
+                registration_contract_id,
+                draw_number,
+            });
+
+            Ok(())
+        }
+
+        fn inner_cancel_draw(&mut self, draw_number: DrawNumber) -> Result<(), ContractError> {
+            // cancel the draw
+            Raffle::cancel_draw(self, draw_number)?;
+
+            // emit the event
+            let registration_contract_id = self.registration_contract_id;
+            self.env().emit_event(DrawCancelled {
+                registration_contract_id,
+                draw_number,
+            });
+
+            Ok(())
+        }
+
+        fn inner_set_paused(&mut self, paused: bool) -> Result<(), ContractError> {
+            if Raffle::is_paused(self) == paused {
+                // nothing to do
+                return Ok(());
+            }
+
+            // pause or unpause the participations
+            Raffle::set_paused(self, paused);
+
+            // emit the event
+            let registration_contract_id = self.registration_contract_id;
+            if paused {
+                self.env().emit_event(Paused {
+                    registration_contract_id,
+                });
+            } else {
+                self.env().emit_event(Unpaused {
+                    registration_contract_id,
+                });
+            }
+
+            Ok(())
+        }
+
         #[ink(message)]
         #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
         pub fn register_attestor(
@@ -334,11 +1396,57 @@ pub mod lotto_registration_contract {
         }
 
         #[ink(message)]
-        #[openbrush::modifiers(only_role(DEFAULT_ADMIN_ROLE))]
-        pub fn withdraw(&mut self, value: Balance) -> Result<(), ContractError> {
-            let caller = Self::env().caller();
+        pub fn get_treasury_role(&self) -> RoleType {
+            TREASURY_ROLE
+        }
+
+        /// Withdraw the given amount from the treasury.
+        /// The jackpot and the reserve cannot be withdrawn
+        #[ink(message)]
+        #[openbrush::modifiers(only_role(TREASURY_ROLE))]
+        pub fn withdraw_treasury(&mut self, value: Balance) -> Result<(), ContractError> {
+            Raffle::withdraw_treasury(self, value)?;
+            self.inner_transfer(Self::env().caller(), value)
+        }
+
+        /// Refund the amount paid by the caller for the given draw when this draw is cancelled
+        #[ink(message)]
+        pub fn claim_refund(&mut self, draw_number: DrawNumber) -> Result<(), ContractError> {
+            if !Raffle::is_draw_cancelled(self, draw_number) {
+                return Err(ContractError::DrawNotCancelled);
+            }
+            let owner = self.env().caller();
+            let amount = self.stakes.get((draw_number, owner)).unwrap_or_default();
+            if amount == 0 {
+                return Err(ContractError::NothingToRefund);
+            }
+            self.stakes.remove((draw_number, owner));
+            self.inner_transfer(owner, amount)?;
+
+            // emit the event
+            self.env().emit_event(RefundClaimed {
+                draw_number,
+                owner,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Return the amount paid by the given account for the given draw
+        #[ink(message)]
+        pub fn get_stake(&self, draw_number: DrawNumber, account: AccountId) -> Balance {
+            self.stakes.get((draw_number, account)).unwrap_or_default()
+        }
+
+        /// Transfer the given amount with the token used to pay the tickets
+        fn inner_transfer(&mut self, to: AccountId, value: Balance) -> Result<(), ContractError> {
+            if let Some(token) = self.payment_token {
+                PSP22Ref::transfer(&token, to, value, Vec::new())
+                    .map_err(|_| ContractError::TokenTransferError)?;
+                return Ok(());
+            }
             self.env()
-                .transfer(caller, value)
+                .transfer(to, value)
                 .map_err(|_| ContractError::TransferError)?;
             Ok(())
         }
@@ -351,8 +1459,18 @@ pub mod lotto_registration_contract {
                 .or(Err(RollupAnchorError::FailedToDecode))?;
 
             match request {
-                RequestForAction::SetConfigAndStart(config, registration_contract_id) => {
-                    self.inner_set_config_and_start(config, registration_contract_id)?;
+                RequestForAction::SetConfigAndStart(
+                    config,
+                    registration_contract_id,
+                    revenue_split,
+                    ticket_price,
+                ) => {
+                    self.inner_set_config_and_start(
+                        config,
+                        registration_contract_id,
+                        revenue_split,
+                        ticket_price,
+                    )?;
                 }
                 RequestForAction::OpenRegistrations(draw_number) => {
                     self.inner_open_registrations(draw_number)?;
@@ -366,6 +1484,18 @@ pub mod lotto_registration_contract {
                 RequestForAction::SetResults(draw_number, numbers, has_winner) => {
                     self.inner_set_results(draw_number, numbers, has_winner)?
                 }
+                RequestForAction::Stop(draw_number) => {
+                    self.inner_stop(draw_number)?;
+                }
+                RequestForAction::CancelDraw(draw_number) => {
+                    self.inner_cancel_draw(draw_number)?;
+                }
+                RequestForAction::SetPaused(paused) => {
+                    self.inner_set_paused(paused)?;
+                }
+                RequestForAction::UpdateConfig(config) => {
+                    self.inner_update_config(config)?;
+                }
             }
 
             Ok(())
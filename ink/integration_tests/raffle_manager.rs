@@ -3,10 +3,9 @@ use ink_e2e::subxt::tx::Signer;
 use ink_e2e::{build_message, PolkadotConfig};
 use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
 use openbrush::traits::AccountId;
-use scale::Decode;
 use scale::Encode;
 
-use lotto::config::Config;
+use lotto::config::{Config, RevenueSplit};
 use lotto::raffle_manager;
 use lotto::*;
 
@@ -112,6 +111,15 @@ async fn alice_starts_raffle(
     );
 }
 
+/// Encode the response as the reply to the last request removed from the queue
+fn encode_reply(response: LottoManagerResponseMessage, queue_head: u32) -> Vec<u8> {
+    LottoManagerReply {
+        request_index: Some(queue_head - 1),
+        response,
+    }
+    .encode()
+}
+
 async fn attestor_sends_config_propagated(
     client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     contract_id: &AccountId,
@@ -126,7 +134,7 @@ async fn attestor_sends_config_propagated(
         LottoManagerResponseMessage::ConfigPropagated(registration_contracts.clone(), config_hash.into());
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -160,7 +168,7 @@ async fn attestor_sends_all_registrations_open(
         LottoManagerResponseMessage::RegistrationsOpen(draw_number, registration_contracts.clone());
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -192,16 +200,16 @@ async fn attestor_sends_all_registrations_closed(
     client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     contract_id: &AccountId,
     draw_number: DrawNumber,
-    registration_contracts: Vec<RegistrationContractId>,
+    contracts_tickets: Vec<(RegistrationContractId, u32, Balance)>,
     queue_head: u32,
 ) {
     let payload = LottoManagerResponseMessage::RegistrationsClosed(
         draw_number,
-        registration_contracts.clone(),
+        contracts_tickets.clone(),
     );
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -229,7 +237,7 @@ async fn attestor_sends_salts(
     );
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -258,7 +266,7 @@ async fn attestor_sends_winning_numbers(
         LottoManagerResponseMessage::WinningNumbers(draw_number, numbers.clone(), config_salt_hash.into());
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -281,10 +289,10 @@ async fn attestor_sends_winners(
     numbers_hash: [u8; 32],
     queue_head: u32,
 ) {
-    let payload = LottoManagerResponseMessage::Winners(draw_number, winners.0.clone(), winners.1.clone(), numbers_hash.into());
+    let payload = LottoManagerResponseMessage::Winners(draw_number, winners.clone(), numbers_hash.into());
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -314,7 +322,7 @@ async fn attestor_sends_results_propagated(
     );
 
     let actions = vec![
-        HandleActionInput::Reply(payload.encode()),
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
         HandleActionInput::SetQueueHead(queue_head),
     ];
     let rollup_cond_eq =
@@ -338,6 +346,47 @@ async fn attestor_sends_results_propagated(
     assert!(result.contains_event("Contracts", "ContractEmitted"));
 }
 
+async fn alice_cancels_draw(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+) {
+    let cancel_draw =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel_draw(draw_number));
+    let result = client
+        .call(&ink_e2e::alice(), cancel_draw, 0, None)
+        .await
+        .expect("cancel draw failed");
+    // two events : DrawCancelled and MessageQueued
+    assert!(result.contains_event("Contracts", "ContractEmitted"));
+}
+
+async fn attestor_sends_draw_cancelled(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+    registration_contracts: Vec<RegistrationContractId>,
+    queue_head: u32,
+) {
+    let payload =
+        LottoManagerResponseMessage::DrawCancelled(draw_number, registration_contracts.clone());
+
+    let actions = vec![
+        HandleActionInput::Reply(encode_reply(payload, queue_head)),
+        HandleActionInput::SetQueueHead(queue_head),
+    ];
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+
+    let result = client
+        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+        .await
+        .expect("send draw cancelled failed");
+    assert!(result.contains_event("Contracts", "ContractEmitted"));
+}
+
 async fn get_draw_number(
     client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     contract_id: &AccountId,
@@ -412,6 +461,22 @@ async fn get_results(
     result.return_value()
 }
 
+async fn get_generated_salt(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+) -> Option<Salt> {
+    let get_generated_salt =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_generated_salt(draw_number));
+
+    let result = client
+        .call_dry_run(&ink_e2e::alice(), &get_generated_salt, 0, None)
+        .await;
+
+    result.return_value()
+}
+
 async fn get_winners(
     client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     contract_id: &AccountId,
@@ -428,60 +493,55 @@ async fn get_winners(
     result.return_value()
 }
 
-async fn get_messages_in_queue(
+async fn get_last_finished_draw(
     client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
     contract_id: &AccountId,
-) -> Vec<LottoManagerRequestMessage> {
-    const QUEUE_PREFIX: &[u8] = b"q/";
-    const QUEUE_HEAD_KEY: &[u8] = b"_head";
-    const QUEUE_TAIL_KEY: &[u8] = b"_tail";
-
-    let get_queue_head =
+) -> Option<raffle_manager::DrawRecord> {
+    let get_last_finished_draw =
         build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.get_value([QUEUE_PREFIX, QUEUE_HEAD_KEY].concat()));
+            .call(|contract| contract.get_last_finished_draw());
 
     let result = client
-        .call_dry_run(&ink_e2e::alice(), &get_queue_head, 0, None)
+        .call_dry_run(&ink_e2e::alice(), &get_last_finished_draw, 0, None)
         .await;
 
-    let queue_head: Option<Vec<u8>> = result.return_value();
-    let queue_head = match queue_head {
-        Some(v) => u32::decode(&mut v.as_slice()).ok().unwrap_or_default(),
-        None => 0,
-    };
+    result.return_value()
+}
 
-    let get_queue_tail =
+async fn get_jackpot(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+) -> Option<raffle_manager::JackpotInfo> {
+    let get_jackpot =
         build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.get_value([QUEUE_PREFIX, QUEUE_TAIL_KEY].concat()));
+            .call(|contract| contract.get_jackpot(draw_number));
 
     let result = client
-        .call_dry_run(&ink_e2e::alice(), &get_queue_tail, 0, None)
+        .call_dry_run(&ink_e2e::alice(), &get_jackpot, 0, None)
         .await;
 
-    let queue_tail: Option<Vec<u8>> = result.return_value();
-    let queue_tail = match queue_tail {
-        Some(v) => u32::decode(&mut v.as_slice()).ok().unwrap_or_default(),
-        None => 0,
-    };
+    result.return_value()
+}
 
-    let mut messages = Vec::new();
-    for i in queue_head..queue_tail {
-        let get_message =
-            build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-                .call(|contract| contract.get_value([QUEUE_PREFIX, &i.encode()].concat()));
-
-        let result = client
-            .call_dry_run(&ink_e2e::alice(), &get_message, 0, None)
-            .await;
-
-        let message: Option<Vec<u8>> = result.return_value();
-        if let Some(v) = message {
-            if let Some(m) = LottoManagerRequestMessage::decode(&mut v.as_slice()).ok() {
-                messages.push(m);
-            }
-        };
-    }
-    messages
+async fn get_messages_in_queue(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+) -> Vec<LottoManagerRequestMessage> {
+    let get_queue_view =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_queue_view(0, 50));
+
+    let result = client
+        .call_dry_run(&ink_e2e::alice(), &get_queue_view, 0, None)
+        .await;
+
+    let queue_view: QueueView = result.return_value();
+    queue_view
+        .messages
+        .into_iter()
+        .map(|(_, message)| message)
+        .collect()
 }
 
 #[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
@@ -527,7 +587,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     assert_eq!(messages.len(), 1);
     assert_eq!(
         messages[0],
-        LottoManagerRequestMessage::PropagateConfig(config.clone(), vec![101, 102, 103])
+        LottoManagerRequestMessage::PropagateConfig(config.clone(), RevenueSplit::default(), 0, vec![101, 102, 103])
     );
 
     let mut queue_head = 1;
@@ -548,7 +608,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     assert_eq!(messages.len(), 1);
     assert_eq!(
         messages[0],
-        LottoManagerRequestMessage::PropagateConfig(config.clone(), vec![101, 102, 103])
+        LottoManagerRequestMessage::PropagateConfig(config.clone(), RevenueSplit::default(), 0, vec![101, 102, 103])
     );
 
     // propagate the missing config
@@ -567,7 +627,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     assert_eq!(messages.len(), 1);
     assert_eq!(
         messages[0],
-        LottoManagerRequestMessage::PropagateConfig(config.clone(), vec![102])
+        LottoManagerRequestMessage::PropagateConfig(config.clone(), RevenueSplit::default(), 0, vec![102])
     );
 
     // propagate the missing config
@@ -648,7 +708,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
         &mut client,
         &contract_id,
         draw_number,
-        vec![103],
+        vec![(103, 2, 200)],
         queue_head,
     )
     .await;
@@ -669,7 +729,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
         &mut client,
         &contract_id,
         draw_number,
-        vec![101, 102],
+        vec![(101, 5, 500), (102, 0, 0)],
         queue_head,
     )
     .await;
@@ -759,7 +819,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     );
 
     // send no winner
-    let winners: Winners = (vec![], vec![]);
+    let winners: Winners = vec![];
     attestor_sends_winners(
         &mut client,
         &contract_id,
@@ -789,6 +849,16 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
         get_winners(&mut client, &contract_id, draw_number).await
     );
 
+    // check the archive
+    let draw = get_last_finished_draw(&mut client, &contract_id)
+        .await
+        .expect("the draw should be archived");
+    assert_eq!(draw.draw_number, draw_number);
+    assert_eq!(draw.config, config);
+    assert_eq!(draw.numbers, Some(numbers.clone()));
+    assert!(draw.winners.is_empty());
+    assert!(draw.close_block.is_some());
+
     // check the message in the queue
     let messages = get_messages_in_queue(&mut client, &contract_id).await;
     assert_eq!(messages.len(), 1);
@@ -874,7 +944,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
         &mut client,
         &contract_id,
         draw_number,
-        vec![101, 102, 103],
+        vec![(101, 5, 500), (102, 0, 0), (103, 2, 200)],
         queue_head,
     )
         .await;
@@ -924,8 +994,8 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     queue_head += 1;
 
     // send a winner
-    let dave_address = ink_e2e::dave().public_key().0;
-    let winners: Winners = (vec![dave_address], vec![]);
+    let dave_address = MultiAddress::AccountId32(ink_e2e::dave().public_key().0);
+    let winners: Winners = vec![dave_address.clone()];
     attestor_sends_winners(
         &mut client,
         &contract_id,
@@ -951,7 +1021,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
 
     // check the winners
     assert_eq!(
-        Some((vec![dave_address], vec![])),
+        Some(vec![dave_address]),
         get_winners(&mut client, &contract_id, draw_number).await
     );
 
@@ -984,56 +1054,820 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
 }
 
 #[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
-async fn test_bad_attestor(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+async fn test_cancel_draw(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     // given
     let contract_id = alice_instantiates_raffle_manager(&mut client).await;
 
-    // bob is not granted as attestor => it should not be able to send a message
-    let rollup_cond_eq =
-        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.rollup_cond_eq(vec![], vec![], vec![]));
-    let result = client.call(&ink_e2e::bob(), rollup_cond_eq, 0, None).await;
-    assert!(
-        result.is_err(),
-        "only attestor should be able to send messages"
-    );
+    let registration_contracts = vec![101, 102, 103];
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    // configure the raffle
+    alice_configures_raffle_manager(
+        &mut client,
+        &contract_id,
+        config.clone(),
+        registration_contracts.clone(),
+    )
+    .await;
 
     // bob is granted as attestor
     alice_grants_bob_as_attestor(&mut client, &contract_id).await;
 
-    // then bob is able to send a message
-    let rollup_cond_eq =
+    // start the raffle
+    alice_starts_raffle(&mut client, &contract_id, 10).await;
+
+    let mut queue_head = 1;
+
+    // propagate the config
+    attestor_sends_config_propagated(&mut client, &contract_id, vec![101, 102, 103], queue_head).await;
+    queue_head += 1;
+
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 11);
+
+    // propagate all registrations are open
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102, 103],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // no draw can be cancelled by a non admin
+    let cancel_draw =
         build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.rollup_cond_eq(vec![], vec![], vec![]));
-    let result = client
-        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
-        .await
-        .expect("rollup cond eq failed");
-    // no event
-    assert!(!result.contains_event("Contracts", "ContractEmitted"));
+            .call(|contract| contract.cancel_draw(draw_number));
+    let result = client.call(&ink_e2e::bob(), cancel_draw, 0, None).await;
+    assert!(result.is_err(), "only admin should be able to cancel a draw");
+
+    // close the registrations
+    alice_close_registrations(&mut client, &contract_id).await;
+    attestor_sends_all_registrations_closed(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, 5, 500), (102, 0, 0), (103, 2, 200)],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // send the salts
+    attestor_sends_salts(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, [1u8;32].to_vec()), (102, [2u8;32].to_vec()), (103, [3u8;32].to_vec())],
+        queue_head,
+    ).await;
+    queue_head += 1;
+
+    assert_eq!(
+        raffle_manager::Status::WaitingResult,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    assert!(get_generated_salt(&mut client, &contract_id, draw_number).await.is_some());
+
+    // the draw is stuck, another draw number cannot be cancelled
+    let cancel_draw =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel_draw(draw_number + 1));
+    let result = client.call(&ink_e2e::alice(), cancel_draw, 0, None).await;
+    assert!(result.is_err(), "only the current draw can be cancelled");
+
+    // cancel the draw
+    alice_cancels_draw(&mut client, &contract_id, draw_number).await;
+
+    assert_eq!(
+        raffle_manager::Status::DrawCancelled,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    assert_eq!(None, get_generated_salt(&mut client, &contract_id, draw_number).await);
+    assert_eq!(None, get_results(&mut client, &contract_id, draw_number).await);
+
+    // check the messages in the queue
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[1],
+        LottoManagerRequestMessage::CancelDraw(draw_number, vec![101, 102, 103])
+    );
+
+    // the response for the previous request is ignored
+    attestor_sends_winning_numbers(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![5, 40, 8, 2],
+        [0u8; 32],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    assert_eq!(
+        raffle_manager::Status::DrawCancelled,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    assert_eq!(None, get_results(&mut client, &contract_id, draw_number).await);
+
+    // propagate the cancellation
+    attestor_sends_draw_cancelled(&mut client, &contract_id, draw_number, vec![101], queue_head)
+        .await;
+    queue_head += 1;
+
+    // all contracts are not synched
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0],
+        LottoManagerRequestMessage::CancelDraw(draw_number, vec![102, 103])
+    );
+    assert_eq!(
+        raffle_manager::Status::DrawCancelled,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    // propagate the cancellation
+    attestor_sends_draw_cancelled(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![102, 103],
+        queue_head,
+    )
+    .await;
+
+    // all contracts are synched, the registrations are open for a new draw
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 12);
+    assert_eq!(
+        raffle_manager::Status::RegistrationsOpen,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0],
+        LottoManagerRequestMessage::OpenRegistrations(draw_number, vec![101, 102, 103])
+    );
 
     Ok(())
 }
 
 #[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
-async fn test_bad_messages(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+async fn test_cancel_draw_and_rerun(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     // given
     let contract_id = alice_instantiates_raffle_manager(&mut client).await;
 
+    let registration_contracts = vec![101, 102, 103];
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    // configure the raffle
+    alice_configures_raffle_manager(
+        &mut client,
+        &contract_id,
+        config.clone(),
+        registration_contracts.clone(),
+    )
+    .await;
+
     // bob is granted as attestor
     alice_grants_bob_as_attestor(&mut client, &contract_id).await;
 
-    let actions = vec![HandleActionInput::Reply(58u128.encode())];
-    let rollup_cond_eq =
-        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
-            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
-    let result = client.call(&ink_e2e::bob(), rollup_cond_eq, 0, None).await;
-    assert!(
-        result.is_err(),
-        "we should not be able to proceed bad messages"
-    );
+    // start the raffle
+    alice_starts_raffle(&mut client, &contract_id, 10).await;
 
-    Ok(())
+    let mut queue_head = 1;
+
+    // propagate the config
+    attestor_sends_config_propagated(&mut client, &contract_id, vec![101, 102, 103], queue_head).await;
+    queue_head += 1;
+
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 11);
+
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102, 103],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    alice_close_registrations(&mut client, &contract_id).await;
+    attestor_sends_all_registrations_closed(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, 5, 500), (102, 0, 0), (103, 2, 200)],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // the draw is stuck before the salts are generated => cancel it
+    alice_cancels_draw(&mut client, &contract_id, draw_number).await;
+    attestor_sends_draw_cancelled(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102, 103],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // the tickets of the cancelled draw are refunded, they are not rolled over
+    let jackpot = get_jackpot(&mut client, &contract_id, draw_number)
+        .await
+        .expect("jackpot not found");
+    assert_eq!(jackpot.revenue(), 700);
+    assert_eq!(jackpot.rolled_over, 0);
+    assert_eq!(jackpot.paid, 0);
+
+    // the draw is run again with a new draw number
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 12);
+    assert_eq!(
+        raffle_manager::Status::RegistrationsOpen,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    let jackpot = get_jackpot(&mut client, &contract_id, draw_number)
+        .await
+        .expect("jackpot not found");
+    assert_eq!(jackpot.carried_over, 0);
+
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102, 103],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    alice_close_registrations(&mut client, &contract_id).await;
+    attestor_sends_all_registrations_closed(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, 3, 300), (102, 0, 0), (103, 1, 100)],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    attestor_sends_salts(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, [1u8;32].to_vec()), (102, [2u8;32].to_vec()), (103, [3u8;32].to_vec())],
+        queue_head,
+    ).await;
+    queue_head += 1;
+
+    let generated_salt: [u8;32] = [94, 193, 212, 179, 22, 80, 18, 236, 194, 56, 99, 20, 16, 125, 123, 20, 14, 26, 212, 42, 96, 187, 51, 110, 129, 113, 120, 162, 223, 50, 36, 79];
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0],
+        LottoManagerRequestMessage::DrawNumbers(draw_number, config, generated_salt.to_vec())
+    );
+
+    let config_salt_hash: [u8;32] = hex::decode("c6aac4e20883f260241bbae6963be7ae78d9cc0136f0a2409aa40e0fdef11cb1")
+        .expect("hex decode failed")
+        .try_into()
+        .expect("incorrect length");
+
+    let numbers: Vec<Number> = vec![5, 40, 8, 2];
+    let numbers_hash: [u8;32] = hex::decode("0c70b0cb9b2d87768d1efacd6ca6a89be08a4c8c70855b54455f7f46caeeb155")
+        .expect("hex decode failed")
+        .try_into()
+        .expect("incorrect length");
+
+    attestor_sends_winning_numbers(
+        &mut client,
+        &contract_id,
+        draw_number,
+        numbers.clone(),
+        config_salt_hash,
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // no winner
+    attestor_sends_winners(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![],
+        numbers_hash.clone(),
+        queue_head,
+    )
+    .await;
+
+    // the draw run again is finished
+    assert_eq!(
+        raffle_manager::Status::DrawFinished,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    assert_eq!(
+        Some(numbers.clone()),
+        get_results(&mut client, &contract_id, draw_number).await
+    );
+    let draw = get_last_finished_draw(&mut client, &contract_id)
+        .await
+        .expect("the draw should be archived");
+    assert_eq!(draw.draw_number, draw_number);
+
+    // only the tickets of the new draw are rolled over
+    let jackpot = get_jackpot(&mut client, &contract_id, draw_number)
+        .await
+        .expect("jackpot not found");
+    assert_eq!(jackpot.rolled_over, 400);
+
+    Ok(())
+}
+
+#[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
+async fn test_participation_threshold(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_manager(&mut client).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    // configure the raffle
+    alice_configures_raffle_manager(&mut client, &contract_id, config.clone(), vec![101, 102])
+        .await;
+
+    // at least 10 tickets are required, the registrations can be extended once
+    let threshold = raffle_manager::ParticipationThreshold {
+        min_tickets: 10,
+        min_pot: 0,
+        extension_blocks: 0,
+        max_extensions: 1,
+    };
+    let set_participation_threshold =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_participation_threshold(Some(threshold)));
+    client
+        .call(&ink_e2e::alice(), set_participation_threshold, 0, None)
+        .await
+        .expect("set participation threshold failed");
+
+    // bob is granted as attestor
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    // start the raffle
+    alice_starts_raffle(&mut client, &contract_id, 10).await;
+
+    let mut queue_head = 1;
+
+    attestor_sends_config_propagated(&mut client, &contract_id, vec![101, 102], queue_head).await;
+    queue_head += 1;
+
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 11);
+
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // close the registrations, the threshold is not met
+    alice_close_registrations(&mut client, &contract_id).await;
+    attestor_sends_all_registrations_closed(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, 4, 400), (102, 3, 300)],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // the registrations are open again for the same draw
+    assert_eq!(draw_number, get_draw_number(&mut client, &contract_id).await);
+    assert_eq!(
+        raffle_manager::Status::RegistrationsOpen,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0],
+        LottoManagerRequestMessage::OpenRegistrations(draw_number, vec![101, 102])
+    );
+
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    // close the registrations, the threshold is still not met but the maximum of extensions is reached
+    alice_close_registrations(&mut client, &contract_id).await;
+    attestor_sends_all_registrations_closed(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![(101, 5, 500), (102, 3, 300)],
+        queue_head,
+    )
+    .await;
+
+    let get_nb_tickets =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_tickets(draw_number));
+    let nb_tickets = client
+        .call_dry_run(&ink_e2e::alice(), &get_nb_tickets, 0, None)
+        .await
+        .return_value();
+    assert_eq!(nb_tickets, 8);
+
+    // the draw continues, the salts are requested
+    assert_eq!(
+        raffle_manager::Status::WaitingSalt,
+        get_manager_status(&mut client, &contract_id).await
+    );
+    let messages = get_messages_in_queue(&mut client, &contract_id).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0],
+        LottoManagerRequestMessage::GenerateSalt(draw_number, vec![101, 102])
+    );
+
+    Ok(())
+}
+
+#[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
+async fn test_bad_attestor(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_manager(&mut client).await;
+
+    // bob is not granted as attestor => it should not be able to send a message
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], vec![]));
+    let result = client.call(&ink_e2e::bob(), rollup_cond_eq, 0, None).await;
+    assert!(
+        result.is_err(),
+        "only attestor should be able to send messages"
+    );
+
+    // bob is granted as attestor
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    // then bob is able to send a message
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], vec![]));
+    let result = client
+        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+        .await
+        .expect("rollup cond eq failed");
+    // no event
+    assert!(!result.contains_event("Contracts", "ContractEmitted"));
+
+    Ok(())
+}
+
+#[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
+async fn test_bad_messages(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_manager(&mut client).await;
+
+    // bob is granted as attestor
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let actions = vec![HandleActionInput::Reply(58u128.encode())];
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+    let result = client.call(&ink_e2e::bob(), rollup_cond_eq, 0, None).await;
+    assert!(
+        result.is_err(),
+        "we should not be able to proceed bad messages"
+    );
+
+    Ok(())
+}
+
+/// Reply sent by an attestor with the conditions on the status and the draw number of the manager
+async fn attestor_sends_registrations_closed_with_conditions(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+    expected_status: raffle_manager::Status,
+    expected_draw_number: DrawNumber,
+    queue_head: u32,
+) -> bool {
+    const STATUS: u32 = ink::selector_id!("STATUS");
+    const DRAW_NUMBER: u32 = ink::selector_id!("DRAW_NUMBER");
+
+    let conditions = vec![
+        (STATUS.encode(), Some(expected_status.encode())),
+        (DRAW_NUMBER.encode(), Some(expected_draw_number.encode())),
+    ];
+    let payload =
+        LottoManagerResponseMessage::RegistrationsClosed(draw_number, vec![(101, 1, 100), (102, 1, 100)]);
+    let actions = vec![HandleActionInput::Reply(encode_reply(payload, queue_head))];
+
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(conditions.clone(), vec![], actions.clone()));
+
+    client
+        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+        .await
+        .is_ok()
+}
+
+#[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
+async fn test_optimistic_locking(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_manager(&mut client).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    alice_configures_raffle_manager(&mut client, &contract_id, config.clone(), vec![101, 102])
+        .await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+    alice_starts_raffle(&mut client, &contract_id, 10).await;
+
+    let mut queue_head = 1;
+
+    attestor_sends_config_propagated(&mut client, &contract_id, vec![101, 102], queue_head).await;
+    queue_head += 1;
+
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 11);
+
+    attestor_sends_all_registrations_open(
+        &mut client,
+        &contract_id,
+        draw_number,
+        vec![101, 102],
+        queue_head,
+    )
+    .await;
+    queue_head += 1;
+
+    alice_close_registrations(&mut client, &contract_id).await;
+    assert_eq!(
+        raffle_manager::Status::RegistrationsClosed,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    // the conditions don't match with the draw number => the reply is rejected
+    assert!(
+        !attestor_sends_registrations_closed_with_conditions(
+            &mut client,
+            &contract_id,
+            draw_number,
+            raffle_manager::Status::RegistrationsClosed,
+            draw_number + 1,
+            queue_head,
+        )
+        .await,
+        "the reply should be rejected because the draw number doesn't match"
+    );
+
+    // the conditions don't match with the status => the reply is rejected
+    assert!(
+        !attestor_sends_registrations_closed_with_conditions(
+            &mut client,
+            &contract_id,
+            draw_number,
+            raffle_manager::Status::RegistrationsOpen,
+            draw_number,
+            queue_head,
+        )
+        .await,
+        "the reply should be rejected because the status doesn't match"
+    );
+    assert_eq!(
+        raffle_manager::Status::RegistrationsClosed,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    // first worker: the conditions match => the reply is applied
+    assert!(
+        attestor_sends_registrations_closed_with_conditions(
+            &mut client,
+            &contract_id,
+            draw_number,
+            raffle_manager::Status::RegistrationsClosed,
+            draw_number,
+            queue_head,
+        )
+        .await,
+        "the reply should be applied"
+    );
+    assert_eq!(
+        raffle_manager::Status::WaitingSalt,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    // second worker: same reply for the same phase => the reply is rejected
+    assert!(
+        !attestor_sends_registrations_closed_with_conditions(
+            &mut client,
+            &contract_id,
+            draw_number,
+            raffle_manager::Status::RegistrationsClosed,
+            draw_number,
+            queue_head,
+        )
+        .await,
+        "the reply should be rejected because the phase is already applied"
+    );
+    assert_eq!(
+        raffle_manager::Status::WaitingSalt,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    Ok(())
+}
+
+/// Reply sent by an attestor for the given request without moving the queue head
+async fn attestor_sends_reply(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    request_index: Option<u32>,
+    response: LottoManagerResponseMessage,
+) -> bool {
+    let reply = LottoManagerReply {
+        request_index,
+        response,
+    };
+    let actions = vec![HandleActionInput::Reply(reply.encode())];
+
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+
+    client
+        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+        .await
+        .is_ok()
+}
+
+#[ink_e2e::test(additional_contracts = "contracts/raffle_manager/Cargo.toml")]
+async fn test_replay_protection(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_manager(&mut client).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    alice_configures_raffle_manager(&mut client, &contract_id, config.clone(), vec![101, 102])
+        .await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+    alice_starts_raffle(&mut client, &contract_id, 10).await;
+
+    let config_hash: [u8; 32] = hex::decode("1af688b7e4ccbd51529a15d28753270a04adf361d4eb1cbd9553ef19d353c656")
+        .expect("hex decode failed")
+        .try_into()
+        .expect("incorrect length");
+
+    // the request 1 is not in the queue => the reply is rejected
+    assert!(
+        !attestor_sends_reply(
+            &mut client,
+            &contract_id,
+            Some(1),
+            LottoManagerResponseMessage::ConfigPropagated(vec![101, 102], config_hash.into()),
+        )
+        .await,
+        "the reply should be rejected because the request is unknown"
+    );
+
+    attestor_sends_config_propagated(&mut client, &contract_id, vec![101, 102], 1).await;
+
+    let draw_number = get_draw_number(&mut client, &contract_id).await;
+    assert_eq!(draw_number, 11);
+    assert_eq!(
+        raffle_manager::Status::RegistrationsOpen,
+        get_manager_status(&mut client, &contract_id).await
+    );
+
+    // the request 0 is already processed => the reply is rejected
+    assert!(
+        !attestor_sends_reply(
+            &mut client,
+            &contract_id,
+            Some(0),
+            LottoManagerResponseMessage::ConfigPropagated(vec![101, 102], config_hash.into()),
+        )
+        .await,
+        "the reply should be rejected because the request is already processed"
+    );
+    assert_eq!(Some(0), get_last_answered_request(&mut client, &contract_id).await);
+
+    // the reply to a request without index is rejected (the replay protection cannot be bypassed)
+    assert!(
+        !attestor_sends_reply(
+            &mut client,
+            &contract_id,
+            None,
+            LottoManagerResponseMessage::RegistrationsOpen(draw_number, vec![101, 102]),
+        )
+        .await,
+        "the reply should be rejected because the request index is missing"
+    );
+    assert_eq!(Some(0), get_last_answered_request(&mut client, &contract_id).await);
+
+    // the responses not answering a request are accepted without index
+    assert!(
+        attestor_sends_reply(
+            &mut client,
+            &contract_id,
+            None,
+            LottoManagerResponseMessage::CloseRegistrations(),
+        )
+        .await,
+        "the response should be accepted without index"
+    );
+    assert_eq!(Some(0), get_last_answered_request(&mut client, &contract_id).await);
+
+    // the request 1 is answered twice in the same transaction => the second reply is rejected
+    let reply = LottoManagerReply {
+        request_index: Some(1),
+        response: LottoManagerResponseMessage::RegistrationsOpen(draw_number, vec![101, 102]),
+    };
+    let actions = vec![
+        HandleActionInput::Reply(reply.encode()),
+        HandleActionInput::Reply(reply.encode()),
+        HandleActionInput::SetQueueHead(2),
+    ];
+    let rollup_cond_eq =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+    assert!(
+        client
+            .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+            .await
+            .is_err(),
+        "the transaction should be rejected because the request is answered twice"
+    );
+    assert_eq!(Some(0), get_last_answered_request(&mut client, &contract_id).await);
+
+    Ok(())
+}
+
+async fn get_last_answered_request(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+) -> Option<u32> {
+    let get_last_answered_request =
+        build_message::<lotto_registration_manager_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_last_answered_request());
+
+    let result = client
+        .call_dry_run(&ink_e2e::alice(), &get_last_answered_request, 0, None)
+        .await;
+
+    result.return_value()
 }
 
 ///
@@ -2,10 +2,12 @@ use ink::env::DefaultEnvironment;
 use ink_e2e::subxt::tx::Signer;
 use ink_e2e::{build_message, PolkadotConfig};
 use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
+use openbrush::contracts::psp34::psp34_external::PSP34;
+use openbrush::contracts::psp34::Id;
 use openbrush::traits::AccountId;
 use scale::Encode;
 
-use lotto::config::Config;
+use lotto::config::{Config, RevenueSplit};
 use lotto::*;
 
 use lotto::raffle_registration::raffle_external::Raffle;
@@ -56,8 +58,14 @@ async fn attestor_set_config_and_start(
     contract_id: &AccountId,
     config: Config,
     registration_contract_id: RegistrationContractId,
+    ticket_price: Balance,
 ) {
-    let payload = RequestForAction::SetConfigAndStart(config.clone(), registration_contract_id);
+    let payload = RequestForAction::SetConfigAndStart(
+        config.clone(),
+        registration_contract_id,
+        RevenueSplit::default(),
+        ticket_price,
+    );
 
     let actions = vec![HandleActionInput::Reply(payload.encode())];
     let rollup_cond_eq =
@@ -278,6 +286,7 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
         &contract_id,
         config.clone(),
         registration_contract_id,
+        0,
     )
     .await;
 
@@ -438,6 +447,597 @@ async fn test_raffles(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
     Ok(())
 }
 
+async fn get_remaining_tickets(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    account: AccountId,
+) -> Option<u32> {
+    let get_remaining_tickets =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_remaining_tickets(account));
+    client
+        .call_dry_run(&ink_e2e::alice(), &get_remaining_tickets, 0, None)
+        .await
+        .return_value()
+}
+
+#[ink_e2e::test(
+    additional_contracts = "contracts/raffle_registration/Cargo.toml"
+)]
+async fn test_max_tickets_per_account(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_registration(&mut client).await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+    attestor_set_config_and_start(&mut client, &contract_id, config, 33, 0).await;
+
+    let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+    // no limit by default
+    assert_eq!(None, get_remaining_tickets(&mut client, &contract_id, dave_address).await);
+
+    // only the admin can set the limit
+    let set_max_tickets =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_max_tickets_per_account(Some(2)));
+    let result = client.call(&ink_e2e::bob(), set_max_tickets, 0, None).await;
+    assert!(result.is_err(), "only the admin should be able to set the limit");
+    let set_max_tickets =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_max_tickets_per_account(Some(2)));
+    client
+        .call(&ink_e2e::alice(), set_max_tickets, 0, None)
+        .await
+        .expect("set max tickets failed");
+
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+    assert_eq!(Some(2), get_remaining_tickets(&mut client, &contract_id, dave_address).await);
+
+    // dave participates twice
+    participates(&mut client, &contract_id, &ink_e2e::dave(), vec![1, 2, 3, 4]).await;
+    participates(&mut client, &contract_id, &ink_e2e::dave(), vec![5, 6, 7, 8]).await;
+    assert_eq!(Some(0), get_remaining_tickets(&mut client, &contract_id, dave_address).await);
+
+    // the third ticket is rejected
+    let participate_batch =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.participate_batch(vec![vec![9, 10, 11, 12]]));
+    let result = client.call(&ink_e2e::dave(), participate_batch, 0, None).await;
+    assert!(result.is_err(), "dave should not be able to register a third ticket");
+
+    // charlie can still participate
+    participates(&mut client, &contract_id, &ink_e2e::charlie(), vec![1, 2, 3, 4]).await;
+
+    // the limit cannot be changed while the draw is in progress
+    let set_max_tickets =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_max_tickets_per_account(Some(3)));
+    let result = client
+        .call(&ink_e2e::alice(), set_max_tickets, 0, None)
+        .await;
+    assert!(
+        result.is_err(),
+        "the limit should not be changed during the draw"
+    );
+
+    // the draw is finished => the limit can be changed for the next one
+    attestor_close_registrations(&mut client, &contract_id, 10).await;
+    attestor_set_results(&mut client, &contract_id, 10, vec![1, 2, 3, 4], vec![]).await;
+    let set_max_tickets =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_max_tickets_per_account(Some(3)));
+    client
+        .call(&ink_e2e::alice(), set_max_tickets, 0, None)
+        .await
+        .expect("set max tickets failed");
+
+    // new draw => the tickets are counted again
+    attestor_open_registrations(&mut client, &contract_id, 11).await;
+    assert_eq!(Some(3), get_remaining_tickets(&mut client, &contract_id, dave_address).await);
+    participates(&mut client, &contract_id, &ink_e2e::dave(), vec![1, 2, 3, 4]).await;
+    assert_eq!(Some(2), get_remaining_tickets(&mut client, &contract_id, dave_address).await);
+
+    Ok(())
+}
+
+#[ink_e2e::test(
+    additional_contracts = "contracts/raffle_registration/Cargo.toml"
+)]
+async fn test_subscription(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_registration(&mut client).await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+    attestor_set_config_and_start(&mut client, &contract_id, config, 33, 100).await;
+
+    // the ticket must be prepaid for all the draws
+    let subscribe = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.subscribe(vec![1, 2, 3, 4], 3));
+    let result = client.call(&ink_e2e::dave(), subscribe, 0, None).await;
+    assert!(result.is_err(), "the prepaid amount should match the ticket price for all the draws");
+
+    // dave subscribes for 3 draws
+    let subscribe = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.subscribe(vec![1, 2, 3, 4], 3));
+    client
+        .call(&ink_e2e::dave(), subscribe, 300, None)
+        .await
+        .expect("subscribe failed");
+
+    // charlie subscribes for 2 draws and cancels it
+    let subscribe = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.subscribe(vec![5, 6, 7, 8], 2));
+    client
+        .call(&ink_e2e::charlie(), subscribe, 200, None)
+        .await
+        .expect("subscribe failed");
+
+    // only the owner can cancel the subscription
+    let cancel_subscription =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel_subscription(1));
+    let result = client
+        .call(&ink_e2e::dave(), cancel_subscription, 0, None)
+        .await;
+    assert!(result.is_err(), "only the owner should be able to cancel the subscription");
+    let cancel_subscription =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.cancel_subscription(1));
+    client
+        .call(&ink_e2e::charlie(), cancel_subscription, 0, None)
+        .await
+        .expect("cancel subscription failed");
+
+    // the subscription of dave is played once the registrations are open
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+    let get_subscriptions_to_play =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_subscriptions_to_play());
+    let subscriptions_to_play = client
+        .call_dry_run(&ink_e2e::alice(), &get_subscriptions_to_play, 0, None)
+        .await
+        .return_value();
+    assert_eq!(vec![0], subscriptions_to_play);
+
+    // anyone can play the subscriptions by batch
+    let play_subscriptions =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.play_subscriptions(10));
+    client
+        .call(&ink_e2e::eve(), play_subscriptions, 0, None)
+        .await
+        .expect("play subscriptions failed");
+
+    let get_nb_tickets = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.get_nb_tickets());
+    let nb_tickets = client
+        .call_dry_run(&ink_e2e::alice(), &get_nb_tickets, 0, None)
+        .await
+        .return_value();
+    assert_eq!(1, nb_tickets);
+    let subscriptions_to_play = client
+        .call_dry_run(&ink_e2e::alice(), &get_subscriptions_to_play, 0, None)
+        .await
+        .return_value();
+    assert!(subscriptions_to_play.is_empty());
+
+    let get_subscription =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_subscription(0));
+    let subscription = client
+        .call_dry_run(&ink_e2e::alice(), &get_subscription, 0, None)
+        .await
+        .return_value()
+        .expect("subscription not found");
+    assert_eq!(2, subscription.remaining_draws);
+    assert_eq!(200, subscription.prepaid);
+
+    // the subscription is not played again when the registrations are extended
+    attestor_close_registrations(&mut client, &contract_id, 10).await;
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+    let subscriptions_to_play = client
+        .call_dry_run(&ink_e2e::alice(), &get_subscriptions_to_play, 0, None)
+        .await
+        .return_value();
+    assert!(subscriptions_to_play.is_empty());
+    let subscription = client
+        .call_dry_run(&ink_e2e::alice(), &get_subscription, 0, None)
+        .await
+        .return_value()
+        .expect("subscription not found");
+    assert_eq!(2, subscription.remaining_draws);
+
+    // the number of active subscriptions is limited by account
+    for _ in 0..10 {
+        let subscribe =
+            build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.subscribe(vec![1, 2, 3, 4], 1));
+        client
+            .call(&ink_e2e::eve(), subscribe, 100, None)
+            .await
+            .expect("subscribe failed");
+    }
+    let subscribe = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.subscribe(vec![1, 2, 3, 4], 1));
+    let result = client.call(&ink_e2e::eve(), subscribe, 100, None).await;
+    assert!(result.is_err(), "the number of subscriptions should be limited by account");
+
+    // the other accounts can still subscribe
+    let subscribe = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.subscribe(vec![1, 2, 3, 4], 1));
+    client
+        .call(&ink_e2e::charlie(), subscribe, 100, None)
+        .await
+        .expect("subscribe failed");
+
+    let get_nb_active_subscriptions =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_nb_active_subscriptions());
+    let nb_active_subscriptions = client
+        .call_dry_run(&ink_e2e::alice(), &get_nb_active_subscriptions, 0, None)
+        .await
+        .return_value();
+    assert_eq!(12, nb_active_subscriptions);
+
+    Ok(())
+}
+
+#[ink_e2e::test(
+    additional_contracts = "contracts/raffle_registration/Cargo.toml"
+)]
+async fn test_ticket_receipts(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_registration(&mut client).await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+
+    // alice enables the tickets before starting
+    let set_ticket_receipts =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_ticket_receipts(true));
+    client
+        .call(&ink_e2e::alice(), set_ticket_receipts, 0, None)
+        .await
+        .expect("set ticket receipts failed");
+
+    attestor_set_config_and_start(&mut client, &contract_id, config, 33, 100).await;
+
+    // the tickets cannot be disabled once started
+    let set_ticket_receipts =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.set_ticket_receipts(false));
+    let result = client
+        .call(&ink_e2e::alice(), set_ticket_receipts, 0, None)
+        .await;
+    assert!(result.is_err(), "the tickets cannot be disabled once started");
+
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+
+    // the ticket price must be paid
+    let participate = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.participate(vec![1, 2, 3, 4]));
+    let result = client.call(&ink_e2e::dave(), participate, 50, None).await;
+    assert!(result.is_err(), "the amount should match the ticket price");
+
+    // dave participates => a ticket is minted
+    let participate = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.participate(vec![1, 2, 3, 4]));
+    client
+        .call(&ink_e2e::dave(), participate, 100, None)
+        .await
+        .expect("Participate failed");
+
+    let get_ticket = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.get_ticket(0));
+    let ticket = client
+        .call_dry_run(&ink_e2e::alice(), &get_ticket, 0, None)
+        .await
+        .return_value()
+        .expect("ticket not found");
+    assert_eq!(33, ticket.registration_contract_id);
+    assert_eq!(10, ticket.draw_number);
+    assert_eq!(vec![1, 2, 3, 4], ticket.numbers);
+
+    // dave transfers the ticket to charlie before the draw
+    let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+    let transfer = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.transfer(charlie_address, Id::U128(0), vec![]));
+    client
+        .call(&ink_e2e::dave(), transfer, 0, None)
+        .await
+        .expect("transfer failed");
+
+    let owner_of = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.owner_of(Id::U128(0)));
+    let owner = client
+        .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+        .await
+        .return_value();
+    assert_eq!(Some(charlie_address), owner);
+
+    attestor_close_registrations(&mut client, &contract_id, 10).await;
+    attestor_set_results(
+        &mut client,
+        &contract_id,
+        10,
+        vec![4, 3, 2, 1],
+        vec![charlie_address],
+    )
+    .await;
+
+    let is_winning_ticket =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.is_winning_ticket(0));
+    let is_winning_ticket = client
+        .call_dry_run(&ink_e2e::alice(), &is_winning_ticket, 0, None)
+        .await
+        .return_value();
+    assert!(is_winning_ticket);
+
+    // the only winning ticket gets the whole jackpot
+    let get_draw_payout =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_draw_payout(10));
+    let draw_payout = client
+        .call_dry_run(&ink_e2e::alice(), &get_draw_payout, 0, None)
+        .await
+        .return_value();
+    assert_eq!(
+        Some(DrawPayout {
+            share_per_ticket: 100,
+            remaining: 100
+        }),
+        draw_payout
+    );
+
+    // alice grants herself to pay the winning tickets
+    let get_payout_role =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_payout_role());
+    let payout_role = client
+        .call_dry_run(&ink_e2e::alice(), &get_payout_role, 0, None)
+        .await
+        .return_value();
+    let alice_address = ink::primitives::AccountId::from(ink_e2e::alice().public_key().0);
+    let grant_role = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.grant_role(payout_role, Some(alice_address)));
+    client
+        .call(&ink_e2e::alice(), grant_role, 0, None)
+        .await
+        .expect("grant the payout role failed");
+
+    // the holder (charlie) is paid
+    let pay_winning_ticket =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.pay_winning_ticket(0));
+    client
+        .call(&ink_e2e::alice(), pay_winning_ticket, 0, None)
+        .await
+        .expect("pay winning ticket failed");
+
+    let get_draw_payout =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.get_draw_payout(10));
+    let draw_payout = client
+        .call_dry_run(&ink_e2e::alice(), &get_draw_payout, 0, None)
+        .await
+        .return_value()
+        .expect("draw payout not found");
+    assert_eq!(0, draw_payout.remaining);
+
+    // the ticket cannot be paid twice
+    let pay_winning_ticket =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.pay_winning_ticket(0));
+    let result = client
+        .call(&ink_e2e::alice(), pay_winning_ticket, 0, None)
+        .await;
+    assert!(result.is_err(), "the ticket should not be paid twice");
+
+    Ok(())
+}
+
+///
+/// Test the participation signed by the player
+/// Charlie (ecdsa account) is the player
+/// Dave is the relayer
+///
+#[ink_e2e::test(
+    additional_contracts = "contracts/raffle_registration/Cargo.toml"
+)]
+async fn test_participate_with_signature(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_registration(&mut client).await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+    attestor_set_config_and_start(&mut client, &contract_id, config, 33, 100).await;
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+
+    // use the ecsda account because we are not able to verify the sr25519 signature
+    let keypair = subxt_signer::ecdsa::dev::charlie();
+    let player = ink::primitives::AccountId::from(
+        Signer::<PolkadotConfig>::account_id(&keypair).0,
+    );
+
+    let get_nonce = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.get_participation_nonce(player));
+    let nonce = client
+        .call_dry_run(&ink_e2e::alice(), &get_nonce, 0, None)
+        .await
+        .return_value();
+    assert_eq!(0, nonce);
+
+    // Charlie signs the participation
+    let request = ParticipationRequest {
+        registration_contract: contract_id.clone(),
+        draw_number: 10,
+        numbers: vec![1, 2, 3, 4],
+        nonce,
+        deadline: u64::MAX,
+    };
+    let signature = keypair.sign(&scale::Encode::encode(&request)).0;
+
+    // Dave submits the participation
+    let participate = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.participate_with_signature(player, request.clone(), signature));
+    client
+        .call(&ink_e2e::dave(), participate, 100, None)
+        .await
+        .expect("participate with signature failed");
+
+    // the nonce is incremented
+    let nonce = client
+        .call_dry_run(&ink_e2e::alice(), &get_nonce, 0, None)
+        .await
+        .return_value();
+    assert_eq!(1, nonce);
+
+    // the same request cannot be submitted again
+    let participate = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.participate_with_signature(player, request.clone(), signature));
+    let result = client.call(&ink_e2e::dave(), participate, 100, None).await;
+    assert!(result.is_err(), "the request should not be used twice");
+
+    // the numbers cannot be changed by the relayer
+    let request = ParticipationRequest {
+        numbers: vec![5, 6, 7, 8],
+        ..request
+    };
+    let participate = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.participate_with_signature(player, request.clone(), signature));
+    let result = client.call(&ink_e2e::dave(), participate, 100, None).await;
+    assert!(result.is_err(), "the signature should not match");
+
+    Ok(())
+}
+
+async fn attestor_cancel_draw(
+    client: &mut ink_e2e::Client<PolkadotConfig, DefaultEnvironment>,
+    contract_id: &AccountId,
+    draw_number: DrawNumber,
+) {
+    let payload = RequestForAction::CancelDraw(draw_number);
+
+    let actions = vec![HandleActionInput::Reply(payload.encode())];
+    let rollup_cond_eq =
+        build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+            .call(|contract| contract.rollup_cond_eq(vec![], vec![], actions.clone()));
+
+    client
+        .call(&ink_e2e::bob(), rollup_cond_eq, 0, None)
+        .await
+        .expect("cancel draw failed");
+
+    // check the draw number and the status
+    assert_eq!(draw_number, get_draw_number(client, contract_id).await);
+    assert_eq!(
+        raffle_registration::Status::DrawCancelled,
+        get_status(client, contract_id).await
+    );
+}
+
+#[ink_e2e::test(
+    additional_contracts = "contracts/raffle_registration/Cargo.toml"
+)]
+async fn test_claim_refund(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    // given
+    let contract_id = alice_instantiates_raffle_registration(&mut client).await;
+    alice_grants_bob_as_attestor(&mut client, &contract_id).await;
+
+    let config = Config {
+        nb_numbers: 4,
+        min_number: 1,
+        max_number: 50,
+    };
+    attestor_set_config_and_start(&mut client, &contract_id, config, 33, 100).await;
+    attestor_open_registrations(&mut client, &contract_id, 10).await;
+
+    // dave participates twice
+    for numbers in [vec![1, 2, 3, 4], vec![5, 6, 7, 8]] {
+        let participate =
+            build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+                .call(|contract| contract.participate(numbers.clone()));
+        client
+            .call(&ink_e2e::dave(), participate, 100, None)
+            .await
+            .expect("Participate failed");
+    }
+
+    let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+    let get_stake = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.get_stake(10, dave_address));
+    let stake = client
+        .call_dry_run(&ink_e2e::alice(), &get_stake, 0, None)
+        .await
+        .return_value();
+    assert_eq!(200, stake);
+
+    // the draw is not cancelled
+    let claim_refund = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.claim_refund(10));
+    let result = client.call(&ink_e2e::dave(), claim_refund, 0, None).await;
+    assert!(result.is_err(), "the draw is not cancelled");
+
+    attestor_close_registrations(&mut client, &contract_id, 10).await;
+    attestor_cancel_draw(&mut client, &contract_id, 10).await;
+
+    // the revenue of the draw is refunded
+    let get_revenue = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.get_revenue());
+    let revenue = client
+        .call_dry_run(&ink_e2e::alice(), &get_revenue, 0, None)
+        .await
+        .return_value();
+    assert_eq!(0, revenue);
+
+    // charlie has nothing to refund
+    let claim_refund = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.claim_refund(10));
+    let result = client.call(&ink_e2e::charlie(), claim_refund, 0, None).await;
+    assert!(result.is_err(), "charlie has nothing to refund");
+
+    // dave claims the refund
+    let claim_refund = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.claim_refund(10));
+    client
+        .call(&ink_e2e::dave(), claim_refund, 0, None)
+        .await
+        .expect("claim refund failed");
+
+    let stake = client
+        .call_dry_run(&ink_e2e::alice(), &get_stake, 0, None)
+        .await
+        .return_value();
+    assert_eq!(0, stake);
+
+    // the refund can be claimed only once
+    let claim_refund = build_message::<lotto_registration_contract::ContractRef>(contract_id.clone())
+        .call(|contract| contract.claim_refund(10));
+    let result = client.call(&ink_e2e::dave(), claim_refund, 0, None).await;
+    assert!(result.is_err(), "the refund is already claimed");
+
+    Ok(())
+}
+
 #[ink_e2e::test(
     additional_contracts = "contracts/raffle_registration/Cargo.toml"
 )]
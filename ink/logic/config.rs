@@ -1,12 +1,16 @@
 use crate::error::RaffleError;
 use crate::error::RaffleError::*;
-use crate::Number;
+use crate::{Balance, Number};
+use ink::prelude::vec::Vec;
 use openbrush::traits::Storage;
 
 #[derive(Default, Debug)]
 #[openbrush::storage_item]
 pub struct Data {
     config: Option<Config>,
+    pending_config: Option<Config>,
+    revenue_split: Option<RevenueSplit>,
+    ticket_price: Option<Balance>,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
@@ -20,10 +24,49 @@ pub struct Config {
     pub max_number: Number,
 }
 
+/// Split of the revenue of the tickets, in percent
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RevenueSplit {
+    /// part added to the jackpot of the current draw
+    pub jackpot: u8,
+    /// part kept by the operator
+    pub treasury: u8,
+    /// part kept in reserve to seed the jackpot of the next draw
+    pub reserve: u8,
+}
+
+/// By default, all the revenue goes to the jackpot
+impl Default for RevenueSplit {
+    fn default() -> Self {
+        RevenueSplit {
+            jackpot: 100,
+            treasury: 0,
+            reserve: 0,
+        }
+    }
+}
+
 #[openbrush::trait_definition]
 pub trait RaffleConfig: Storage<Data> {
     fn set_config(&mut self, config: Config) -> Result<(), RaffleError> {
         // check the config
+        self.check_config(&config)?;
+
+        self.data::<Data>().config = Some(config);
+        Ok(())
+    }
+
+    #[ink(message)]
+    fn get_config(&self) -> Option<Config> {
+        self.data::<Data>().config
+    }
+
+    /// check if the config is valid
+    fn check_config(&self, config: &Config) -> Result<(), RaffleError> {
         if config.nb_numbers == 0 {
             return Err(IncorrectConfig);
         }
@@ -32,13 +75,65 @@ pub trait RaffleConfig: Storage<Data> {
             return Err(IncorrectConfig);
         }
 
+        Ok(())
+    }
+
+    /// schedule a config change, the config will be updated when it is applied (ie at the next draw)
+    fn set_pending_config(&mut self, config: Config) -> Result<(), RaffleError> {
+        // check the config
+        self.check_config(&config)?;
+
+        self.data::<Data>().pending_config = Some(config);
+        Ok(())
+    }
+
+    /// return the config change scheduled, if any
+    #[ink(message)]
+    fn get_pending_config(&self) -> Option<Config> {
+        self.data::<Data>().pending_config
+    }
+
+    /// apply the config change scheduled, if any, and return the new config
+    fn apply_pending_config(&mut self) -> Option<Config> {
+        let config = self.data::<Data>().pending_config.take()?;
         self.data::<Data>().config = Some(config);
+        Some(config)
+    }
+
+    fn set_revenue_split(&mut self, revenue_split: RevenueSplit) -> Result<(), RaffleError> {
+        // the parts must cover all the revenue
+        let total = revenue_split.jackpot as u16
+            + revenue_split.treasury as u16
+            + revenue_split.reserve as u16;
+        if total != 100 {
+            return Err(IncorrectRevenueSplit);
+        }
+
+        self.data::<Data>().revenue_split = Some(revenue_split);
         Ok(())
     }
 
     #[ink(message)]
-    fn get_config(&self) -> Option<Config> {
-        self.data::<Data>().config
+    fn get_revenue_split(&self) -> RevenueSplit {
+        self.data::<Data>().revenue_split.unwrap_or_default()
+    }
+
+    fn set_ticket_price(&mut self, ticket_price: Balance) -> Result<(), RaffleError> {
+        self.data::<Data>().ticket_price = Some(ticket_price);
+        Ok(())
+    }
+
+    /// return the price of a ticket
+    #[ink(message)]
+    fn get_ticket_price(&self) -> Balance {
+        self.data::<Data>().ticket_price.unwrap_or_default()
+    }
+
+    /// return the amount to pay for the given number of tickets
+    fn get_tickets_amount(&self, nb_tickets: u32) -> Result<Balance, RaffleError> {
+        self.get_ticket_price()
+            .checked_mul(nb_tickets as Balance)
+            .ok_or(MulOverFlow)
     }
 
     /// return the config and throw an error of the config is missing
@@ -84,6 +179,49 @@ pub trait RaffleConfig: Storage<Data> {
 
         Ok(())
     }
+
+    /// generate valid numbers from the given seed with the current config (quick pick)
+    fn quick_pick(&self, seed: &[u8]) -> Result<Vec<Number>, RaffleError> {
+        let config = self.ensure_config()?;
+        quick_pick_numbers(&config, seed)
+    }
+}
+
+/// Generate valid numbers without duplicate from the given seed (quick pick).
+/// The same seed always gives the same numbers.
+/// The numbers are picked with the Floyd's algorithm to stop after nb_numbers iterations
+pub fn quick_pick_numbers(config: &Config, seed: &[u8]) -> Result<Vec<Number>, RaffleError> {
+    use ink::env::hash;
+
+    if config.nb_numbers == 0 || config.min_number > config.max_number {
+        return Err(IncorrectConfig);
+    }
+    let range = (config.max_number - config.min_number) as u32 + 1;
+    let nb_numbers = config.nb_numbers as u32;
+    if nb_numbers > range {
+        return Err(IncorrectConfig);
+    }
+
+    let mut numbers = Vec::with_capacity(nb_numbers as usize);
+    for j in (range - nb_numbers)..range {
+        // random index between 0 and j
+        let mut input = seed.to_vec();
+        input.extend_from_slice(&j.to_le_bytes());
+        let mut output = <hash::Blake2x256 as hash::HashOutput>::Type::default();
+        ink::env::hash_bytes::<hash::Blake2x256>(&input, &mut output);
+        let mut random = [0u8; 8];
+        random.copy_from_slice(&output[..8]);
+        let index = (u64::from_le_bytes(random) % (j as u64 + 1)) as Number;
+
+        let number = config.min_number + index;
+        if numbers.contains(&number) {
+            numbers.push(config.min_number + j as Number);
+        } else {
+            numbers.push(number);
+        }
+    }
+
+    Ok(numbers)
 }
 
 #[cfg(test)]
@@ -161,6 +299,46 @@ mod tests {
         assert_eq!(config.max_number, 50);
     }
 
+    #[ink::test]
+    fn test_pending_config() {
+        let mut contract = Contract::new();
+
+        contract
+            .set_config(Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            })
+            .expect("failed to set the config");
+
+        assert_eq!(contract.get_pending_config(), None);
+        assert_eq!(contract.apply_pending_config(), None);
+
+        let result = contract.set_pending_config(Config {
+            nb_numbers: 5,
+            min_number: 50,
+            max_number: 50,
+        });
+        assert_eq!(result, Err(IncorrectConfig));
+
+        let new_config = Config {
+            nb_numbers: 5,
+            min_number: 1,
+            max_number: 60,
+        };
+        contract
+            .set_pending_config(new_config)
+            .expect("failed to schedule the config");
+
+        // the current config is not updated yet
+        assert_eq!(contract.get_pending_config(), Some(new_config));
+        assert_eq!(contract.get_config().map(|c| c.nb_numbers), Some(4));
+
+        assert_eq!(contract.apply_pending_config(), Some(new_config));
+        assert_eq!(contract.get_config(), Some(new_config));
+        assert_eq!(contract.get_pending_config(), None);
+    }
+
     #[ink::test]
     fn test_ensure_same_config() {
         let mut contract = Contract::new();
@@ -238,4 +416,103 @@ mod tests {
         let result = contract.check_numbers(vec![9u16, 10, 25, 0].as_slice());
         assert_eq!(result, Err(IncorrectNumbers));
     }
+
+    #[ink::test]
+    fn test_revenue_split() {
+        let mut contract = Contract::new();
+
+        // by default, all the revenue goes to the jackpot
+        assert_eq!(contract.get_revenue_split(), RevenueSplit::default());
+        assert_eq!(contract.get_revenue_split().jackpot, 100);
+
+        let result = contract.set_revenue_split(RevenueSplit {
+            jackpot: 80,
+            treasury: 10,
+            reserve: 5,
+        });
+        assert_eq!(result, Err(IncorrectRevenueSplit));
+
+        let result = contract.set_revenue_split(RevenueSplit {
+            jackpot: 200,
+            treasury: 100,
+            reserve: 0,
+        });
+        assert_eq!(result, Err(IncorrectRevenueSplit));
+
+        let revenue_split = RevenueSplit {
+            jackpot: 80,
+            treasury: 15,
+            reserve: 5,
+        };
+        contract
+            .set_revenue_split(revenue_split)
+            .expect("failed to set the revenue split");
+        assert_eq!(contract.get_revenue_split(), revenue_split);
+    }
+
+    #[ink::test]
+    fn test_ticket_price() {
+        let mut contract = Contract::new();
+
+        // free by default
+        assert_eq!(contract.get_ticket_price(), 0);
+        assert_eq!(contract.get_tickets_amount(3), Ok(0));
+
+        contract
+            .set_ticket_price(100)
+            .expect("failed to set the ticket price");
+        assert_eq!(contract.get_ticket_price(), 100);
+        assert_eq!(contract.get_tickets_amount(1), Ok(100));
+        assert_eq!(contract.get_tickets_amount(3), Ok(300));
+
+        contract
+            .set_ticket_price(Balance::MAX)
+            .expect("failed to set the ticket price");
+        assert_eq!(contract.get_tickets_amount(2), Err(MulOverFlow));
+    }
+
+    #[ink::test]
+    fn test_quick_pick() {
+        let mut contract = Contract::new();
+
+        assert_eq!(contract.quick_pick(&[1, 2, 3]), Err(ConfigNotSet));
+
+        contract
+            .set_config(Config {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            })
+            .expect("failed to set the config");
+
+        let numbers = contract.quick_pick(&[1, 2, 3]).expect("failed to pick numbers");
+        contract
+            .check_numbers(numbers.as_slice())
+            .expect("failed to check numbers");
+        // no duplicate
+        for (i, number) in numbers.iter().enumerate() {
+            assert!(!numbers[i + 1..].contains(number), "duplicate number");
+        }
+        // deterministic
+        assert_eq!(contract.quick_pick(&[1, 2, 3]), Ok(numbers.clone()));
+        assert_ne!(contract.quick_pick(&[1, 2, 4]), Ok(numbers));
+
+        // all the numbers are picked when the range is full
+        let config = Config {
+            nb_numbers: 5,
+            min_number: 10,
+            max_number: 14,
+        };
+        let mut numbers = quick_pick_numbers(&config, &[7]).expect("failed to pick numbers");
+        numbers.sort();
+        assert_eq!(numbers, vec![10, 11, 12, 13, 14]);
+
+        // not enough numbers in the range
+        let config = Config {
+            nb_numbers: 6,
+            min_number: 10,
+            max_number: 14,
+        };
+        assert_eq!(quick_pick_numbers(&config, &[7]), Err(IncorrectConfig));
+    }
 }
@@ -0,0 +1,126 @@
+use crate::error::RaffleError;
+use crate::error::RaffleError::*;
+use crate::RegistrationContractId;
+
+/// Family of the chain where a registration contract is deployed.
+/// The discriminant is encoded in the registration contract id.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ChainFamily {
+    /// ids picked by hand before the structured scheme (the 32 most significant bits are zero),
+    /// accepted for any kind of contract
+    Legacy = 0,
+    Substrate = 1,
+    Evm = 2,
+    Solana = 3,
+    Cosmos = 4,
+    Move = 5,
+}
+
+impl TryFrom<u8> for ChainFamily {
+    type Error = RaffleError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ChainFamily::Legacy),
+            1 => Ok(ChainFamily::Substrate),
+            2 => Ok(ChainFamily::Evm),
+            3 => Ok(ChainFamily::Solana),
+            4 => Ok(ChainFamily::Cosmos),
+            5 => Ok(ChainFamily::Move),
+            _ => Err(IncorrectRegistrationContractId),
+        }
+    }
+}
+
+const CHAIN_FAMILY_SHIFT: u32 = 120;
+const CHAIN_ID_SHIFT: u32 = 32;
+const RESERVED_MASK: u128 = ((1 << 24) - 1) << 96;
+
+/// Structured registration contract id, unique as long as the local index is unique on a given chain.
+/// Layout of the u128 (from the most significant bits):
+/// - 8 bits: chain family (0 for the legacy ids)
+/// - 24 bits: reserved, must be zero
+/// - 64 bits: chain id (ie: EVM chain id, parachain id)
+/// - 32 bits: local index of the contract on this chain
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ShardedContractId {
+    pub chain_family: ChainFamily,
+    pub chain_id: u64,
+    pub index: u32,
+}
+
+impl ShardedContractId {
+    pub fn new(chain_family: ChainFamily, chain_id: u64, index: u32) -> Self {
+        Self {
+            chain_family,
+            chain_id,
+            index,
+        }
+    }
+
+    /// encode the structured id into a registration contract id
+    pub fn to_id(&self) -> RegistrationContractId {
+        ((self.chain_family as u128) << CHAIN_FAMILY_SHIFT)
+            | ((self.chain_id as u128) << CHAIN_ID_SHIFT)
+            | self.index as u128
+    }
+
+    /// decode the registration contract id
+    pub fn from_id(id: RegistrationContractId) -> Result<Self, RaffleError> {
+        if id & RESERVED_MASK != 0 {
+            return Err(IncorrectRegistrationContractId);
+        }
+        let chain_family = ChainFamily::try_from((id >> CHAIN_FAMILY_SHIFT) as u8)?;
+        let chain_id = (id >> CHAIN_ID_SHIFT) as u64;
+        let index = id as u32;
+        Ok(Self {
+            chain_family,
+            chain_id,
+            index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn test_encode_decode() {
+        let id = ShardedContractId::new(ChainFamily::Evm, 1287, 3);
+        assert_eq!(id.to_id(), 0x0200_0000_0000_0000_0000_0507_0000_0003);
+        assert_eq!(ShardedContractId::from_id(id.to_id()), Ok(id));
+
+        let id = ShardedContractId::new(ChainFamily::Move, u64::MAX, u32::MAX);
+        assert_eq!(ShardedContractId::from_id(id.to_id()), Ok(id));
+    }
+
+    #[ink::test]
+    fn test_decode_legacy_ids() {
+        let id = ShardedContractId::from_id(10).expect("legacy id expected");
+        assert_eq!(id, ShardedContractId::new(ChainFamily::Legacy, 0, 10));
+        assert_eq!(id.to_id(), 10);
+    }
+
+    #[ink::test]
+    fn test_decode_incorrect_ids() {
+        // unknown chain family
+        assert_eq!(
+            ShardedContractId::from_id(0x0600_0000_0000_0000_0000_0000_0000_0001),
+            Err(IncorrectRegistrationContractId)
+        );
+        // reserved bits
+        assert_eq!(
+            ShardedContractId::from_id(0x0100_0100_0000_0000_0000_0000_0000_0001),
+            Err(IncorrectRegistrationContractId)
+        );
+    }
+}
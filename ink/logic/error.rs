@@ -14,4 +14,15 @@ pub enum RaffleError {
     ExistingWinners,
     AddOverFlow,
     FailedToDecode,
+    IncorrectRegistrationContractId,
+    DuplicateRegistrationContract,
+    StopRequested,
+    Paused,
+    UnknownRegistrationContract,
+    IncorrectSchedule,
+    IncorrectRevenueSplit,
+    InsufficientTreasury,
+    IncorrectExchangeRate,
+    MulOverFlow,
+    SubOverFlow,
 }
@@ -3,14 +3,54 @@
 pub type RegistrationContractId = u128;
 pub type DrawNumber = u32;
 pub type Number = u16;
+pub type Balance = u128;
 pub type Salt = ink::prelude::vec::Vec<u8>;
 pub type AccountId32 = [u8; 32];
 pub type AccountId20 = [u8; 20];
 
+/// Account of a participant, whatever the ecosystem of the registration contract.
+/// New kinds of account must be added at the end to keep the SCALE encoding.
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum MultiAddress {
+    /// Substrate account (SS58 encoded)
+    AccountId32(AccountId32),
+    /// EVM account (0x prefixed hex encoded)
+    AccountId20(AccountId20),
+    /// Ed25519 public key not SS58 encoded (ie: Solana, base58 encoded)
+    Ed25519([u8; 32]),
+    /// Cosmos account (bech32 encoded)
+    /// arg1: human readable part
+    /// arg2: data
+    Bech32(ink::prelude::string::String, ink::prelude::vec::Vec<u8>),
+    /// Move account (ie: Aptos, Sui, 0x prefixed hex encoded)
+    Move([u8; 32]),
+}
+
+/// Asset used to pay the tickets in a registration contract.
+/// New kinds of asset must be added at the end to keep the SCALE encoding.
+#[derive(Default, Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum PaymentAsset {
+    /// native token of the chain
+    #[default]
+    Native,
+    /// fungible token (PSP22, ERC-20, ...) identified by the address of its contract
+    Token(MultiAddress),
+}
+
 pub mod config;
+pub mod contract_id;
 pub mod error;
 pub mod raffle_manager;
 pub mod raffle_registration;
+pub mod schedule;
 
 #[cfg(test)]
 mod test_contract;
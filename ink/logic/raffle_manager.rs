@@ -1,5 +1,8 @@
+use crate::config::Config;
 use crate::error::{RaffleError, RaffleError::*};
-use crate::{AccountId20, AccountId32, DrawNumber, Number, RegistrationContractId, Salt};
+use crate::{
+    Balance, DrawNumber, MultiAddress, Number, PaymentAsset, RegistrationContractId, Salt,
+};
 use ink::prelude::vec::Vec;
 use ink::storage::Mapping;
 use openbrush::traits::Storage;
@@ -8,8 +11,13 @@ use scale::{Decode, Encode};
 
 const STATUS: u32 = ink::selector_id!("STATUS");
 const DRAW_NUMBER: u32 = ink::selector_id!("DRAW_NUMBER");
+const DRAW_CONFIG: u32 = ink::selector_id!("DRAW_CONFIG");
+const LAST_FAILURE: u32 = ink::selector_id!("LAST_FAILURE");
 
-pub type Winners = (Vec<AccountId32>, Vec<AccountId20>);
+/// Maximum number of draws read in one call of `get_draws`
+pub const MAX_DRAWS_PAGE: u32 = 50;
+
+pub type Winners = Vec<MultiAddress>;
 
 #[derive(Default, Debug)]
 #[openbrush::storage_item]
@@ -21,6 +29,168 @@ pub struct Data {
     results: Mapping<DrawNumber, Vec<Number>>,
     winners: Mapping<DrawNumber, Winners>,
     min_number_salts: u8,
+    stop_requested: bool,
+    cancelled_draws: Mapping<DrawNumber, bool>,
+    paused_requested: Mapping<RegistrationContractId, bool>,
+    paused: Mapping<RegistrationContractId, bool>,
+    participation_threshold: Option<ParticipationThreshold>,
+    nb_tickets: Mapping<(DrawNumber, RegistrationContractId), u32>,
+    nb_extensions: Mapping<DrawNumber, u8>,
+    nb_failures: Mapping<RegistrationContractId, u32>,
+    draws: Mapping<DrawNumber, DrawInfo>,
+    last_finished_draw: Option<DrawNumber>,
+    config_not_synchronized: Vec<RegistrationContractId>,
+    jackpots: Mapping<DrawNumber, JackpotInfo>,
+    jackpot_to_carry: Balance,
+    settlement_assets: Mapping<RegistrationContractId, SettlementAsset>,
+}
+
+/// Info saved when the registrations are open and closed for a draw
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DrawInfo {
+    open_block: u32,
+    close_block: Option<u32>,
+    config: Config,
+}
+
+/// Summary of a draw
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct DrawRecord {
+    pub draw_number: DrawNumber,
+    /// block when the registrations are open
+    pub open_block: u32,
+    /// block when the registrations are closed (the last time if the registrations are extended)
+    pub close_block: Option<u32>,
+    /// config used for this draw
+    pub config: Config,
+    /// registration contracts having contributed to the salt
+    pub contributing_contracts: Vec<RegistrationContractId>,
+    /// salt used to draw the numbers
+    pub salt: Option<Salt>,
+    /// winning numbers
+    pub numbers: Option<Vec<Number>>,
+    /// winners (empty if there is no winner or if the winners are not known yet)
+    pub winners: Winners,
+    /// number of tickets recorded by the registration contracts, if known
+    pub nb_tickets: Vec<(RegistrationContractId, u32)>,
+    /// true if the draw is cancelled
+    pub cancelled: bool,
+}
+
+/// Accounting of the jackpot for a draw
+#[derive(Debug, Default, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct JackpotInfo {
+    /// amount rolled over from the previous draws
+    pub carried_over: Balance,
+    /// amount paid for the tickets in each registration contract,
+    /// converted with the exchange rate of the asset used by this contract
+    pub contributions: Vec<(RegistrationContractId, Balance)>,
+    /// amount paid to the winners
+    pub paid: Balance,
+    /// amount rolled over to the next draw when there is no winner
+    pub rolled_over: Balance,
+}
+
+impl JackpotInfo {
+    /// Return the amount paid for the tickets of this draw in all registration contracts
+    pub fn revenue(&self) -> Balance {
+        self.contributions
+            .iter()
+            .fold(0, |total, (_, amount)| total.saturating_add(*amount))
+    }
+
+    /// Return the total amount of the jackpot
+    pub fn total(&self) -> Balance {
+        self.carried_over.saturating_add(self.revenue())
+    }
+}
+
+/// Asset in which a registration contract settles the tickets, with the exchange rate used
+/// to convert the amounts in the unit of the jackpot (amount * numerator / denominator)
+#[derive(Debug, Eq, PartialEq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SettlementAsset {
+    pub asset: PaymentAsset,
+    pub rate_numerator: Balance,
+    pub rate_denominator: Balance,
+}
+
+/// By default, the tickets are paid with the native token and the amounts are not converted
+impl Default for SettlementAsset {
+    fn default() -> Self {
+        SettlementAsset {
+            asset: PaymentAsset::Native,
+            rate_numerator: 1,
+            rate_denominator: 1,
+        }
+    }
+}
+
+impl SettlementAsset {
+    /// Convert the given amount in the unit of the jackpot
+    pub fn convert(&self, amount: Balance) -> Result<Balance, RaffleError> {
+        amount
+            .checked_mul(self.rate_numerator)
+            .ok_or(MulOverFlow)?
+            .checked_div(self.rate_denominator)
+            .ok_or(IncorrectExchangeRate)
+    }
+
+    /// Convert the given amount in the unit of the jackpot into this asset
+    pub fn convert_back(&self, amount: Balance) -> Result<Balance, RaffleError> {
+        amount
+            .checked_mul(self.rate_denominator)
+            .ok_or(MulOverFlow)?
+            .checked_div(self.rate_numerator)
+            .ok_or(IncorrectExchangeRate)
+    }
+}
+
+/// Rule to keep the registrations open until enough tickets are recorded
+/// or enough revenue is collected across all chains
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ParticipationThreshold {
+    /// minimum number of tickets to close the registrations (0 for no minimum)
+    pub min_tickets: u32,
+    /// minimum revenue of the draw, converted with the exchange rates, to close the registrations (0 for no minimum)
+    pub min_pot: Balance,
+    /// number of blocks added to the window when the threshold is not met
+    pub extension_blocks: u32,
+    /// maximum number of extensions for a draw
+    pub max_extensions: u8,
+}
+
+/// Kind of error reported by the offchain rollup when a registration contract cannot be synchronized
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ActionFailure {
+    /// the registration contract is not configured in the offchain rollup
+    MissingConfig,
+    /// the rpc call or the transaction failed
+    RpcError,
+    /// the status, the draw number or another value in the kv store cannot be read
+    InvalidState,
+    /// other error
+    Other,
 }
 
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
@@ -38,6 +208,8 @@ pub enum Status {
     WaitingResult,
     WaitingWinner,
     DrawFinished,
+    Closed,
+    DrawCancelled,
 }
 
 #[openbrush::trait_definition]
@@ -51,6 +223,13 @@ pub trait RaffleManager: Storage<Data> + RollupAnchor {
         // check the status
         self.check_registration_contracts_status(Status::NotStarted)?;
 
+        // reject the collisions
+        for (i, registration_contract) in registration_contracts.iter().enumerate() {
+            if registration_contracts[i + 1..].contains(registration_contract) {
+                return Err(DuplicateRegistrationContract);
+            }
+        }
+
         // update the new contract
         self.data::<Data>().registration_contracts = registration_contracts.clone();
         // add the default status for this added contract
@@ -95,13 +274,24 @@ pub trait RaffleManager: Storage<Data> + RollupAnchor {
 
     /// Open the registrations
     fn open_registrations(&mut self) -> Result<DrawNumber, RaffleError> {
+        // no new draw when the lotto is stopping
+        if self.data::<Data>().stop_requested {
+            return Err(StopRequested);
+        }
         // check the status
         let status = self.get_status()?;
-        if status != Status::Started && status != Status::DrawFinished {
+        if status != Status::Started
+            && status != Status::DrawFinished
+            && status != Status::DrawCancelled
+        {
             return Err(IncorrectStatus);
         }
         // check the status
         self.check_registration_contracts_status(status)?;
+        // wait until the new config is propagated to all contracts
+        if self.is_config_update_in_progress() {
+            return Err(IncorrectStatus);
+        }
 
         // increment the draw number
         let new_draw_number = self.get_draw_number()?
@@ -111,181 +301,134 @@ pub trait RaffleManager: Storage<Data> + RollupAnchor {
         self.set_draw_number(new_draw_number);
         self.set_status(Status::RegistrationsOpen);
 
-        Ok(new_draw_number)
-    }
+        // open the jackpot with the amount rolled over from the previous draws
+        let carried_over = core::mem::take(&mut self.data::<Data>().jackpot_to_carry);
+        let jackpot = JackpotInfo {
+            carried_over,
+            ..Default::default()
+        };
+        self.data::<Data>().jackpots.insert(new_draw_number, &jackpot);
 
-    /// Return true if the registrations can be closed
-    fn can_close_registrations(&self) -> bool {
-        self.check_registration_contracts_status(Status::RegistrationsOpen)
-            .is_ok()
+        Ok(new_draw_number)
     }
 
-    /// Close the registrations
-    fn close_registrations(&mut self) -> Result<DrawNumber, RaffleError> {
+    /// Request to stop the lotto, no new draw will be open
+    fn request_stop(&mut self) -> Result<(), RaffleError> {
         // check the status
-        self.check_registration_contracts_status(Status::RegistrationsOpen)?;
-
-        // update the status
-        self.set_status(Status::RegistrationsClosed);
-        self.get_draw_number()
+        let status = self.get_status()?;
+        if status == Status::NotStarted || status == Status::Closed {
+            return Err(IncorrectStatus);
+        }
+        self.data::<Data>().stop_requested = true;
+        Ok(())
     }
 
-    /// Try to generate the salt
-    /// Return the salt or the list of missing contracts
-    fn try_to_generate_salt(&mut self) -> Result<(Option<Salt>, Vec<RegistrationContractId>), RaffleError> {
-        // check and update the status
-        match self.get_status()? {
-            Status::RegistrationsClosed => self.set_status(Status::WaitingSalt),
-            Status::WaitingSalt => {},
-            _ => return Err(IncorrectStatus),
-        };
-
-        let draw_number = self.get_draw_number()?;
+    #[ink(message)]
+    fn is_stop_requested(&self) -> bool {
+        self.data::<Data>().stop_requested
+    }
 
-        // manage the case when we don't want salts generated by registration contracts
-        if self.data::<Data>().min_number_salts == 0 {
-            // default salt used for test purpose
-            let salts = Vec::new();
-            //let default_salt = [0u8; 32];
-            //salts.push(&default_salt.to_vec());
-            // generate the salt
-            let generated_salt = self.generate_salt(draw_number, salts.as_slice())?;
-            // update the status
-            self.set_status(Status::WaitingResult);
-            return Ok((Some(generated_salt), Vec::new()));
+    /// Close the lotto when the stop is requested.
+    /// The current draw is cancelled if the registrations are still open.
+    /// Return the last draw number
+    fn close(&mut self) -> Result<DrawNumber, RaffleError> {
+        if !self.data::<Data>().stop_requested {
+            return Err(IncorrectStatus);
         }
 
-        // get the salts generated by registration contracts
-        let contracts_salts = self.data::<Data>().salts.get(draw_number).unwrap_or_default();
-
-        // test if we received enough salts
-        let min_number_salts = self.data::<Data>().min_number_salts as usize;
-        if contracts_salts.len() >= min_number_salts  {
-            // we already receive enough salt to generate the final salt
-            // collect all salt
-            let salts : Vec<_> = contracts_salts.iter().map(|(_contract, salt)| salt).collect();
-            // generate the salt
-            let generated_salt = self.generate_salt(draw_number, &salts[..min_number_salts])?;
-            // update the status
-            self.set_status(Status::WaitingResult);
-            return Ok((Some(generated_salt), Vec::new()));
-        }
-        // we didn't receive enough salt
-        let mut missing_contracts = Vec::new();
+        let draw_number = self.get_draw_number()?;
 
-        for i in 0..self.data::<Data>().registration_contracts.len() {
-            let contract_id = self.data::<Data>().registration_contracts[i];
-            let contract_status = self
-                .data::<Data>()
-                .registration_contracts_status
-                .get(contract_id);
-            if contract_status != Some(Status::WaitingSalt) {
-                missing_contracts.push(contract_id);
+        // check the status
+        match self.get_status()? {
+            Status::Started | Status::DrawFinished | Status::DrawCancelled => {}
+            Status::RegistrationsOpen => {
+                // cancel the current draw
+                self.data::<Data>().cancelled_draws.insert(draw_number, &true);
             }
+            _ => return Err(IncorrectStatus),
         }
-        Ok((None, missing_contracts))
-    }
-
-    fn generate_salt(
-        &mut self,
-        draw_number: DrawNumber,
-        salts: &[&Salt]
-    ) -> Result<Salt, RaffleError> {
-
-        use ink::env::hash;
-
-        let mut input_salts: Vec<u8> = Vec::new();
-        for salt in salts.iter() {
-            input_salts.extend_from_slice(salt);
-        }
-
-        let mut output_salt = <hash::Blake2x256 as hash::HashOutput>::Type::default();
-        ink::env::hash_bytes::<hash::Blake2x256>(&input_salts, &mut output_salt);
 
-        match self.data::<Data>().generated_salt.get(draw_number) {
-            Some(_) => return Err(ExistingSalt),
-            None => self.data::<Data>().generated_salt.insert(draw_number, &output_salt.to_vec()),
-        };
-
-        Ok(output_salt.to_vec())
+        self.set_status(Status::Closed);
+        Ok(draw_number)
     }
 
-    /// Save the salts for given registration contracts
-    fn save_salts(
-        &mut self,
-        draw_number: DrawNumber,
-        contracts_salts: Vec<(RegistrationContractId, Salt)>,
-    ) -> Result<(), RaffleError> {
-        // check the status
-        if self.get_status()? != Status::WaitingSalt {
-            return Err(IncorrectStatus);
-        }
+    /// Cancel the current draw when it is stuck.
+    /// The salts, the generated salt and the results of this draw are discarded
+    fn cancel_draw(&mut self, draw_number: DrawNumber) -> Result<(), RaffleError> {
         // check the draw number
         if self.get_draw_number()? != draw_number {
             return Err(IncorrectDrawNumber);
         }
 
-        for (contract_id, salt) in contracts_salts.iter() {
-            match self.data::<Data>().registration_contracts_status.get(contract_id) {
-                Some(Status::RegistrationsClosed) => {
-                    // update the status
-                    self.data::<Data>().registration_contracts_status.insert(contract_id, &Status::WaitingSalt);
-                    // add the hash
-                    let mut registered_contracts_salts = self.data::<Data>().salts.get(draw_number).unwrap_or_default();
-                    registered_contracts_salts.push((*contract_id, salt.to_vec()));
-                    self.data::<Data>().salts.insert(draw_number, &registered_contracts_salts);
-                },
-                _ => return Err(IncorrectStatus)
-            }
+        // check the status
+        match self.get_status()? {
+            Status::RegistrationsOpen
+            | Status::RegistrationsClosed
+            | Status::WaitingSalt
+            | Status::WaitingResult
+            | Status::WaitingWinner => {}
+            _ => return Err(IncorrectStatus),
         }
 
+        // discard the data of this draw
+        self.data::<Data>().salts.remove(draw_number);
+        self.data::<Data>().generated_salt.remove(draw_number);
+        self.data::<Data>().results.remove(draw_number);
+        self.data::<Data>().cancelled_draws.insert(draw_number, &true);
+
+        self.set_status(Status::DrawCancelled);
         Ok(())
     }
 
-    /// Save the status for given registration contracts
-    /// return the contracts not synchronized yet
-    fn check_registration_contracts_status(&self, status: Status) -> Result<(), RaffleError> {
-        // check the status in the manager
-        if self.get_status()? != status {
-            return Err(IncorrectStatus);
-        }
+    #[ink(message)]
+    fn is_draw_cancelled(&self, draw_number: DrawNumber) -> bool {
+        self.data::<Data>().cancelled_draws.get(draw_number).unwrap_or_default()
+    }
 
-        // check the status in all  registration contracts
-        for i in 0..self.data::<Data>().registration_contracts.len() {
-            let contract_id = self.data::<Data>().registration_contracts[i];
-            let contract_status = self
+    /// Request to pause (or unpause) the participations in the given registration contracts
+    fn request_paused(
+        &mut self,
+        registration_contracts: &[RegistrationContractId],
+        paused: bool,
+    ) -> Result<(), RaffleError> {
+        // check the contracts
+        for registration_contract in registration_contracts {
+            if !self
                 .data::<Data>()
-                .registration_contracts_status
-                .get(contract_id);
-            if contract_status != Some(status) {
-                return Err(IncorrectStatus);
+                .registration_contracts
+                .contains(registration_contract)
+            {
+                return Err(UnknownRegistrationContract);
             }
         }
 
+        for registration_contract in registration_contracts {
+            self.data::<Data>()
+                .paused_requested
+                .insert(registration_contract, &paused);
+        }
+
         Ok(())
     }
 
-    /// Save the status for given registration contracts
-    /// return the contracts not synchronized yet
-    fn save_registration_contracts_status(
+    /// Save the paused flag for given registration contracts
+    /// return the updated contracts and the contracts not synchronized yet
+    fn save_paused(
         &mut self,
-        draw_number: DrawNumber,
-        status: Status,
+        paused: bool,
         registration_contracts: Vec<RegistrationContractId>,
-    ) -> Result<Vec<RegistrationContractId>, RaffleError> {
-        // check the status
-        if self.get_status()? != status {
-            return Err(IncorrectStatus);
-        }
-        // check the draw number
-        if self.get_draw_number()? != draw_number {
-            return Err(IncorrectDrawNumber);
-        }
-
-        for registration_contract in &registration_contracts {
-            self.data::<Data>()
-                .registration_contracts_status
-                .insert(registration_contract, &status);
+    ) -> (Vec<RegistrationContractId>, Vec<RegistrationContractId>) {
+        let mut updated_contracts = Vec::new();
+        for registration_contract in registration_contracts {
+            // the flag is ignored if another request has been made in the meantime
+            if self.data::<Data>().paused_requested.get(registration_contract) == Some(paused)
+                && self.data::<Data>().paused.get(registration_contract) != Some(paused)
+            {
+                self.data::<Data>()
+                    .paused
+                    .insert(registration_contract, &paused);
+                updated_contracts.push(registration_contract);
+            }
         }
 
         // contract not synchronized yet
@@ -293,490 +436,1851 @@ pub trait RaffleManager: Storage<Data> + RollupAnchor {
 
         for i in 0..self.data::<Data>().registration_contracts.len() {
             let contract_id = self.data::<Data>().registration_contracts[i];
-            let contract_status = self
+            let requested = self
                 .data::<Data>()
-                .registration_contracts_status
-                .get(contract_id);
-            if contract_status != Some(status) {
+                .paused_requested
+                .get(contract_id)
+                .unwrap_or_default();
+            let current = self.data::<Data>().paused.get(contract_id).unwrap_or_default();
+            if requested == paused && current != paused {
                 not_synchronized_contracts.push(contract_id);
             }
         }
 
-        Ok(not_synchronized_contracts)
+        (updated_contracts, not_synchronized_contracts)
     }
 
+    /// Return the registration contracts where the participations are paused
     #[ink(message)]
-    fn get_draw_number(&self) -> Result<DrawNumber, RaffleError> {
-        match RollupAnchor::get_value(self, DRAW_NUMBER.encode()) {
-            Some(v) => DrawNumber::decode(&mut v.as_slice()).map_err(|_| FailedToDecode),
-            _ => Ok(0),
+    fn get_paused_registration_contracts(&self) -> Vec<RegistrationContractId> {
+        let mut paused_contracts = Vec::new();
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            if self.data::<Data>().paused.get(contract_id) == Some(true) {
+                paused_contracts.push(contract_id);
+            }
         }
+        paused_contracts
     }
 
-    fn set_draw_number(&mut self, draw_number: DrawNumber) {
-        RollupAnchor::set_value(self, &DRAW_NUMBER.encode(), Some(&draw_number.encode()));
-    }
-
-    #[ink(message)]
-    fn get_status(&self) -> Result<Status, RaffleError> {
-        match RollupAnchor::get_value(self, STATUS.encode()) {
-            Some(v) => Status::decode(&mut v.as_slice()).map_err(|_| FailedToDecode),
-            _ => Ok(Status::NotStarted),
+    /// Save the failures reported by the offchain rollup for the given registration contracts
+    /// return the number of failures reported for each known contract since its last synchronization.
+    /// The last kind of failure is saved in the kv store so that the offchain rollup reports
+    /// a failure only when its kind changes
+    fn save_failures(
+        &mut self,
+        failures: &[(RegistrationContractId, ActionFailure)],
+    ) -> Vec<(RegistrationContractId, u32)> {
+        let mut nb_failures = Vec::new();
+        for (contract_id, failure) in failures {
+            // ignore the unknown contracts
+            if !self.data::<Data>().registration_contracts.contains(contract_id) {
+                continue;
+            }
+            let nb = self
+                .data::<Data>()
+                .nb_failures
+                .get(contract_id)
+                .unwrap_or_default()
+                .saturating_add(1);
+            self.data::<Data>().nb_failures.insert(contract_id, &nb);
+            RollupAnchor::set_value(
+                self,
+                &(LAST_FAILURE, *contract_id).encode(),
+                Some(&failure.encode()),
+            );
+            nb_failures.push((*contract_id, nb));
         }
+        nb_failures
     }
 
-    fn set_status(&mut self, status: Status) {
-        RollupAnchor::set_value(self, &STATUS.encode(), Some(&status.encode()));
+    /// Reset the failures of the given registration contract when it is synchronized
+    fn reset_failures(&mut self, registration_contract_id: RegistrationContractId) {
+        self.data::<Data>()
+            .nb_failures
+            .remove(registration_contract_id);
+        RollupAnchor::set_value(self, &(LAST_FAILURE, registration_contract_id).encode(), None);
     }
 
+    /// Return the number of failures reported for the given registration contract since its last synchronization
     #[ink(message)]
-    fn get_registration_contracts(&self) -> Vec<RegistrationContractId> {
-        self.data::<Data>().registration_contracts.clone()
+    fn get_nb_failures(&self, registration_contract_id: RegistrationContractId) -> u32 {
+        self.data::<Data>()
+            .nb_failures
+            .get(registration_contract_id)
+            .unwrap_or_default()
     }
 
+    /// Return the last kind of failure reported for the given registration contract
     #[ink(message)]
-    fn get_registration_contract_status(
+    fn get_last_failure(
         &self,
-        registration_contract: RegistrationContractId,
-    ) -> Option<Status> {
-        self.data::<Data>()
-            .registration_contracts_status
-            .get(registration_contract)
+        registration_contract_id: RegistrationContractId,
+    ) -> Option<ActionFailure> {
+        RollupAnchor::get_value(self, (LAST_FAILURE, registration_contract_id).encode())
+            .and_then(|v| ActionFailure::decode(&mut v.as_slice()).ok())
     }
 
-    #[ink(message)]
-    fn get_generated_salt(&self, draw_number: DrawNumber) -> Option<Salt> {
-        self.data::<Data>().generated_salt.get(draw_number)
+    /// Save the block and the config when the registrations are open for the current draw
+    fn save_draw_opened(&mut self, block_number: u32, config: Config) -> Result<(), RaffleError> {
+        let draw_number = self.get_draw_number()?;
+        let draw_info = DrawInfo {
+            open_block: block_number,
+            close_block: None,
+            config,
+        };
+        self.data::<Data>().draws.insert(draw_number, &draw_info);
+        // save in the kv store the config used for this draw (used by the offchain rollup for verification)
+        RollupAnchor::set_value(
+            self,
+            &(DRAW_CONFIG, draw_number).encode(),
+            Some(&config.encode()),
+        );
+        Ok(())
     }
 
+    /// Return the config used for the given draw
     #[ink(message)]
-    fn get_results(&self, draw_number: DrawNumber) -> Option<Vec<Number>> {
-        self.data::<Data>().results.get(draw_number)
+    fn get_draw_config(&self, draw_number: DrawNumber) -> Option<Config> {
+        self.data::<Data>()
+            .draws
+            .get(draw_number)
+            .map(|draw_info| draw_info.config)
+    }
+
+    /// Request to update the config in all registration contracts before opening the next draw
+    /// return the contracts to synchronize
+    fn request_config_update(&mut self) -> Result<Vec<RegistrationContractId>, RaffleError> {
+        // the config can be updated only between two draws
+        match self.get_status()? {
+            Status::Started | Status::DrawFinished | Status::DrawCancelled => {}
+            _ => return Err(IncorrectStatus),
+        }
+        let registration_contracts = self.data::<Data>().registration_contracts.clone();
+        self.data::<Data>().config_not_synchronized = registration_contracts.clone();
+        Ok(registration_contracts)
+    }
+
+    /// Save the given registration contracts as synchronized with the new config
+    /// return the contracts not synchronized yet
+    fn save_config_updated(
+        &mut self,
+        registration_contracts: Vec<RegistrationContractId>,
+    ) -> Vec<RegistrationContractId> {
+        for registration_contract in &registration_contracts {
+            self.data::<Data>()
+                .config_not_synchronized
+                .retain(|contract_id| contract_id != registration_contract);
+            // the contract is synchronized, reset the failures
+            self.reset_failures(*registration_contract);
+        }
+        self.data::<Data>().config_not_synchronized.clone()
     }
 
+    /// Return true if the new config is not propagated to all registration contracts yet
     #[ink(message)]
-    fn get_winners(&self, draw_number: DrawNumber) -> Option<Winners> {
-        self.data::<Data>().winners.get(draw_number)
+    fn is_config_update_in_progress(&self) -> bool {
+        !self.data::<Data>().config_not_synchronized.is_empty()
     }
 
-    /// save the results for the current raffle.
-    fn set_results(
+    /// Save the block when the registrations are closed for the current draw
+    fn save_draw_closed(&mut self, block_number: u32) -> Result<(), RaffleError> {
+        let draw_number = self.get_draw_number()?;
+        if let Some(mut draw_info) = self.data::<Data>().draws.get(draw_number) {
+            draw_info.close_block = Some(block_number);
+            self.data::<Data>().draws.insert(draw_number, &draw_info);
+        }
+        Ok(())
+    }
+
+    /// Return the summary of the given draw
+    #[ink(message)]
+    fn get_draw(&self, draw_number: DrawNumber) -> Option<DrawRecord> {
+        let draw_info = self.data::<Data>().draws.get(draw_number)?;
+
+        let contributing_contracts = self
+            .data::<Data>()
+            .salts
+            .get(draw_number)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(contract_id, _)| contract_id)
+            .collect();
+
+        let mut nb_tickets = Vec::new();
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            if let Some(n) = self.data::<Data>().nb_tickets.get((draw_number, contract_id)) {
+                nb_tickets.push((contract_id, n));
+            }
+        }
+
+        Some(DrawRecord {
+            draw_number,
+            open_block: draw_info.open_block,
+            close_block: draw_info.close_block,
+            config: draw_info.config,
+            contributing_contracts,
+            salt: self.data::<Data>().generated_salt.get(draw_number),
+            numbers: self.data::<Data>().results.get(draw_number),
+            winners: self.data::<Data>().winners.get(draw_number).unwrap_or_default(),
+            nb_tickets,
+            cancelled: self.is_draw_cancelled(draw_number),
+        })
+    }
+
+    /// Return the summaries of the draws from the given draw number (included).
+    /// At most `limit` draw numbers are read, the limit is capped to `MAX_DRAWS_PAGE`.
+    #[ink(message)]
+    fn get_draws(&self, from: DrawNumber, limit: u32) -> Vec<DrawRecord> {
+        let mut draws = Vec::new();
+        let last_draw_number = self.get_draw_number().unwrap_or_default();
+        let limit = limit.min(MAX_DRAWS_PAGE);
+        let to = from.saturating_add(limit).min(last_draw_number.saturating_add(1));
+        for draw_number in from..to {
+            if let Some(draw) = self.get_draw(draw_number) {
+                draws.push(draw);
+            }
+        }
+        draws
+    }
+
+    /// Return the summary of the last draw with the winners revealed
+    #[ink(message)]
+    fn get_last_finished_draw(&self) -> Option<DrawRecord> {
+        let draw_number = self.data::<Data>().last_finished_draw?;
+        self.get_draw(draw_number)
+    }
+
+    /// Set the minimum participation threshold (None to remove it)
+    fn set_participation_threshold(
+        &mut self,
+        participation_threshold: Option<ParticipationThreshold>,
+    ) -> Result<(), RaffleError> {
+        self.data::<Data>().participation_threshold = participation_threshold;
+        Ok(())
+    }
+
+    #[ink(message)]
+    fn get_participation_threshold(&self) -> Option<ParticipationThreshold> {
+        self.data::<Data>().participation_threshold
+    }
+
+    /// Save the number of tickets recorded by the given registration contracts
+    fn save_nb_tickets(
         &mut self,
         draw_number: DrawNumber,
-        results: Vec<Number>,
+        contracts_tickets: &[(RegistrationContractId, u32)],
     ) -> Result<(), RaffleError> {
-        // check the raffle number
+        // check the draw number
         if self.get_draw_number()? != draw_number {
             return Err(IncorrectDrawNumber);
         }
 
-        // check the status
-        let status = self.get_status()?;
-        //if status != Status::RegistrationsClosed && status != Status::WaitingSalt {
-        if status != Status::WaitingResult {
-            return Err(IncorrectStatus);
+        for (contract_id, nb_tickets) in contracts_tickets {
+            self.data::<Data>()
+                .nb_tickets
+                .insert((draw_number, *contract_id), nb_tickets);
         }
+        Ok(())
+    }
 
-        match self.data::<Data>().results.get(draw_number) {
-            Some(_) => Err(ExistingResults),
-            None => {
-                // save the results
-                self.data::<Data>().results.insert(draw_number, &results);
-                // update the status
-                self.set_status(Status::WaitingWinner);
-                Ok(())
-            }
+    /// Return the number of tickets recorded across all registration contracts for the given draw
+    #[ink(message)]
+    fn get_nb_tickets(&self, draw_number: DrawNumber) -> u32 {
+        let mut nb_tickets: u32 = 0;
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            let contract_tickets = self
+                .data::<Data>()
+                .nb_tickets
+                .get((draw_number, contract_id))
+                .unwrap_or_default();
+            nb_tickets = nb_tickets.saturating_add(contract_tickets);
         }
+        nb_tickets
     }
 
-    /// check if the saved results are the same as the ones given in parameter
-    fn ensure_same_results(
+    /// Set the asset in which the given registration contract settles the tickets
+    fn set_settlement_asset(
         &mut self,
-        draw_number: DrawNumber,
-        numbers: &[Number],
+        registration_contract_id: RegistrationContractId,
+        settlement_asset: SettlementAsset,
     ) -> Result<(), RaffleError> {
-        // get the correct results for the given raffle
-        let result = self
+        if !self
             .data::<Data>()
-            .results
-            .get(draw_number)
-            .ok_or(DifferentResults)?;
-
-        if result.len() != numbers.len() {
-            return Err(DifferentResults);
+            .registration_contracts
+            .contains(&registration_contract_id)
+        {
+            return Err(UnknownRegistrationContract);
         }
-
-        for i in 0..numbers.len() {
-            if numbers[i] != result[i] {
-                return Err(DifferentResults);
-            }
+        if settlement_asset.rate_numerator == 0 || settlement_asset.rate_denominator == 0 {
+            return Err(IncorrectExchangeRate);
         }
-
+        self.data::<Data>()
+            .settlement_assets
+            .insert(registration_contract_id, &settlement_asset);
         Ok(())
     }
 
-    /// save the winners for the current raffle.
-    fn set_winners(
+    #[ink(message)]
+    fn get_settlement_asset(
+        &self,
+        registration_contract_id: RegistrationContractId,
+    ) -> SettlementAsset {
+        self.data::<Data>()
+            .settlement_assets
+            .get(registration_contract_id)
+            .unwrap_or_default()
+    }
+
+    /// Save the amount paid for the tickets in the given registration contracts.
+    /// The amounts are converted in the unit of the jackpot with the exchange rate of each contract.
+    /// The previous amount is replaced because the registration contracts report the total for the draw
+    fn save_jackpot_contributions(
         &mut self,
         draw_number: DrawNumber,
-        winners: Winners,
+        contributions: &[(RegistrationContractId, Balance)],
     ) -> Result<(), RaffleError> {
-        // check the raffle number
+        // check the draw number
         if self.get_draw_number()? != draw_number {
             return Err(IncorrectDrawNumber);
         }
 
-        // check the status
-        if self.get_status()? != Status::WaitingWinner {
-            return Err(IncorrectStatus);
+        let mut jackpot = self
+            .data::<Data>()
+            .jackpots
+            .get(draw_number)
+            .unwrap_or_default();
+        for (contract_id, amount) in contributions {
+            let amount = self.get_settlement_asset(*contract_id).convert(*amount)?;
+            match jackpot
+                .contributions
+                .iter_mut()
+                .find(|(id, _)| id == contract_id)
+            {
+                Some((_, contribution)) => *contribution = amount,
+                None => jackpot.contributions.push((*contract_id, amount)),
+            }
         }
+        self.data::<Data>().jackpots.insert(draw_number, &jackpot);
+        Ok(())
+    }
 
-        match self.data::<Data>().winners.get(draw_number) {
-            Some(_) => Err(ExistingWinners),
-            None => {
-                // save the result
-                if !winners.0.is_empty() || !winners.1.is_empty() {
-                    self.data::<Data>().winners.insert(draw_number, &winners);
-                }
-                // update the status
-                self.set_status(Status::DrawFinished);
-                Ok(())
-            }
+    /// Settle the jackpot of the given draw: the jackpot is paid if there is a winner,
+    /// otherwise it is rolled over to the next draw.
+    /// Return None if the jackpot is unknown or already settled
+    fn settle_jackpot(
+        &mut self,
+        draw_number: DrawNumber,
+        has_winner: bool,
+    ) -> Result<Option<JackpotInfo>, RaffleError> {
+        let Some(mut jackpot) = self.data::<Data>().jackpots.get(draw_number) else {
+            return Ok(None);
+        };
+        if jackpot.paid != 0 || jackpot.rolled_over != 0 {
+            return Ok(None);
+        }
+
+        let total = jackpot.total();
+        if has_winner {
+            jackpot.paid = total;
+        } else {
+            jackpot.rolled_over = total;
+            self.data::<Data>().jackpot_to_carry = self
+                .data::<Data>()
+                .jackpot_to_carry
+                .checked_add(total)
+                .ok_or(AddOverFlow)?;
         }
+        self.data::<Data>().jackpots.insert(draw_number, &jackpot);
+        Ok(Some(jackpot))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test_contract::lotto_contract::Contract;
+    /// Settle the jackpot of a cancelled draw.
+    /// The tickets are refunded by the registration contracts,
+    /// only the amount carried over from the previous draws is rolled over
+    fn cancel_jackpot(
+        &mut self,
+        draw_number: DrawNumber,
+    ) -> Result<Option<JackpotInfo>, RaffleError> {
+        let Some(mut jackpot) = self.data::<Data>().jackpots.get(draw_number) else {
+            return Ok(None);
+        };
+        if jackpot.paid != 0 || jackpot.rolled_over != 0 {
+            return Ok(None);
+        }
 
-    #[ink::test]
-    fn test_add_registrations_contract() {
-        let mut contract = Contract::new();
+        jackpot.rolled_over = jackpot.carried_over;
+        self.data::<Data>().jackpot_to_carry = self
+            .data::<Data>()
+            .jackpot_to_carry
+            .checked_add(jackpot.carried_over)
+            .ok_or(AddOverFlow)?;
+        self.data::<Data>().jackpots.insert(draw_number, &jackpot);
+        Ok(Some(jackpot))
+    }
 
-        contract
-            .set_registration_contracts(vec![100, 101, 102])
-            .expect("Fail to add registrations contract");
+    #[ink(message)]
+    fn get_jackpot(&self, draw_number: DrawNumber) -> Option<JackpotInfo> {
+        self.data::<Data>().jackpots.get(draw_number)
+    }
 
-        assert_eq!(contract.get_status(), Ok(Status::NotStarted));
-        assert_eq!(contract.get_draw_number(), Ok(0));
-        assert_eq!(contract.get_registration_contracts(), vec![100, 101, 102]);
-        assert_eq!(
-            contract.get_registration_contract_status(100),
-            Some(Status::NotStarted)
-        );
-        assert_eq!(
-            contract.get_registration_contract_status(101),
-            Some(Status::NotStarted)
-        );
-        assert_eq!(
-            contract.get_registration_contract_status(102),
-            Some(Status::NotStarted)
-        );
-        assert_eq!(contract.get_registration_contract_status(103), None);
+    #[ink(message)]
+    fn get_nb_extensions(&self, draw_number: DrawNumber) -> u8 {
+        self.data::<Data>()
+            .nb_extensions
+            .get(draw_number)
+            .unwrap_or_default()
     }
 
-    #[ink::test]
-    fn test_start() {
-        let mut contract = Contract::new();
+    /// Open again the registrations for the current draw when the participation threshold is not met,
+    /// up to the maximum number of extensions.
+    /// Return true if the registrations are extended
+    fn try_to_extend_registrations(&mut self) -> Result<bool, RaffleError> {
+        // check the status
+        self.check_registration_contracts_status(Status::RegistrationsClosed)?;
 
-        assert_eq!(contract.get_status(), Ok(Status::NotStarted));
-        assert_eq!(contract.get_draw_number(), Ok(0));
+        let threshold = match self.data::<Data>().participation_threshold {
+            Some(threshold) => threshold,
+            None => return Ok(false),
+        };
 
-        contract.start(1).expect("Fail to start");
+        let draw_number = self.get_draw_number()?;
+        let revenue = self
+            .get_jackpot(draw_number)
+            .map(|jackpot| jackpot.revenue())
+            .unwrap_or_default();
+        if self.get_nb_tickets(draw_number) >= threshold.min_tickets
+            && revenue >= threshold.min_pot
+        {
+            return Ok(false);
+        }
 
-        assert_eq!(contract.get_status(), Ok(Status::Started));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+        let nb_extensions = self.get_nb_extensions(draw_number);
+        if nb_extensions >= threshold.max_extensions {
+            return Ok(false);
+        }
 
-        // we cannot add registration contract when it started
-        assert_eq!(contract.set_registration_contracts(vec![1]), Err(IncorrectStatus));
+        self.data::<Data>()
+            .nb_extensions
+            .insert(draw_number, &(nb_extensions + 1));
+        self.set_status(Status::RegistrationsOpen);
+
+        Ok(true)
     }
 
-    #[ink::test]
-    fn test_open_registrations() {
-        let mut contract = Contract::new();
+    /// Return true if the registrations can be closed
+    fn can_close_registrations(&self) -> bool {
+        self.check_registration_contracts_status(Status::RegistrationsOpen)
+            .is_ok()
+    }
 
-        assert_eq!(contract.open_registrations(), Err(IncorrectStatus));
+    /// Close the registrations
+    fn close_registrations(&mut self) -> Result<DrawNumber, RaffleError> {
+        // check the status
+        self.check_registration_contracts_status(Status::RegistrationsOpen)?;
 
-        contract.start(0).expect("Fail to start");
+        // update the status
+        self.set_status(Status::RegistrationsClosed);
+        self.get_draw_number()
+    }
 
-        contract
-            .open_registrations()
-            .expect("Fail to open the registrations");
-        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+    /// Try to generate the salt
+    /// Return the salt or the list of missing contracts
+    fn try_to_generate_salt(&mut self) -> Result<(Option<Salt>, Vec<RegistrationContractId>), RaffleError> {
+        // check and update the status
+        match self.get_status()? {
+            Status::RegistrationsClosed => self.set_status(Status::WaitingSalt),
+            Status::WaitingSalt => {},
+            _ => return Err(IncorrectStatus),
+        };
 
-        assert_eq!(contract.open_registrations(), Err(IncorrectStatus));
+        let draw_number = self.get_draw_number()?;
+
+        // manage the case when we don't want salts generated by registration contracts
+        if self.data::<Data>().min_number_salts == 0 {
+            // default salt used for test purpose
+            let salts = Vec::new();
+            //let default_salt = [0u8; 32];
+            //salts.push(&default_salt.to_vec());
+            // generate the salt
+            let generated_salt = self.generate_salt(draw_number, salts.as_slice())?;
+            // update the status
+            self.set_status(Status::WaitingResult);
+            return Ok((Some(generated_salt), Vec::new()));
+        }
+
+        // get the salts generated by registration contracts
+        let contracts_salts = self.data::<Data>().salts.get(draw_number).unwrap_or_default();
+
+        // test if we received enough salts
+        let min_number_salts = self.data::<Data>().min_number_salts as usize;
+        if contracts_salts.len() >= min_number_salts  {
+            // we already receive enough salt to generate the final salt
+            // collect all salt
+            let salts : Vec<_> = contracts_salts.iter().map(|(_contract, salt)| salt).collect();
+            // generate the salt
+            let generated_salt = self.generate_salt(draw_number, &salts[..min_number_salts])?;
+            // update the status
+            self.set_status(Status::WaitingResult);
+            return Ok((Some(generated_salt), Vec::new()));
+        }
+        // we didn't receive enough salt
+        let mut missing_contracts = Vec::new();
+
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            let contract_status = self
+                .data::<Data>()
+                .registration_contracts_status
+                .get(contract_id);
+            if contract_status != Some(Status::WaitingSalt) {
+                missing_contracts.push(contract_id);
+            }
+        }
+        Ok((None, missing_contracts))
+    }
+
+    fn generate_salt(
+        &mut self,
+        draw_number: DrawNumber,
+        salts: &[&Salt]
+    ) -> Result<Salt, RaffleError> {
+
+        use ink::env::hash;
+
+        let mut input_salts: Vec<u8> = Vec::new();
+        for salt in salts.iter() {
+            input_salts.extend_from_slice(salt);
+        }
+
+        let mut output_salt = <hash::Blake2x256 as hash::HashOutput>::Type::default();
+        ink::env::hash_bytes::<hash::Blake2x256>(&input_salts, &mut output_salt);
+
+        match self.data::<Data>().generated_salt.get(draw_number) {
+            Some(_) => return Err(ExistingSalt),
+            None => self.data::<Data>().generated_salt.insert(draw_number, &output_salt.to_vec()),
+        };
+
+        Ok(output_salt.to_vec())
+    }
+
+    /// Save the salts for given registration contracts
+    fn save_salts(
+        &mut self,
+        draw_number: DrawNumber,
+        contracts_salts: Vec<(RegistrationContractId, Salt)>,
+    ) -> Result<(), RaffleError> {
+        // check the status
+        if self.get_status()? != Status::WaitingSalt {
+            return Err(IncorrectStatus);
+        }
+        // check the draw number
+        if self.get_draw_number()? != draw_number {
+            return Err(IncorrectDrawNumber);
+        }
+
+        for (contract_id, salt) in contracts_salts.iter() {
+            match self.data::<Data>().registration_contracts_status.get(contract_id) {
+                Some(Status::RegistrationsClosed) => {
+                    // update the status
+                    self.data::<Data>().registration_contracts_status.insert(contract_id, &Status::WaitingSalt);
+                    // add the hash
+                    let mut registered_contracts_salts = self.data::<Data>().salts.get(draw_number).unwrap_or_default();
+                    registered_contracts_salts.push((*contract_id, salt.to_vec()));
+                    self.data::<Data>().salts.insert(draw_number, &registered_contracts_salts);
+                },
+                _ => return Err(IncorrectStatus)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the status for given registration contracts
+    /// return the contracts not synchronized yet
+    fn check_registration_contracts_status(&self, status: Status) -> Result<(), RaffleError> {
+        // check the status in the manager
+        if self.get_status()? != status {
+            return Err(IncorrectStatus);
+        }
+
+        // check the status in all  registration contracts
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            let contract_status = self
+                .data::<Data>()
+                .registration_contracts_status
+                .get(contract_id);
+            if contract_status != Some(status) {
+                return Err(IncorrectStatus);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the status for given registration contracts
+    /// return the contracts not synchronized yet
+    fn save_registration_contracts_status(
+        &mut self,
+        draw_number: DrawNumber,
+        status: Status,
+        registration_contracts: Vec<RegistrationContractId>,
+    ) -> Result<Vec<RegistrationContractId>, RaffleError> {
+        // check the status
+        if self.get_status()? != status {
+            return Err(IncorrectStatus);
+        }
+        // check the draw number
+        if self.get_draw_number()? != draw_number {
+            return Err(IncorrectDrawNumber);
+        }
+
+        for registration_contract in &registration_contracts {
+            self.data::<Data>()
+                .registration_contracts_status
+                .insert(registration_contract, &status);
+            // the contract is synchronized, reset the failures
+            self.reset_failures(*registration_contract);
+        }
+
+        // contract not synchronized yet
+        let mut not_synchronized_contracts = Vec::new();
+
+        for i in 0..self.data::<Data>().registration_contracts.len() {
+            let contract_id = self.data::<Data>().registration_contracts[i];
+            let contract_status = self
+                .data::<Data>()
+                .registration_contracts_status
+                .get(contract_id);
+            if contract_status != Some(status) {
+                not_synchronized_contracts.push(contract_id);
+            }
+        }
+
+        Ok(not_synchronized_contracts)
+    }
+
+    #[ink(message)]
+    fn get_draw_number(&self) -> Result<DrawNumber, RaffleError> {
+        match RollupAnchor::get_value(self, DRAW_NUMBER.encode()) {
+            Some(v) => DrawNumber::decode(&mut v.as_slice()).map_err(|_| FailedToDecode),
+            _ => Ok(0),
+        }
+    }
+
+    fn set_draw_number(&mut self, draw_number: DrawNumber) {
+        RollupAnchor::set_value(self, &DRAW_NUMBER.encode(), Some(&draw_number.encode()));
+    }
+
+    #[ink(message)]
+    fn get_status(&self) -> Result<Status, RaffleError> {
+        match RollupAnchor::get_value(self, STATUS.encode()) {
+            Some(v) => Status::decode(&mut v.as_slice()).map_err(|_| FailedToDecode),
+            _ => Ok(Status::NotStarted),
+        }
+    }
+
+    fn set_status(&mut self, status: Status) {
+        RollupAnchor::set_value(self, &STATUS.encode(), Some(&status.encode()));
+    }
+
+    #[ink(message)]
+    fn get_registration_contracts(&self) -> Vec<RegistrationContractId> {
+        self.data::<Data>().registration_contracts.clone()
+    }
+
+    #[ink(message)]
+    fn get_registration_contract_status(
+        &self,
+        registration_contract: RegistrationContractId,
+    ) -> Option<Status> {
+        self.data::<Data>()
+            .registration_contracts_status
+            .get(registration_contract)
+    }
+
+    #[ink(message)]
+    fn get_generated_salt(&self, draw_number: DrawNumber) -> Option<Salt> {
+        self.data::<Data>().generated_salt.get(draw_number)
+    }
+
+    #[ink(message)]
+    fn get_results(&self, draw_number: DrawNumber) -> Option<Vec<Number>> {
+        self.data::<Data>().results.get(draw_number)
+    }
+
+    #[ink(message)]
+    fn get_winners(&self, draw_number: DrawNumber) -> Option<Winners> {
+        self.data::<Data>().winners.get(draw_number)
+    }
+
+    /// save the results for the current raffle.
+    fn set_results(
+        &mut self,
+        draw_number: DrawNumber,
+        results: Vec<Number>,
+    ) -> Result<(), RaffleError> {
+        // check the raffle number
+        if self.get_draw_number()? != draw_number {
+            return Err(IncorrectDrawNumber);
+        }
+
+        // check the status
+        let status = self.get_status()?;
+        //if status != Status::RegistrationsClosed && status != Status::WaitingSalt {
+        if status != Status::WaitingResult {
+            return Err(IncorrectStatus);
+        }
+
+        match self.data::<Data>().results.get(draw_number) {
+            Some(_) => Err(ExistingResults),
+            None => {
+                // save the results
+                self.data::<Data>().results.insert(draw_number, &results);
+                // update the status
+                self.set_status(Status::WaitingWinner);
+                Ok(())
+            }
+        }
+    }
+
+    /// check if the saved results are the same as the ones given in parameter
+    fn ensure_same_results(
+        &mut self,
+        draw_number: DrawNumber,
+        numbers: &[Number],
+    ) -> Result<(), RaffleError> {
+        // get the correct results for the given raffle
+        let result = self
+            .data::<Data>()
+            .results
+            .get(draw_number)
+            .ok_or(DifferentResults)?;
+
+        if result.len() != numbers.len() {
+            return Err(DifferentResults);
+        }
+
+        for i in 0..numbers.len() {
+            if numbers[i] != result[i] {
+                return Err(DifferentResults);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// save the winners for the current raffle.
+    fn set_winners(
+        &mut self,
+        draw_number: DrawNumber,
+        winners: Winners,
+    ) -> Result<(), RaffleError> {
+        // check the raffle number
+        if self.get_draw_number()? != draw_number {
+            return Err(IncorrectDrawNumber);
+        }
+
+        // check the status
+        if self.get_status()? != Status::WaitingWinner {
+            return Err(IncorrectStatus);
+        }
+
+        match self.data::<Data>().winners.get(draw_number) {
+            Some(_) => Err(ExistingWinners),
+            None => {
+                // save the result
+                if !winners.is_empty() {
+                    self.data::<Data>().winners.insert(draw_number, &winners);
+                }
+                self.data::<Data>().last_finished_draw = Some(draw_number);
+                // update the status
+                self.set_status(Status::DrawFinished);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_contract::lotto_contract::Contract;
+
+    #[ink::test]
+    fn test_add_registrations_contract() {
+        let mut contract = Contract::new();
+
+        contract
+            .set_registration_contracts(vec![100, 101, 102])
+            .expect("Fail to add registrations contract");
+
+        assert_eq!(contract.get_status(), Ok(Status::NotStarted));
+        assert_eq!(contract.get_draw_number(), Ok(0));
+        assert_eq!(contract.get_registration_contracts(), vec![100, 101, 102]);
+        assert_eq!(
+            contract.get_registration_contract_status(100),
+            Some(Status::NotStarted)
+        );
+        assert_eq!(
+            contract.get_registration_contract_status(101),
+            Some(Status::NotStarted)
+        );
+        assert_eq!(
+            contract.get_registration_contract_status(102),
+            Some(Status::NotStarted)
+        );
+        assert_eq!(contract.get_registration_contract_status(103), None);
+    }
+
+    #[ink::test]
+    fn test_add_duplicate_registrations_contract() {
+        let mut contract = Contract::new();
+
+        assert_eq!(
+            contract.set_registration_contracts(vec![100, 101, 100]),
+            Err(DuplicateRegistrationContract)
+        );
+        assert_eq!(contract.get_registration_contracts(), Vec::<RegistrationContractId>::new());
+    }
+
+    #[ink::test]
+    fn test_start() {
+        let mut contract = Contract::new();
+
+        assert_eq!(contract.get_status(), Ok(Status::NotStarted));
+        assert_eq!(contract.get_draw_number(), Ok(0));
+
+        contract.start(1).expect("Fail to start");
+
+        assert_eq!(contract.get_status(), Ok(Status::Started));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+        // we cannot add registration contract when it started
+        assert_eq!(contract.set_registration_contracts(vec![1]), Err(IncorrectStatus));
+    }
+
+    #[ink::test]
+    fn test_open_registrations() {
+        let mut contract = Contract::new();
+
+        assert_eq!(contract.open_registrations(), Err(IncorrectStatus));
+
+        contract.start(0).expect("Fail to start");
+
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+        assert_eq!(contract.open_registrations(), Err(IncorrectStatus));
+    }
+
+    #[ink::test]
+    fn test_close_registrations() {
+        let mut contract = Contract::new();
+
+        assert_eq!(false, contract.can_close_registrations());
+        assert_eq!(contract.close_registrations(), Err(IncorrectStatus));
+
+        contract.start(0).expect("Fail to start");
+
+        assert_eq!(false, contract.can_close_registrations());
+        assert_eq!(contract.close_registrations(), Err(IncorrectStatus));
+
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+
+        assert_eq!(true, contract.can_close_registrations());
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+    }
+
+
+    #[ink::test]
+    fn test_try_to_generate_salt() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+
+        assert_eq!(contract.try_to_generate_salt(), Err(IncorrectStatus));
+
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+        // test purpose
+        let contract_salt = contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        assert!(contract_salt.0.is_some(), "Salt not generated");
+        assert_eq!(contract_salt.1, vec![]);
+
+        assert_eq!(contract.get_status(), Ok(Status::WaitingResult));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+    }
+
+
+    #[ink::test]
+    fn test_generate_salt() {
+        let mut contract = Contract::new();
+
+        let salt_1 : Salt = [1u8; 32].to_vec();
+        let salt_2 : Salt = [2u8; 32].to_vec();
+        let salt_3 : Salt = [3u8; 32].to_vec();
+        let salts = [&salt_1, &salt_2, &salt_3];
+
+        let generated_salt = contract.generate_salt(1, &salts)
+            .expect("Fail to generate salt");
+
+        let expected_salt : Salt = [94, 193, 212, 179, 22, 80, 18, 236, 194, 56, 99, 20, 16, 125, 123, 20, 14, 26, 212, 42, 96, 187, 51, 110, 129, 113, 120, 162, 223, 50, 36, 79].to_vec();
+        assert_eq!(expected_salt, generated_salt);
+
+    }
+
+    #[ink::test]
+    fn test_set_results() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+
+        assert_eq!(contract.set_results(1, vec![]), Err(IncorrectStatus));
+
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+
+        assert_eq!(contract.set_results(0, vec![]), Err(IncorrectDrawNumber));
+        assert_eq!(contract.set_results(2, vec![]), Err(IncorrectDrawNumber));
+
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+        assert_eq!(contract.get_status(), Ok(Status::WaitingWinner));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+        assert_eq!(contract.get_results(1), Some(vec![1, 2, 3, 4]));
+        assert_eq!(contract.get_results(0), None);
+        assert_eq!(contract.get_results(2), None);
+    }
+
+    #[ink::test]
+    fn test_ensure_same_results() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate the salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        contract
+            .ensure_same_results(1, vec![1, 2, 3, 4].as_slice())
+            .expect("Check same results failed");
+        assert_eq!(
+            contract.ensure_same_results(1, vec![1, 2, 3, 5].as_slice()),
+            Err(DifferentResults)
+        );
+        assert_eq!(
+            contract.ensure_same_results(2, vec![1, 2, 3, 4].as_slice()),
+            Err(DifferentResults)
+        );
+    }
+
+    #[ink::test]
+    fn test_set_winners() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+
+        assert_eq!(contract.set_winners(1, vec![]), Err(IncorrectStatus));
+
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+
+        assert_eq!(contract.set_winners(1, vec![]), Err(IncorrectStatus));
+
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        assert_eq!(contract.set_winners(0, vec![]), Err(IncorrectDrawNumber));
+        assert_eq!(contract.set_winners(2, vec![]), Err(IncorrectDrawNumber));
+
+        assert_eq!(contract.get_status(), Ok(Status::WaitingWinner));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+        contract
+            .set_winners(1, vec![])
+            .expect("Fail to save the winners");
+
+        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+
+        assert_eq!(contract.get_winners(1), None);
+        assert_eq!(contract.get_results(0), None);
+        assert_eq!(contract.get_results(2), None);
+    }
+
+
+    #[ink::test]
+    fn test_set_winners_substrate() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        let address_substrate_1 = MultiAddress::AccountId32([1;32]);
+        let address_substrate_2 = MultiAddress::AccountId32([2;32]);
+
+        contract
+            .set_winners(1, vec![address_substrate_1.clone(), address_substrate_2.clone()])
+            .expect("Fail to save the winners");
+
+        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+        assert_eq!(contract.get_winners(1), Some(vec![address_substrate_1, address_substrate_2]));
+    }
+
+
+    #[ink::test]
+    fn test_set_winners_evm() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        let address_evm_1 = MultiAddress::AccountId20([1;20]);
+
+        contract
+            .set_winners(1, vec![address_evm_1.clone()])
+            .expect("Fail to save the winners");
+
+        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+        assert_eq!(contract.get_winners(1), Some(vec![address_evm_1]));
+    }
+
+
+    #[ink::test]
+    fn test_set_winners_other_accounts() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        let winners = vec![
+            MultiAddress::AccountId32([1;32]),
+            MultiAddress::AccountId20([2;20]),
+            MultiAddress::Ed25519([3;32]),
+            MultiAddress::Bech32("cosmos".into(), vec![4;20]),
+            MultiAddress::Move([5;32]),
+        ];
+
+        contract
+            .set_winners(1, winners.clone())
+            .expect("Fail to save the winners");
+
+        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
+        assert_eq!(contract.get_winners(1), Some(winners));
+    }
+
+
+    #[ink::test]
+    fn test_reopen_after_results_and_winners() {
+        let mut contract = Contract::new();
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+        contract
+            .set_winners(1, vec![])
+            .expect("Fail to save the winners");
+
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert_eq!(contract.get_draw_number(), Ok(2));
+    }
+
+    #[ink::test]
+    fn test_stop_between_draws() {
+        let mut contract = Contract::new();
+
+        assert_eq!(contract.request_stop(), Err(IncorrectStatus));
+
+        contract.start(0).expect("Fail to start");
+
+        // the stop must be requested before closing
+        assert_eq!(contract.close(), Err(IncorrectStatus));
+
+        contract.request_stop().expect("Fail to request the stop");
+        assert!(contract.is_stop_requested());
+
+        // no new draw
+        assert_eq!(contract.open_registrations(), Err(StopRequested));
+
+        assert_eq!(contract.close(), Ok(0));
+        assert_eq!(contract.get_status(), Ok(Status::Closed));
+        assert!(!contract.is_draw_cancelled(0));
+
+        assert_eq!(contract.request_stop(), Err(IncorrectStatus));
+        assert_eq!(contract.open_registrations(), Err(StopRequested));
+    }
+
+    #[ink::test]
+    fn test_stop_when_registrations_open() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+
+        contract.request_stop().expect("Fail to request the stop");
+        assert_eq!(contract.close(), Ok(1));
+        assert_eq!(contract.get_status(), Ok(Status::Closed));
+        // the draw is cancelled
+        assert!(contract.is_draw_cancelled(1));
+        assert_eq!(contract.close_registrations(), Err(IncorrectStatus));
+    }
+
+    #[ink::test]
+    fn test_stop_during_draw() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+
+        contract.request_stop().expect("Fail to request the stop");
+        // the current draw must be finished before closing
+        assert_eq!(contract.close(), Err(IncorrectStatus));
+
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+        contract
+            .set_winners(1, vec![])
+            .expect("Fail to save the winners");
+
+        assert_eq!(contract.open_registrations(), Err(StopRequested));
+        assert_eq!(contract.close(), Ok(1));
+        assert_eq!(contract.get_status(), Ok(Status::Closed));
+        assert!(!contract.is_draw_cancelled(1));
+    }
+
+    #[ink::test]
+    fn test_cancel_draw() {
+        let mut contract = Contract::new();
+
+        contract.start(0).expect("Fail to start");
+        // no draw in progress
+        assert_eq!(contract.cancel_draw(0), Err(IncorrectStatus));
+
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .try_to_generate_salt()
+            .expect("Fail to generate salt");
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+
+        assert_eq!(contract.cancel_draw(2), Err(IncorrectDrawNumber));
+        contract.cancel_draw(1).expect("Fail to cancel the draw");
+
+        assert_eq!(contract.get_status(), Ok(Status::DrawCancelled));
+        assert!(contract.is_draw_cancelled(1));
+        assert_eq!(contract.get_generated_salt(1), None);
+        assert_eq!(contract.get_results(1), None);
+        assert_eq!(contract.set_winners(1, vec![]), Err(IncorrectStatus));
+        assert_eq!(contract.cancel_draw(1), Err(IncorrectStatus));
+
+        // a new draw can be run
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert_eq!(contract.get_draw_number(), Ok(2));
+        assert!(!contract.is_draw_cancelled(2));
+    }
+
+    #[ink::test]
+    fn test_pause_registration_contracts() {
+        let mut contract = Contract::new();
+
+        contract
+            .set_registration_contracts(vec![100, 101, 102])
+            .expect("Fail to add registrations contract");
+
+        assert_eq!(
+            contract.request_paused(&[101, 103], true),
+            Err(UnknownRegistrationContract)
+        );
+
+        contract
+            .request_paused(&[101, 102], true)
+            .expect("Fail to request the pause");
+        // the contracts are paused only when they are synchronized
+        assert!(contract.get_paused_registration_contracts().is_empty());
+
+        assert_eq!(contract.save_paused(true, vec![101]), (vec![101], vec![102]));
+        assert_eq!(contract.get_paused_registration_contracts(), vec![101]);
+
+        assert_eq!(contract.save_paused(true, vec![101, 102]), (vec![102], vec![]));
+        assert_eq!(contract.get_paused_registration_contracts(), vec![101, 102]);
+
+        contract
+            .request_paused(&[101], false)
+            .expect("Fail to request the unpause");
+        // the response for a previous request is ignored
+        assert_eq!(contract.save_paused(true, vec![101]), (vec![], vec![]));
+        assert_eq!(contract.get_paused_registration_contracts(), vec![101, 102]);
+
+        assert_eq!(contract.save_paused(false, vec![101]), (vec![101], vec![]));
+        assert_eq!(contract.get_paused_registration_contracts(), vec![102]);
     }
 
     #[ink::test]
-    fn test_close_registrations() {
+    fn test_save_failures() {
         let mut contract = Contract::new();
 
-        assert_eq!(false, contract.can_close_registrations());
-        assert_eq!(contract.close_registrations(), Err(IncorrectStatus));
-
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
         contract.start(0).expect("Fail to start");
 
-        assert_eq!(false, contract.can_close_registrations());
-        assert_eq!(contract.close_registrations(), Err(IncorrectStatus));
-
-        contract
-            .open_registrations()
-            .expect("Fail to open the registrations");
+        // the unknown contracts are ignored
+        assert_eq!(
+            contract.save_failures(&[
+                (100, ActionFailure::RpcError),
+                (103, ActionFailure::MissingConfig)
+            ]),
+            vec![(100, 1)]
+        );
+        assert_eq!(
+            contract.save_failures(&[(100, ActionFailure::InvalidState)]),
+            vec![(100, 2)]
+        );
+        assert_eq!(contract.get_nb_failures(100), 2);
+        assert_eq!(contract.get_nb_failures(101), 0);
+        assert_eq!(
+            contract.get_last_failure(100),
+            Some(ActionFailure::InvalidState)
+        );
+        assert_eq!(contract.get_last_failure(101), None);
 
-        assert_eq!(true, contract.can_close_registrations());
+        // the failures are reset when the contract is synchronized
         contract
-            .close_registrations()
-            .expect("Fail to close the registrations");
-        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+            .save_registration_contracts_status(0, Status::Started, vec![100])
+            .expect("Fail to save the status");
+        assert_eq!(contract.get_nb_failures(100), 0);
+        assert_eq!(contract.get_last_failure(100), None);
     }
 
-
     #[ink::test]
-    fn test_try_to_generate_salt() {
+    fn test_draw_records() {
         let mut contract = Contract::new();
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
 
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
         contract.start(0).expect("Fail to start");
+        assert_eq!(contract.get_draws(0, 10), vec![]);
+        assert_eq!(contract.get_last_finished_draw(), None);
+
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
+        contract
+            .save_draw_opened(100, config)
+            .expect("Fail to save the draw");
 
-        assert_eq!(contract.try_to_generate_salt(), Err(IncorrectStatus));
+        let draw = contract.get_draw(1).expect("Draw not found");
+        assert_eq!(draw.open_block, 100);
+        assert_eq!(draw.close_block, None);
+        assert_eq!(draw.config, config);
+        assert_eq!(draw.salt, None);
+        assert!(!draw.cancelled);
 
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .close_registrations()
             .expect("Fail to close the registrations");
-
-        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
-        assert_eq!(contract.get_draw_number(), Ok(1));
-
-        // test purpose
-        let contract_salt = contract
+        contract.save_draw_closed(110).expect("Fail to save the draw");
+        contract
+            .save_nb_tickets(1, &[(100, 3), (101, 2)])
+            .expect("Fail to save the number of tickets");
+        let (salt, _) = contract
             .try_to_generate_salt()
             .expect("Fail to generate salt");
-        assert!(contract_salt.0.is_some(), "Salt not generated");
-        assert_eq!(contract_salt.1, vec![]);
-
-        assert_eq!(contract.get_status(), Ok(Status::WaitingResult));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+        contract
+            .set_results(1, vec![1, 2, 3, 4])
+            .expect("Fail to save the results");
+        let winners = vec![MultiAddress::AccountId32([1; 32])];
+        contract
+            .set_winners(1, winners.clone())
+            .expect("Fail to save the winners");
 
+        let draw = contract.get_last_finished_draw().expect("Draw not found");
+        assert_eq!(draw.draw_number, 1);
+        assert_eq!(draw.open_block, 100);
+        assert_eq!(draw.close_block, Some(110));
+        assert_eq!(draw.salt, salt);
+        assert_eq!(draw.numbers, Some(vec![1, 2, 3, 4]));
+        assert_eq!(draw.winners, winners);
+        assert_eq!(draw.nb_tickets, vec![(100, 3), (101, 2)]);
+
+        assert_eq!(contract.get_draws(0, 10), vec![draw.clone()]);
+        assert_eq!(contract.get_draws(1, 1), vec![draw]);
+        assert_eq!(contract.get_draws(2, 10), vec![]);
+        assert_eq!(contract.get_draws(0, 1), vec![]);
     }
 
+    #[ink::test]
+    fn test_draws_page() {
+        let mut contract = Contract::new();
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
+
+        contract
+            .set_registration_contracts(vec![100])
+            .expect("Fail to add registrations contract");
+        contract.start(60).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(60, Status::Started, vec![100])
+            .expect("Fail to save the status");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .save_draw_opened(100, config)
+            .expect("Fail to save the draw");
+
+        let draw = contract.get_draw(61).expect("Draw not found");
+        // at most MAX_DRAWS_PAGE draw numbers are read
+        assert_eq!(contract.get_draws(11, u32::MAX), vec![]);
+        assert_eq!(contract.get_draws(12, u32::MAX), vec![draw.clone()]);
+        assert_eq!(contract.get_draws(12, MAX_DRAWS_PAGE), vec![draw]);
+    }
 
     #[ink::test]
-    fn test_generate_salt() {
+    fn test_config_update() {
         let mut contract = Contract::new();
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
 
-        let salt_1 : Salt = [1u8; 32].to_vec();
-        let salt_2 : Salt = [2u8; 32].to_vec();
-        let salt_3 : Salt = [3u8; 32].to_vec();
-        let salts = [&salt_1, &salt_2, &salt_3];
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
+        assert_eq!(contract.request_config_update(), Err(IncorrectStatus));
 
-        let generated_salt = contract.generate_salt(1, &salts)
-            .expect("Fail to generate salt");
+        contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
 
-        let expected_salt : Salt = [94, 193, 212, 179, 22, 80, 18, 236, 194, 56, 99, 20, 16, 125, 123, 20, 14, 26, 212, 42, 96, 187, 51, 110, 129, 113, 120, 162, 223, 50, 36, 79].to_vec();
-        assert_eq!(expected_salt, generated_salt);
+        assert_eq!(contract.request_config_update(), Ok(vec![100, 101]));
+        assert!(contract.is_config_update_in_progress());
+        // the registrations cannot be open until the config is propagated to all contracts
+        assert_eq!(contract.open_registrations(), Err(IncorrectStatus));
+
+        assert_eq!(contract.save_config_updated(vec![101]), vec![100]);
+        assert!(contract.is_config_update_in_progress());
+        assert_eq!(contract.save_config_updated(vec![100]), vec![]);
+        assert!(!contract.is_config_update_in_progress());
+
+        assert_eq!(contract.open_registrations(), Ok(1));
+        assert_eq!(contract.request_config_update(), Err(IncorrectStatus));
 
+        // the config is saved for this draw
+        assert_eq!(contract.get_draw_config(1), None);
+        contract
+            .save_draw_opened(100, config)
+            .expect("Fail to save the draw");
+        assert_eq!(contract.get_draw_config(1), Some(config));
+        assert_eq!(contract.get_draw_config(2), None);
     }
 
     #[ink::test]
-    fn test_set_results() {
+    fn test_participation_threshold() {
         let mut contract = Contract::new();
 
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
+        contract
+            .set_participation_threshold(Some(ParticipationThreshold {
+                min_tickets: 10,
+                min_pot: 0,
+                extension_blocks: 5,
+                max_extensions: 2,
+            }))
+            .expect("Fail to set the threshold");
+
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
 
-        assert_eq!(contract.set_results(1, vec![]), Err(IncorrectStatus));
+        // the registration contracts are not synchronized
+        assert_eq!(contract.try_to_extend_registrations(), Err(IncorrectStatus));
+
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100, 101])
+            .expect("Fail to save the status");
+        assert_eq!(contract.save_nb_tickets(2, &[(100, 3)]), Err(IncorrectDrawNumber));
+        contract
+            .save_nb_tickets(1, &[(100, 3), (101, 4)])
+            .expect("Fail to save the number of tickets");
+        assert_eq!(contract.get_nb_tickets(1), 7);
+
+        // threshold not met => extension
+        assert_eq!(contract.try_to_extend_registrations(), Ok(true));
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert_eq!(contract.get_draw_number(), Ok(1));
+        assert_eq!(contract.get_nb_extensions(1), 1);
 
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .close_registrations()
             .expect("Fail to close the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .save_nb_tickets(1, &[(100, 4), (101, 4)])
+            .expect("Fail to save the number of tickets");
 
-        assert_eq!(contract.set_results(0, vec![]), Err(IncorrectDrawNumber));
-        assert_eq!(contract.set_results(2, vec![]), Err(IncorrectDrawNumber));
+        // threshold still not met => second extension
+        assert_eq!(contract.try_to_extend_registrations(), Ok(true));
+        assert_eq!(contract.get_nb_extensions(1), 2);
 
-        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
-        assert_eq!(contract.get_draw_number(), Ok(1));
         contract
-            .try_to_generate_salt()
-            .expect("Fail to generate salt");
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
         contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
-        assert_eq!(contract.get_status(), Ok(Status::WaitingWinner));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100, 101])
+            .expect("Fail to save the status");
 
-        assert_eq!(contract.get_results(1), Some(vec![1, 2, 3, 4]));
-        assert_eq!(contract.get_results(0), None);
-        assert_eq!(contract.get_results(2), None);
+        // maximum number of extensions reached => the draw continues
+        assert_eq!(contract.try_to_extend_registrations(), Ok(false));
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
     }
 
     #[ink::test]
-    fn test_ensure_same_results() {
+    fn test_jackpot() {
         let mut contract = Contract::new();
 
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
-        contract
-            .close_registrations()
-            .expect("Fail to close the registrations");
-        contract
-            .try_to_generate_salt()
-            .expect("Fail to generate the salt");
-        contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
+        assert_eq!(contract.get_jackpot(1), Some(JackpotInfo::default()));
 
-        contract
-            .ensure_same_results(1, vec![1, 2, 3, 4].as_slice())
-            .expect("Check same results failed");
-        assert_eq!(
-            contract.ensure_same_results(1, vec![1, 2, 3, 5].as_slice()),
-            Err(DifferentResults)
-        );
         assert_eq!(
-            contract.ensure_same_results(2, vec![1, 2, 3, 4].as_slice()),
-            Err(DifferentResults)
+            contract.save_jackpot_contributions(2, &[(100, 300)]),
+            Err(IncorrectDrawNumber)
         );
+        contract
+            .save_jackpot_contributions(1, &[(100, 300), (101, 200)])
+            .expect("Fail to save the contributions");
+        // the registrations are extended, the total of the contract is reported again
+        contract
+            .save_jackpot_contributions(1, &[(100, 400)])
+            .expect("Fail to save the contributions");
+        let jackpot = contract.get_jackpot(1).expect("Jackpot unknown");
+        assert_eq!(jackpot.contributions, vec![(100, 400), (101, 200)]);
+        assert_eq!(jackpot.total(), 600);
+
+        // no winner => the jackpot is rolled over
+        contract.cancel_draw(1).expect("Fail to cancel the draw");
+        let jackpot = contract
+            .settle_jackpot(1, false)
+            .expect("Fail to settle the jackpot")
+            .expect("Jackpot not settled");
+        assert_eq!(jackpot.rolled_over, 600);
+        assert_eq!(jackpot.paid, 0);
+        // the jackpot is settled only once
+        assert_eq!(contract.settle_jackpot(1, false), Ok(None));
+
+        // second draw
+        contract
+            .save_registration_contracts_status(1, Status::DrawCancelled, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_jackpot(2).expect("Jackpot unknown").carried_over, 600);
+        contract
+            .save_jackpot_contributions(2, &[(100, 100), (101, 50)])
+            .expect("Fail to save the contributions");
+
+        // winner => the jackpot is paid
+        let jackpot = contract
+            .settle_jackpot(2, true)
+            .expect("Fail to settle the jackpot")
+            .expect("Jackpot not settled");
+        assert_eq!(jackpot.paid, 750);
+        assert_eq!(jackpot.rolled_over, 0);
+        assert_eq!(contract.get_jackpot(3), None);
     }
 
     #[ink::test]
-    fn test_set_winners() {
+    fn test_cancelled_jackpot() {
         let mut contract = Contract::new();
 
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
+        contract
+            .save_jackpot_contributions(1, &[(100, 300), (101, 200)])
+            .expect("Fail to save the contributions");
 
-        assert_eq!(contract.set_winners(1, (vec![], vec![])), Err(IncorrectStatus));
-
+        // the jackpot of the first draw is rolled over
+        contract.cancel_draw(1).expect("Fail to cancel the draw");
         contract
-            .close_registrations()
-            .expect("Fail to close the registrations");
+            .settle_jackpot(1, false)
+            .expect("Fail to settle the jackpot")
+            .expect("Jackpot not settled");
 
+        // second draw
         contract
-            .try_to_generate_salt()
-            .expect("Fail to generate salt");
+            .save_registration_contracts_status(1, Status::DrawCancelled, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        contract
+            .save_jackpot_contributions(2, &[(100, 100), (101, 50)])
+            .expect("Fail to save the contributions");
 
-        assert_eq!(contract.set_winners(1, (vec![], vec![])), Err(IncorrectStatus));
+        // the draw is cancelled => the tickets are refunded, only the amount carried over is rolled over
+        contract.cancel_draw(2).expect("Fail to cancel the draw");
+        let jackpot = contract
+            .cancel_jackpot(2)
+            .expect("Fail to settle the jackpot")
+            .expect("Jackpot not settled");
+        assert_eq!(jackpot.carried_over, 500);
+        assert_eq!(jackpot.rolled_over, 500);
+        assert_eq!(jackpot.paid, 0);
+        // the jackpot is settled only once
+        assert_eq!(contract.cancel_jackpot(2), Ok(None));
+        assert_eq!(contract.settle_jackpot(2, false), Ok(None));
 
+        // third draw
         contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
+            .save_registration_contracts_status(2, Status::DrawCancelled, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .open_registrations()
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_jackpot(3).expect("Jackpot unknown").carried_over, 500);
+    }
 
-        assert_eq!(contract.set_winners(0, (vec![], vec![])), Err(IncorrectDrawNumber));
-        assert_eq!(contract.set_winners(2, (vec![], vec![])), Err(IncorrectDrawNumber));
+    #[ink::test]
+    fn test_settlement_asset() {
+        let mut contract = Contract::new();
 
-        assert_eq!(contract.get_status(), Ok(Status::WaitingWinner));
-        assert_eq!(contract.get_draw_number(), Ok(1));
         contract
-            .set_winners(1, (vec![], vec![]))
-            .expect("Fail to save the winners");
-
-        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
-        assert_eq!(contract.get_draw_number(), Ok(1));
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
 
-        assert_eq!(contract.get_winners(1), None);
-        assert_eq!(contract.get_results(0), None);
-        assert_eq!(contract.get_results(2), None);
-    }
+        // by default, the native token without conversion
+        assert_eq!(contract.get_settlement_asset(100), SettlementAsset::default());
 
+        let stablecoin = SettlementAsset {
+            asset: PaymentAsset::Token(MultiAddress::AccountId20([1u8; 20])),
+            rate_numerator: 5,
+            rate_denominator: 2,
+        };
+        assert_eq!(
+            contract.set_settlement_asset(102, stablecoin.clone()),
+            Err(UnknownRegistrationContract)
+        );
+        assert_eq!(
+            contract.set_settlement_asset(
+                101,
+                SettlementAsset {
+                    rate_denominator: 0,
+                    ..stablecoin.clone()
+                }
+            ),
+            Err(IncorrectExchangeRate)
+        );
+        contract
+            .set_settlement_asset(101, stablecoin.clone())
+            .expect("Fail to set the settlement asset");
+        assert_eq!(contract.get_settlement_asset(101), stablecoin);
 
-    #[ink::test]
-    fn test_set_winners_substrate() {
-        let mut contract = Contract::new();
+        // the ticket price is converted in the asset of the contract
+        assert_eq!(stablecoin.convert_back(500), Ok(200));
+        assert_eq!(stablecoin.convert(200), Ok(500));
+        assert_eq!(SettlementAsset::default().convert_back(500), Ok(500));
 
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
-        contract
-            .close_registrations()
-            .expect("Fail to close the registrations");
-        contract
-            .try_to_generate_salt()
-            .expect("Fail to generate salt");
-        contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
-
-        let address_substrate_1 = [1;32];
-        let address_substrate_2 = [2;32];
 
+        // the amounts are converted in the unit of the jackpot
         contract
-            .set_winners(1, (vec![address_substrate_1, address_substrate_2], vec![]))
-            .expect("Fail to save the winners");
-
-        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
-        assert_eq!(contract.get_draw_number(), Ok(1));
-        assert_eq!(contract.get_winners(1), Some((vec![address_substrate_1, address_substrate_2], vec![])));
+            .save_jackpot_contributions(1, &[(100, 300), (101, 200)])
+            .expect("Fail to save the contributions");
+        let jackpot = contract.get_jackpot(1).expect("Jackpot unknown");
+        assert_eq!(jackpot.contributions, vec![(100, 300), (101, 500)]);
+        assert_eq!(
+            contract.save_jackpot_contributions(1, &[(101, Balance::MAX)]),
+            Err(MulOverFlow)
+        );
     }
 
-
     #[ink::test]
-    fn test_set_winners_evm() {
+    fn test_participation_threshold_met() {
         let mut contract = Contract::new();
 
+        contract
+            .set_participation_threshold(Some(ParticipationThreshold {
+                min_tickets: 10,
+                min_pot: 0,
+                extension_blocks: 5,
+                max_extensions: 2,
+            }))
+            .expect("Fail to set the threshold");
+        contract
+            .set_registration_contracts(vec![100])
+            .expect("Fail to add registrations contract");
+
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100])
+            .expect("Fail to save the status");
         contract
             .close_registrations()
             .expect("Fail to close the registrations");
         contract
-            .try_to_generate_salt()
-            .expect("Fail to generate salt");
-        contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
-
-        let address_evm_1 = [1;20];
-
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100])
+            .expect("Fail to save the status");
         contract
-            .set_winners(1, (vec![], vec![address_evm_1]))
-            .expect("Fail to save the winners");
+            .save_nb_tickets(1, &[(100, 10)])
+            .expect("Fail to save the number of tickets");
 
-        assert_eq!(contract.get_status(), Ok(Status::DrawFinished));
-        assert_eq!(contract.get_draw_number(), Ok(1));
-        assert_eq!(contract.get_winners(1), Some((vec![], vec![address_evm_1])));
+        assert_eq!(contract.try_to_extend_registrations(), Ok(false));
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
+        assert_eq!(contract.get_nb_extensions(1), 0);
     }
 
-
     #[ink::test]
-    fn test_reopen_after_results_and_winners() {
+    fn test_participation_threshold_min_pot() {
         let mut contract = Contract::new();
+
+        contract
+            .set_participation_threshold(Some(ParticipationThreshold {
+                min_tickets: 0,
+                min_pot: 1_000,
+                extension_blocks: 5,
+                max_extensions: 2,
+            }))
+            .expect("Fail to set the threshold");
+        contract
+            .set_registration_contracts(vec![100, 101])
+            .expect("Fail to add registrations contract");
+        contract
+            .set_settlement_asset(
+                101,
+                SettlementAsset {
+                    asset: PaymentAsset::Native,
+                    rate_numerator: 2,
+                    rate_denominator: 1,
+                },
+            )
+            .expect("Fail to set the settlement asset");
+
         contract.start(0).expect("Fail to start");
+        contract
+            .save_registration_contracts_status(0, Status::Started, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .open_registrations()
             .expect("Fail to open the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
         contract
             .close_registrations()
             .expect("Fail to close the registrations");
         contract
-            .try_to_generate_salt()
-            .expect("Fail to generate salt");
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100, 101])
+            .expect("Fail to save the status");
+
+        // 300 + 2 * 300 => the pot is not reached
         contract
-            .set_results(1, vec![1, 2, 3, 4])
-            .expect("Fail to save the results");
+            .save_jackpot_contributions(1, &[(100, 300), (101, 300)])
+            .expect("Fail to save the contributions");
+        assert_eq!(contract.try_to_extend_registrations(), Ok(true));
+        assert_eq!(contract.get_nb_extensions(1), 1);
+
         contract
-            .set_winners(1, (vec![], vec![]))
-            .expect("Fail to save the winners");
+            .save_registration_contracts_status(1, Status::RegistrationsOpen, vec![100, 101])
+            .expect("Fail to save the status");
+        contract
+            .close_registrations()
+            .expect("Fail to close the registrations");
+        contract
+            .save_registration_contracts_status(1, Status::RegistrationsClosed, vec![100, 101])
+            .expect("Fail to save the status");
 
+        // 400 + 2 * 300 => the pot is reached
         contract
-            .open_registrations()
-            .expect("Fail to open the registrations");
-        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
-        assert_eq!(contract.get_draw_number(), Ok(2));
+            .save_jackpot_contributions(1, &[(100, 400)])
+            .expect("Fail to save the contributions");
+        assert_eq!(contract.try_to_extend_registrations(), Ok(false));
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsClosed));
     }
 
     #[ink::test]
@@ -944,7 +2448,7 @@ mod tests {
             .set_results(1, vec![1, 2, 3, 4])
             .expect("Fail to save the results");
         contract
-            .set_winners(1, (vec![], vec![]))
+            .set_winners(1, vec![])
             .expect("Fail to save the winners");
         contract
             .save_registration_contracts_status(1, Status::DrawFinished, vec![100, 101, 102])
@@ -994,7 +2498,7 @@ mod tests {
             .set_results(2, vec![10, 35, 8, 10])
             .expect("Fail to save the results");
         contract
-            .set_winners(2, (vec![], vec![]))
+            .set_winners(2, vec![])
             .expect("Fail to save the winners");
         contract
             .save_registration_contracts_status(2, Status::DrawFinished, vec![100, 101, 102])
@@ -1,11 +1,20 @@
+use crate::config::{Config, RevenueSplit};
 use crate::error::{RaffleError, RaffleError::*};
-use crate::{DrawNumber, Number};
+use crate::{Balance, DrawNumber, Number};
 use ink::prelude::vec::Vec;
 use phat_rollup_anchor_ink::traits::rollup_anchor::RollupAnchor;
 use scale::{Decode, Encode};
 
 const STATUS: u32 = ink::selector_id!("STATUS");
 const DRAW_NUMBER: u32 = ink::selector_id!("DRAW_NUMBER");
+const CANCELLED_DRAW: u32 = ink::selector_id!("CANCELLED_DRAW");
+const PAUSED: u32 = ink::selector_id!("PAUSED");
+const NB_TICKETS: u32 = ink::selector_id!("NB_TICKETS");
+const CONFIG: u32 = ink::selector_id!("CONFIG");
+const REVENUE: u32 = ink::selector_id!("REVENUE");
+const RESERVE: u32 = ink::selector_id!("RESERVE");
+const TREASURY: u32 = ink::selector_id!("TREASURY");
+const DRAW_SHARES: u32 = ink::selector_id!("DRAW_SHARES");
 
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
 #[cfg_attr(
@@ -20,6 +29,16 @@ pub enum Status {
     RegistrationsClosed,
     SaltGenerated,
     ResultsReceived,
+    Closed,
+    DrawCancelled,
+}
+
+/// Parts of the revenue collected for the draw in progress
+#[derive(Default, Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+pub struct DrawShares {
+    pub jackpot: Balance,
+    pub treasury: Balance,
+    pub reserve: Balance,
 }
 
 #[openbrush::trait_definition]
@@ -36,12 +55,31 @@ pub trait Raffle: RollupAnchor {
         Ok(())
     }
 
-    /// Open the registrations
+    /// Open the registrations.
+    /// The registrations can be open again for the same draw when they are extended by the manager
     fn open_registrations(&mut self, draw_number: DrawNumber) -> Result<(), RaffleError> {
         // check the status
         let status = self.get_status()?;
-        if status != Status::Started && status != Status::ResultsReceived {
-            return Err(IncorrectStatus);
+        let extension = match status {
+            Status::Started | Status::ResultsReceived | Status::DrawCancelled => false,
+            Status::RegistrationsClosed => {
+                // check the draw number
+                if self.get_draw_number()? != draw_number {
+                    return Err(IncorrectDrawNumber);
+                }
+                true
+            }
+            _ => return Err(IncorrectStatus),
+        };
+
+        // reset the number of tickets for a new draw
+        // and seed the jackpot with the reserve
+        if !extension {
+            self.set_nb_tickets(0);
+            let reserve = self.get_reserve();
+            self.set_revenue(reserve);
+            self.set_reserve(0);
+            self.set_draw_shares(DrawShares::default());
         }
 
         self.set_draw_number(draw_number);
@@ -100,12 +138,133 @@ pub trait Raffle: RollupAnchor {
             return Err(IncorrectDrawNumber);
         }
 
+        // the draw is settled, its part of the treasury can be withdrawn
+        self.set_draw_shares(DrawShares::default());
+
         self.set_status(Status::ResultsReceived);
         Ok(())
     }
 
+    /// stop the workflow, the draw in progress is cancelled
+    /// Return true if the draw is cancelled
+    fn stop(&mut self, draw_number: DrawNumber) -> Result<bool, RaffleError> {
+        // check the status
+        let status = self.get_status()?;
+        let cancelled = match status {
+            Status::Closed => return Err(IncorrectStatus),
+            Status::RegistrationsOpen | Status::RegistrationsClosed | Status::SaltGenerated => {
+                // the draw in progress is cancelled
+                let current_draw_number = self.get_draw_number()?;
+                self.set_draw_cancelled(current_draw_number);
+                self.refund_draw()?;
+                true
+            }
+            _ => false,
+        };
+
+        self.set_draw_number(draw_number);
+        self.set_status(Status::Closed);
+        Ok(cancelled)
+    }
+
+    /// cancel the given draw, the registrations will be open again for a new draw
+    fn cancel_draw(&mut self, draw_number: DrawNumber) -> Result<(), RaffleError> {
+        // check the status
+        match self.get_status()? {
+            Status::NotStarted | Status::Closed | Status::DrawCancelled => {
+                return Err(IncorrectStatus)
+            }
+            Status::RegistrationsOpen | Status::RegistrationsClosed | Status::SaltGenerated => {
+                // the draw in progress is cancelled
+                self.refund_draw()?;
+            }
+            _ => {}
+        }
+
+        self.set_draw_cancelled(draw_number);
+        self.set_draw_number(draw_number);
+        self.set_status(Status::DrawCancelled);
+        Ok(())
+    }
+
+    /// take back the amounts paid for the draw in progress, the tickets are refunded to the participants.
+    /// The reserve used to seed the jackpot is kept for the next draw
+    fn refund_draw(&mut self) -> Result<(), RaffleError> {
+        let shares = self.get_draw_shares();
+        let seed = self
+            .get_revenue()
+            .checked_sub(shares.jackpot)
+            .ok_or(SubOverFlow)?;
+        let reserve = self
+            .get_reserve()
+            .checked_sub(shares.reserve)
+            .ok_or(SubOverFlow)?
+            .checked_add(seed)
+            .ok_or(AddOverFlow)?;
+        let treasury = self
+            .get_treasury()
+            .checked_sub(shares.treasury)
+            .ok_or(SubOverFlow)?;
+        self.set_revenue(0);
+        self.set_reserve(reserve);
+        self.set_treasury(treasury);
+        self.set_draw_shares(DrawShares::default());
+        Ok(())
+    }
+
+    fn set_draw_cancelled(&mut self, draw_number: DrawNumber) {
+        RollupAnchor::set_value(
+            self,
+            &(CANCELLED_DRAW, draw_number).encode(),
+            Some(&true.encode()),
+        );
+    }
+
+    /// return true if the draw has been cancelled
+    #[ink(message)]
+    fn is_draw_cancelled(&self, draw_number: DrawNumber) -> bool {
+        RollupAnchor::get_value(self, (CANCELLED_DRAW, draw_number).encode()).is_some()
+    }
+
+    /// check if the config can be updated, ie between two draws
+    fn check_can_update_config(&self) -> Result<(), RaffleError> {
+        match self.get_status()? {
+            Status::Started | Status::ResultsReceived | Status::DrawCancelled => Ok(()),
+            _ => Err(IncorrectStatus),
+        }
+    }
+
+    /// save in the kv store the config used by this contract (read by the offchain rollup)
+    fn set_config_in_kv_store(&mut self, config: Config) {
+        RollupAnchor::set_value(self, &CONFIG.encode(), Some(&config.encode()));
+    }
+
+    /// return the config saved in the kv store
+    fn get_config_in_kv_store(&self) -> Option<Config> {
+        RollupAnchor::get_value(self, CONFIG.encode())
+            .and_then(|v| Config::decode(&mut v.as_slice()).ok())
+    }
+
+    /// pause or unpause the participations, the workflow is not impacted
+    fn set_paused(&mut self, paused: bool) {
+        RollupAnchor::set_value(self, &PAUSED.encode(), Some(&paused.encode()));
+    }
+
+    /// return true if the participations are paused
+    #[ink(message)]
+    fn is_paused(&self) -> bool {
+        match RollupAnchor::get_value(self, PAUSED.encode()) {
+            Some(v) => bool::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => false,
+        }
+    }
+
     /// check if the registrations are open
     fn check_can_participate(&mut self) -> Result<(), RaffleError> {
+        // check if the participations are paused
+        if self.is_paused() {
+            return Err(Paused);
+        }
         // check the status
         if !self.can_participate() {
             return Err(IncorrectStatus);
@@ -114,10 +273,124 @@ pub trait Raffle: RollupAnchor {
         Ok(())
     }
 
+    /// record a new ticket for the current draw
+    fn add_ticket(&mut self) -> Result<(), RaffleError> {
+        let nb_tickets = self.get_nb_tickets().checked_add(1).ok_or(AddOverFlow)?;
+        self.set_nb_tickets(nb_tickets);
+        Ok(())
+    }
+
+    fn set_nb_tickets(&mut self, nb_tickets: u32) {
+        RollupAnchor::set_value(self, &NB_TICKETS.encode(), Some(&nb_tickets.encode()));
+    }
+
+    /// return the number of tickets recorded for the current draw
+    #[ink(message)]
+    fn get_nb_tickets(&self) -> u32 {
+        match RollupAnchor::get_value(self, NB_TICKETS.encode()) {
+            Some(v) => u32::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => 0,
+        }
+    }
+
+    /// record the amount paid for the tickets of the current draw.
+    /// The amount is split between the jackpot, the treasury and the reserve for the next draw
+    fn add_revenue(
+        &mut self,
+        amount: Balance,
+        revenue_split: &RevenueSplit,
+    ) -> Result<(), RaffleError> {
+        let jackpot_part = amount / 100 * revenue_split.jackpot as Balance
+            + amount % 100 * revenue_split.jackpot as Balance / 100;
+        let reserve_part = amount / 100 * revenue_split.reserve as Balance
+            + amount % 100 * revenue_split.reserve as Balance / 100;
+        // the rounding goes to the treasury
+        let treasury_part = amount - jackpot_part - reserve_part;
+
+        let revenue = self.get_revenue().checked_add(jackpot_part).ok_or(AddOverFlow)?;
+        let reserve = self.get_reserve().checked_add(reserve_part).ok_or(AddOverFlow)?;
+        let treasury = self.get_treasury().checked_add(treasury_part).ok_or(AddOverFlow)?;
+        self.set_revenue(revenue);
+        self.set_reserve(reserve);
+        self.set_treasury(treasury);
+
+        // keep the parts of the draw in progress to refund them if the draw is cancelled
+        let shares = self.get_draw_shares();
+        self.set_draw_shares(DrawShares {
+            jackpot: shares.jackpot.checked_add(jackpot_part).ok_or(AddOverFlow)?,
+            treasury: shares.treasury.checked_add(treasury_part).ok_or(AddOverFlow)?,
+            reserve: shares.reserve.checked_add(reserve_part).ok_or(AddOverFlow)?,
+        });
+        Ok(())
+    }
+
+    fn set_draw_shares(&mut self, shares: DrawShares) {
+        RollupAnchor::set_value(self, &DRAW_SHARES.encode(), Some(&shares.encode()));
+    }
+
+    /// return the parts of the revenue collected for the draw in progress
+    fn get_draw_shares(&self) -> DrawShares {
+        match RollupAnchor::get_value(self, DRAW_SHARES.encode()) {
+            Some(v) => DrawShares::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => DrawShares::default(),
+        }
+    }
+
+    fn set_revenue(&mut self, revenue: Balance) {
+        RollupAnchor::set_value(self, &REVENUE.encode(), Some(&revenue.encode()));
+    }
+
+    /// return the amount paid for the tickets of the current draw
+    #[ink(message)]
+    fn get_revenue(&self) -> Balance {
+        match RollupAnchor::get_value(self, REVENUE.encode()) {
+            Some(v) => Balance::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => 0,
+        }
+    }
+
+    fn set_reserve(&mut self, reserve: Balance) {
+        RollupAnchor::set_value(self, &RESERVE.encode(), Some(&reserve.encode()));
+    }
+
+    /// return the amount kept in reserve to seed the jackpot of the next draw
+    #[ink(message)]
+    fn get_reserve(&self) -> Balance {
+        match RollupAnchor::get_value(self, RESERVE.encode()) {
+            Some(v) => Balance::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => 0,
+        }
+    }
+
+    fn set_treasury(&mut self, treasury: Balance) {
+        RollupAnchor::set_value(self, &TREASURY.encode(), Some(&treasury.encode()));
+    }
+
+    /// return the amount kept by the operator, it is the only amount that can be withdrawn
+    #[ink(message)]
+    fn get_treasury(&self) -> Balance {
+        match RollupAnchor::get_value(self, TREASURY.encode()) {
+            Some(v) => Balance::decode(&mut v.as_slice()).unwrap_or_default(),
+            _ => 0,
+        }
+    }
+
+    /// withdraw the given amount from the treasury, the jackpot and the reserve are never touched.
+    /// The part of the draw in progress is kept until the draw is settled because it is refunded if the draw is cancelled
+    fn withdraw_treasury(&mut self, amount: Balance) -> Result<(), RaffleError> {
+        let treasury = self.get_treasury();
+        let available = treasury.saturating_sub(self.get_draw_shares().treasury);
+        if amount > available {
+            return Err(InsufficientTreasury);
+        }
+        self.set_treasury(treasury - amount);
+        Ok(())
+    }
+
     /// check if the user can participate are open
     #[ink(message)]
     fn can_participate(&mut self) -> bool {
-        self.get_status() == Ok(Status::RegistrationsOpen)
+        !self.is_paused() && self.get_status() == Ok(Status::RegistrationsOpen)
     }
 
     #[ink(message)]
@@ -366,6 +639,108 @@ mod tests {
         assert_eq!(contract.get_draw_number(), Ok(13));
     }
 
+    #[ink::test]
+    fn test_reopen_same_draw() {
+        let mut contract = Contract::new();
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        contract.add_ticket().expect("Fail to add a ticket");
+        contract.add_ticket().expect("Fail to add a ticket");
+        contract.add_revenue(200, &RevenueSplit::default()).expect("Fail to add the revenue");
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        assert_eq!(contract.get_nb_tickets(), 2);
+        assert_eq!(contract.get_revenue(), 200);
+
+        // the registrations are extended
+        assert_eq!(contract.open_registrations(11), Err(IncorrectDrawNumber));
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert_eq!(contract.get_draw_number(), Ok(10));
+        // the tickets and the revenue are kept
+        assert_eq!(contract.get_nb_tickets(), 2);
+        assert_eq!(contract.get_revenue(), 200);
+        contract.add_ticket().expect("Fail to add a ticket");
+        contract.add_revenue(100, &RevenueSplit::default()).expect("Fail to add the revenue");
+        assert_eq!(contract.add_revenue(Balance::MAX, &RevenueSplit::default()), Err(AddOverFlow));
+
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        assert_eq!(contract.get_nb_tickets(), 3);
+        assert_eq!(contract.get_revenue(), 300);
+        contract
+            .save_results(10, vec![], false)
+            .expect("Fail to save the results");
+
+        // new draw => the tickets and the revenue are reset
+        contract
+            .open_registrations(11)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_nb_tickets(), 0);
+        assert_eq!(contract.get_revenue(), 0);
+    }
+
+    #[ink::test]
+    fn test_revenue_split() {
+        let mut contract = Contract::new();
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(1)
+            .expect("Fail to open the registrations");
+
+        let revenue_split = RevenueSplit {
+            jackpot: 70,
+            treasury: 20,
+            reserve: 10,
+        };
+        contract
+            .add_revenue(1_000, &revenue_split)
+            .expect("Fail to add the revenue");
+        assert_eq!(contract.get_revenue(), 700);
+        assert_eq!(contract.get_treasury(), 200);
+        assert_eq!(contract.get_reserve(), 100);
+
+        // the rounding goes to the treasury
+        contract
+            .add_revenue(15, &revenue_split)
+            .expect("Fail to add the revenue");
+        assert_eq!(contract.get_revenue(), 710);
+        assert_eq!(contract.get_treasury(), 204);
+        assert_eq!(contract.get_reserve(), 101);
+
+        // the treasury of the draw in progress cannot be withdrawn
+        assert_eq!(contract.withdraw_treasury(1), Err(InsufficientTreasury));
+
+        contract
+            .close_registrations(1)
+            .expect("Fail to close the registrations");
+        contract
+            .save_results(1, vec![], false)
+            .expect("Fail to save the results");
+
+        // only the treasury can be withdrawn
+        assert_eq!(contract.withdraw_treasury(205), Err(InsufficientTreasury));
+        contract
+            .withdraw_treasury(200)
+            .expect("Fail to withdraw the treasury");
+        assert_eq!(contract.get_treasury(), 4);
+        assert_eq!(contract.get_revenue(), 710);
+
+        // new draw => the jackpot is seeded with the reserve
+        contract
+            .open_registrations(2)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_revenue(), 101);
+        assert_eq!(contract.get_reserve(), 0);
+        assert_eq!(contract.get_treasury(), 4);
+    }
+
     #[ink::test]
     fn test_full() {
         let mut contract = Contract::new();
@@ -389,4 +764,207 @@ mod tests {
             .save_results(2, vec![], true)
             .expect("Fail to save the results");
     }
+
+    #[ink::test]
+    fn test_stop() {
+        let mut contract = Contract::new();
+
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        contract
+            .save_results(10, vec![1, 2, 3, 4], false)
+            .expect("Fail to save the results");
+
+        // no draw in progress
+        assert_eq!(contract.stop(10), Ok(false));
+        assert_eq!(contract.get_status(), Ok(Status::Closed));
+        assert_eq!(contract.get_draw_number(), Ok(10));
+        assert!(!contract.is_draw_cancelled(10));
+
+        assert_eq!(contract.stop(10), Err(IncorrectStatus));
+        assert_eq!(contract.open_registrations(11), Err(IncorrectStatus));
+        assert!(!contract.can_participate());
+    }
+
+    #[ink::test]
+    fn test_stop_when_registrations_open() {
+        let mut contract = Contract::new();
+
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+
+        // the draw in progress is cancelled
+        assert_eq!(contract.stop(10), Ok(true));
+        assert_eq!(contract.get_status(), Ok(Status::Closed));
+        assert!(contract.is_draw_cancelled(10));
+        assert!(!contract.is_draw_cancelled(9));
+        assert!(!contract.can_participate());
+    }
+
+    #[ink::test]
+    fn test_cancel_draw() {
+        let mut contract = Contract::new();
+
+        assert_eq!(contract.cancel_draw(10), Err(IncorrectStatus));
+
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        contract
+            .generate_salt(10)
+            .expect("Fail to generate the salt");
+
+        contract.cancel_draw(10).expect("Fail to cancel the draw");
+        assert_eq!(contract.get_status(), Ok(Status::DrawCancelled));
+        assert_eq!(contract.get_draw_number(), Ok(10));
+        assert!(contract.is_draw_cancelled(10));
+        assert_eq!(contract.cancel_draw(10), Err(IncorrectStatus));
+        assert_eq!(
+            contract.save_results(10, vec![], false),
+            Err(IncorrectStatus)
+        );
+
+        // the registrations are open again for the next draw
+        contract
+            .open_registrations(11)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_status(), Ok(Status::RegistrationsOpen));
+        assert!(!contract.is_draw_cancelled(11));
+    }
+
+    #[ink::test]
+    fn test_cancel_draw_refund() {
+        let mut contract = Contract::new();
+        let revenue_split = RevenueSplit {
+            jackpot: 70,
+            treasury: 20,
+            reserve: 10,
+        };
+
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        contract
+            .add_revenue(1_000, &revenue_split)
+            .expect("Fail to add the revenue");
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        contract
+            .save_results(10, vec![], false)
+            .expect("Fail to save the results");
+
+        // the next draw is seeded with the reserve
+        contract
+            .open_registrations(11)
+            .expect("Fail to open the registrations");
+        contract
+            .add_revenue(500, &revenue_split)
+            .expect("Fail to add the revenue");
+        assert_eq!(contract.get_revenue(), 450);
+        assert_eq!(contract.get_treasury(), 300);
+        assert_eq!(contract.get_reserve(), 50);
+
+        // the draw is cancelled => the amounts paid are taken back to be refunded
+        contract.cancel_draw(11).expect("Fail to cancel the draw");
+        assert_eq!(contract.get_revenue(), 0);
+        assert_eq!(contract.get_treasury(), 200);
+        // the seed is kept for the next draw
+        assert_eq!(contract.get_reserve(), 100);
+
+        contract
+            .open_registrations(12)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.get_revenue(), 100);
+        assert_eq!(contract.get_reserve(), 0);
+        contract
+            .withdraw_treasury(200)
+            .expect("Fail to withdraw the treasury");
+    }
+
+    #[ink::test]
+    fn test_pause() {
+        let mut contract = Contract::new();
+
+        assert!(!contract.is_paused());
+
+        contract.start().expect("Fail to start");
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        assert!(contract.can_participate());
+
+        contract.set_paused(true);
+        assert!(contract.is_paused());
+        assert!(!contract.can_participate());
+        assert_eq!(contract.check_can_participate(), Err(Paused));
+
+        // the workflow is not impacted
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        contract
+            .save_results(10, vec![], false)
+            .expect("Fail to save the results");
+        contract
+            .open_registrations(11)
+            .expect("Fail to open the registrations");
+        assert!(!contract.can_participate());
+
+        contract.set_paused(false);
+        assert!(!contract.is_paused());
+        assert!(contract.can_participate());
+        contract
+            .check_can_participate()
+            .expect("Check Participations Failed");
+    }
+
+    #[ink::test]
+    fn test_update_config() {
+        let mut contract = Contract::new();
+        let config = Config {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
+
+        assert_eq!(contract.check_can_update_config(), Err(IncorrectStatus));
+        assert_eq!(contract.get_config_in_kv_store(), None);
+        contract.set_config_in_kv_store(config);
+        assert_eq!(contract.get_config_in_kv_store(), Some(config));
+
+        contract.start().expect("Fail to start");
+        contract
+            .check_can_update_config()
+            .expect("Config cannot be updated");
+
+        contract
+            .open_registrations(10)
+            .expect("Fail to open the registrations");
+        assert_eq!(contract.check_can_update_config(), Err(IncorrectStatus));
+
+        contract
+            .close_registrations(10)
+            .expect("Fail to close the registrations");
+        assert_eq!(contract.check_can_update_config(), Err(IncorrectStatus));
+
+        contract
+            .save_results(10, vec![], false)
+            .expect("Fail to save the results");
+        contract
+            .check_can_update_config()
+            .expect("Config cannot be updated");
+    }
 }
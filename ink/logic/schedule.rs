@@ -0,0 +1,187 @@
+use crate::error::RaffleError;
+use crate::error::RaffleError::*;
+
+/// Timestamp in milliseconds since the unix epoch (same as `env().block_timestamp()`)
+pub type Timestamp = u64;
+
+const MINUTE: Timestamp = 60 * 1000;
+const HOUR: Timestamp = 60 * MINUTE;
+const DAY: Timestamp = 24 * HOUR;
+const WEEK: Timestamp = 7 * DAY;
+
+/// Day of the week, Monday is the first day
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum WeekDay {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+/// Rule used to close the registrations at a given time (UTC)
+#[derive(Debug, Eq, PartialEq, Copy, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Schedule {
+    /// every day at the given hour and minute
+    Daily { hour: u8, minute: u8 },
+    /// every week on the given day at the given hour and minute
+    Weekly { day: WeekDay, hour: u8, minute: u8 },
+}
+
+impl Schedule {
+    fn hour_and_minute(&self) -> (u8, u8) {
+        match self {
+            Schedule::Daily { hour, minute } => (*hour, *minute),
+            Schedule::Weekly { hour, minute, .. } => (*hour, *minute),
+        }
+    }
+
+    /// check the hour and the minute
+    pub fn check(&self) -> Result<(), RaffleError> {
+        let (hour, minute) = self.hour_and_minute();
+        if hour >= 24 || minute >= 60 {
+            return Err(IncorrectSchedule);
+        }
+        Ok(())
+    }
+
+    /// return the first occurrence strictly after the given timestamp
+    pub fn next_occurrence(&self, timestamp: Timestamp) -> Result<Timestamp, RaffleError> {
+        self.check()?;
+
+        let (hour, minute) = self.hour_and_minute();
+        let time_of_day = hour as Timestamp * HOUR + minute as Timestamp * MINUTE;
+
+        // number of days since the unix epoch
+        let day_number = timestamp / DAY;
+
+        let (first_day, period) = match self {
+            Schedule::Daily { .. } => (day_number, DAY),
+            Schedule::Weekly { day, .. } => {
+                // the unix epoch (day 0) was a Thursday
+                let current_day = (day_number + WeekDay::Thursday as Timestamp) % 7;
+                let nb_days = (*day as Timestamp + 7 - current_day) % 7;
+                (day_number + nb_days, WEEK)
+            }
+        };
+
+        let next = first_day
+            .checked_mul(DAY)
+            .and_then(|t| t.checked_add(time_of_day))
+            .ok_or(AddOverFlow)?;
+
+        if next > timestamp {
+            return Ok(next);
+        }
+        next.checked_add(period).ok_or(AddOverFlow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Saturday 2024-01-06 20:00 UTC
+    const SATURDAY_8PM: Timestamp = 1704571200000;
+    // Wednesday 2024-01-03 10:30 UTC
+    const WEDNESDAY_10_30AM: Timestamp = 1704277800000;
+
+    #[ink::test]
+    fn test_check() {
+        Schedule::Daily {
+            hour: 23,
+            minute: 59,
+        }
+        .check()
+        .expect("Fail to check the schedule");
+        assert_eq!(
+            Schedule::Daily {
+                hour: 24,
+                minute: 0
+            }
+            .check(),
+            Err(IncorrectSchedule)
+        );
+        assert_eq!(
+            Schedule::Weekly {
+                day: WeekDay::Monday,
+                hour: 0,
+                minute: 60
+            }
+            .check(),
+            Err(IncorrectSchedule)
+        );
+    }
+
+    #[ink::test]
+    fn test_daily() {
+        let schedule = Schedule::Daily {
+            hour: 20,
+            minute: 0,
+        };
+
+        // same day
+        assert_eq!(
+            schedule.next_occurrence(WEDNESDAY_10_30AM),
+            Ok(1704312000000)
+        );
+        // exactly at the given time => next day
+        assert_eq!(
+            schedule.next_occurrence(SATURDAY_8PM),
+            Ok(SATURDAY_8PM + DAY)
+        );
+        assert_eq!(
+            schedule.next_occurrence(SATURDAY_8PM - MINUTE),
+            Ok(SATURDAY_8PM)
+        );
+    }
+
+    #[ink::test]
+    fn test_weekly() {
+        let schedule = Schedule::Weekly {
+            day: WeekDay::Saturday,
+            hour: 20,
+            minute: 0,
+        };
+
+        assert_eq!(
+            schedule.next_occurrence(WEDNESDAY_10_30AM),
+            Ok(SATURDAY_8PM)
+        );
+        assert_eq!(
+            schedule.next_occurrence(SATURDAY_8PM - MINUTE),
+            Ok(SATURDAY_8PM)
+        );
+        // exactly at the given time => next week
+        assert_eq!(
+            schedule.next_occurrence(SATURDAY_8PM),
+            Ok(SATURDAY_8PM + WEEK)
+        );
+        assert_eq!(
+            schedule.next_occurrence(SATURDAY_8PM + 1),
+            Ok(SATURDAY_8PM + WEEK)
+        );
+
+        // Thursday 2024-01-04 at 20:00 UTC
+        let schedule = Schedule::Weekly {
+            day: WeekDay::Thursday,
+            hour: 20,
+            minute: 0,
+        };
+        assert_eq!(
+            schedule.next_occurrence(WEDNESDAY_10_30AM),
+            Ok(1704398400000)
+        );
+        assert_eq!(schedule.next_occurrence(0), Ok(20 * HOUR));
+    }
+}
@@ -9,19 +9,21 @@ mod lotto_draw_multichain {
     use alloc::vec::Vec;
     use ink::prelude::string::String;
     use ink::storage::Mapping;
+    use lotto_draw_logic::contract_id::{ChainFamily, ShardedContractId};
     use lotto_draw_logic::draw::Draw;
     use lotto_draw_logic::error::RaffleDrawError;
     use lotto_draw_logic::evm_contract::EvmContract;
     use lotto_draw_logic::indexer::Indexer;
     use lotto_draw_logic::raffle_manager_contract::{
-        LottoManagerRequestMessage, LottoManagerResponseMessage,
+        merge_requests, ActionFailure, LottoManagerReply, LottoManagerRequestMessage,
+        LottoManagerResponseMessage, QueueView, RaffleManagerStatus,
     };
     use lotto_draw_logic::raffle_registration_contract::{
         RaffleRegistrationContract, RaffleRegistrationStatus, RequestForAction,
     };
     use lotto_draw_logic::types::*;
     use lotto_draw_logic::wasm_contract::WasmContract;
-    use phat_offchain_rollup::clients::ink::Action;
+    use phat_offchain_rollup::clients::ink::{Action, InkRollupClient};
     use pink_extension::chain_extension::signing;
     use pink_extension::{error, info, ResultExt};
     use scale::{Decode, Encode};
@@ -37,6 +39,8 @@ mod lotto_draw_multichain {
         indexer_url: Option<String>,
         /// Key for signing the rollup tx.
         attest_key: [u8; 32],
+        /// Maximum number of requests processed by a rollup transaction
+        batch_size: u8,
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq)]
@@ -58,6 +62,109 @@ mod lotto_draw_multichain {
         UnknownRegistrationStatus,
         MissingRegistrationContract,
         EvmRaffleManagerNotImplemented,
+        IncorrectChainFamily,
+        IncorrectBatchSize,
+        DifferentConfig,
+    }
+
+    /// Outcome of the action for a registration contract
+    #[derive(Encode, Decode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractOutcome {
+        /// id of the registration contract
+        pub contract_id: RegistrationContractId,
+        /// action sent to the registration contract
+        pub action: RequestForAction,
+        /// true if the contract was already synchronized (no transaction is submitted)
+        pub already_synchronized: bool,
+        /// hash of the transaction submitted to the contract
+        pub tx: Option<Vec<u8>>,
+        /// kind of error if the action failed for this contract
+        pub error: Option<ActionFailure>,
+    }
+
+    /// Report returned when the requests of the manager are answered
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AnswerRequestReport {
+        /// number of requests handled and removed from the queue
+        pub nb_requests_handled: u32,
+        /// hash of the transaction submitted to the manager
+        pub manager_tx: Option<Vec<u8>>,
+        /// outcome of the actions for the registration contracts
+        pub contracts: Vec<ContractOutcome>,
+    }
+
+    impl ContractOutcome {
+        fn failed(
+            contract_id: RegistrationContractId,
+            action: RequestForAction,
+            error: &ContractError,
+        ) -> Self {
+            error!("action failed for contract {contract_id}: {error:?}");
+            ContractOutcome {
+                contract_id,
+                action,
+                already_synchronized: false,
+                tx: None,
+                error: Some(Self::failure_kind(error)),
+            }
+        }
+
+        fn failure_kind(error: &ContractError) -> ActionFailure {
+            use RaffleDrawError::*;
+            match error {
+                ContractError::MissingRegistrationContract
+                | ContractError::RaffleDrawError(EvmContractNotConfigured)
+                | ContractError::RaffleDrawError(WasmContractNotConfigured) => {
+                    ActionFailure::MissingConfig
+                }
+                ContractError::FailedToCallRollup
+                | ContractError::RaffleDrawError(FailedToCreateClient)
+                | ContractError::RaffleDrawError(FailedToCommitTx)
+                | ContractError::RaffleDrawError(FailedToCallRollup)
+                | ContractError::RaffleDrawError(HttpRequestFailed) => ActionFailure::RpcError,
+                ContractError::RaffleDrawError(StatusUnknown)
+                | ContractError::RaffleDrawError(DrawNumberUnknown)
+                | ContractError::RaffleDrawError(FailedToDecodeDrawNumber)
+                | ContractError::RaffleDrawError(FailedToDecodeStatus)
+                | ContractError::RaffleDrawError(PausedUnknown)
+                | ContractError::RaffleDrawError(FailedToDecodePaused)
+                | ContractError::RaffleDrawError(NbTicketsUnknown)
+                | ContractError::RaffleDrawError(FailedToDecodeNbTickets)
+                | ContractError::RaffleDrawError(ConfigUnknown)
+                | ContractError::RaffleDrawError(FailedToDecodeConfig)
+                | ContractError::RaffleDrawError(RevenueUnknown)
+                | ContractError::RaffleDrawError(FailedToDecodeRevenue) => {
+                    ActionFailure::InvalidState
+                }
+                _ => ActionFailure::Other,
+            }
+        }
+    }
+
+    /// Action done by the tick
+    #[derive(Encode, Decode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum TickAction {
+        /// the pending request has been answered
+        RequestAnswered(AnswerRequestReport),
+        /// the request to close the registrations has been sent
+        CloseRegistrationsRequested(Option<Vec<u8>>),
+        /// nothing is due
+        NothingToDo,
+    }
+
+    /// Report returned by the tick
+    #[derive(Encode, Decode, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct TickReport {
+        /// status of the manager before the tick
+        pub status: Option<RaffleManagerStatus>,
+        /// draw number of the manager before the tick
+        pub draw_number: Option<DrawNumber>,
+        /// action done by the tick
+        pub action: TickAction,
     }
 
     type Result<T> = core::result::Result<T, ContractError>;
@@ -86,6 +193,7 @@ mod lotto_draw_multichain {
                 raffle_manager: None,
                 raffle_registrations: Mapping::default(),
                 indexer_url: None,
+                batch_size: 1,
             }
         }
 
@@ -212,6 +320,17 @@ mod lotto_draw_multichain {
                     self.raffle_registrations.remove(contract_id);
                 }
                 Some(c) => {
+                    // check the id agrees with the kind of contract (the legacy ids are accepted as is)
+                    let sharded_id = ShardedContractId::from_id(contract_id)?;
+                    let chain_family = match c {
+                        ContractConfig::Wasm(_) => ChainFamily::Substrate,
+                        ContractConfig::Evm(_) => ChainFamily::Evm,
+                    };
+                    if sharded_id.chain_family != ChainFamily::Legacy
+                        && sharded_id.chain_family != chain_family
+                    {
+                        return Err(ContractError::IncorrectChainFamily);
+                    }
                     self.raffle_registrations.insert(contract_id, &c);
                 }
             }
@@ -241,47 +360,252 @@ mod lotto_draw_multichain {
             Ok(())
         }
 
-        /// Processes a request by a rollup transaction
+        /// Gets the maximum number of requests processed by a rollup transaction
         #[ink(message)]
-        pub fn answer_request(&self) -> Result<Vec<(RegistrationContractId, Option<Vec<u8>>)>> {
-            let config = self.ensure_client_configured()?;
+        pub fn get_batch_size(&self) -> u8 {
+            self.batch_size
+        }
+
+        /// Configures the maximum number of requests processed by a rollup transaction (admin only)
+        #[ink(message)]
+        pub fn set_batch_size(&mut self, batch_size: u8) -> Result<()> {
+            self.ensure_owner()?;
+            if batch_size == 0 {
+                return Err(ContractError::IncorrectBatchSize);
+            }
+            self.batch_size = batch_size;
+            Ok(())
+        }
+
+        /// Processes the requests (up to the batch size) by a rollup transaction
+        #[ink(message)]
+        pub fn answer_request(&self) -> Result<AnswerRequestReport> {
+            let (mut client, manager_contract_id, sender_key) = self.connect_manager()?;
+
+            // Get the requests if present
+            let (queue_head, requests) = self.pop_requests(&mut client)?;
+            if requests.is_empty() {
+                return Err(ContractError::NoRequestInQueue);
+            }
 
-            let (mut client, manager_contract_id, sender_key) = match config {
-                ContractConfig::Wasm(config) => (WasmContract::connect(config)?, config.contract_id , config.sender_key),
+            // read the status to include it in the where clause (optimistic locking)
+            let status = lotto_draw_logic::wasm_contract::get_manager_status(&mut client)?;
+            self.inner_answer_requests(
+                client,
+                queue_head,
+                requests,
+                status,
+                manager_contract_id,
+                sender_key,
+            )
+        }
+
+        /// Gets the messages in the queue of the manager not processed yet,
+        /// at most `limit` messages from the given index
+        #[ink(message)]
+        pub fn get_manager_queue(&self, from: QueueIndex, limit: QueueIndex) -> Result<QueueView> {
+            let (mut client, _, _) = self.connect_manager()?;
+            let queue =
+                lotto_draw_logic::wasm_contract::get_manager_queue(&mut client, from, limit)?;
+            Ok(queue)
+        }
+
+        fn connect_manager(&self) -> Result<(InkRollupClient, WasmContractId, Option<[u8; 32]>)> {
+            let config = self.ensure_client_configured()?;
+            match config {
+                ContractConfig::Wasm(config) => Ok((WasmContract::connect(config)?, config.contract_id , config.sender_key)),
                 ContractConfig::Evm(_config) => {
-                    return Err(ContractError::EvmRaffleManagerNotImplemented)
+                    Err(ContractError::EvmRaffleManagerNotImplemented)
                 }
-            };
+            }
+        }
 
-            // Get a request if presents
-            let request: LottoManagerRequestMessage = client
-                .pop()
-                .log_err("answer_request: failed to read queue")?
-                .ok_or(ContractError::NoRequestInQueue)?;
+        /// Read the requests in the queue, up to the batch size
+        /// return the index in the queue of the first request with the requests
+        fn pop_requests(
+            &self,
+            client: &mut InkRollupClient,
+        ) -> Result<(QueueIndex, Vec<LottoManagerRequestMessage>)> {
+            let queue_head = lotto_draw_logic::wasm_contract::get_manager_queue_head(client)?;
+            let mut requests = Vec::new();
+            while requests.len() < self.batch_size as usize {
+                let request: Option<LottoManagerRequestMessage> = client
+                    .pop()
+                    .log_err("answer_request: failed to read queue")?;
+                match request {
+                    Some(request) => requests.push(request),
+                    None => break,
+                }
+            }
+            Ok((queue_head, requests))
+        }
 
-            ink::env::debug_println!("Received request: {request:02x?}");
+        /// Encode the response with the index of the request answered
+        fn encode_reply(
+            request_index: Option<QueueIndex>,
+            response: LottoManagerResponseMessage,
+        ) -> Vec<u8> {
+            LottoManagerReply {
+                request_index,
+                response,
+            }
+            .encode()
+        }
 
-            // read the status and the draw number to include them in the where clause (optimistic locking)
-            /*
-            let status = lotto_draw_logic::wasm_contract::get_manager_status(&mut client)?;
-            let draw_number = lotto_draw_logic::wasm_contract::get_manager_draw_number(&mut client)?;
+        /// Answer the requests popped by the given client, all replies are sent in the same transaction.
+        /// The manager status must be read with the same client to be included in the where clause
+        fn inner_answer_requests(
+            &self,
+            mut client: InkRollupClient,
+            queue_head: QueueIndex,
+            requests: Vec<LottoManagerRequestMessage>,
+            status: Option<RaffleManagerStatus>,
+            manager_contract_id: WasmContractId,
+            sender_key: Option<[u8; 32]>,
+        ) -> Result<AnswerRequestReport> {
             ink::env::debug_println!("manager status : {status:?}");
+
+            // read the draw number to include it in the where clause (optimistic locking)
+            // the status has already been read with the same client, so the replies are applied
+            // only if the manager is still in the same phase for the same draw
+            let draw_number = lotto_draw_logic::wasm_contract::get_manager_draw_number(&mut client)?;
             ink::env::debug_println!("manager draw_number : {draw_number:?}");
-             */
 
-            let (r, mut txs) = self.handle_request(request, manager_contract_id)?;
-            if let Some(response) = r {
-                let encoded_response = response.encode();
+            let nb_read_requests = requests.len();
+            // number of requests read in the queue and answered (or skipped)
+            let mut nb_answered_requests = 0;
+            let mut responses = Vec::new();
+            let mut report = AnswerRequestReport::default();
+
+            for (request, nb_requests) in merge_requests(requests) {
+                ink::env::debug_println!("Received request: {request:02x?}");
+
+                // skip the requests still in the queue when the draw was cancelled or the lotto was closed
+                if let Some(ref status) = status {
+                    if request.is_obsolete(status) {
+                        ink::env::debug_println!("Obsolete request: {request:02x?}");
+                        nb_answered_requests += nb_requests as usize;
+                        continue;
+                    }
+                }
+
+                let (r, outcomes) = self.handle_request(request, manager_contract_id)?;
+                report.contracts.extend(outcomes);
+                match r {
+                    Some(response) => {
+                        nb_answered_requests += nb_requests as usize;
+                        // the response references the last request merged in this one
+                        let request_index = queue_head + nb_answered_requests as QueueIndex - 1;
+                        responses.push((request_index, response));
+                    }
+                    // the contracts are not synchronized yet,
+                    // this request and the next ones will be answered later
+                    None => break,
+                }
+            }
+
+            // the errors are reported to the manager so that it can decide to retry or quarantine the contracts.
+            // A failure is reported only when its kind changes to not submit a transaction at each tick
+            let mut failures: Vec<(RegistrationContractId, ActionFailure)> = Vec::new();
+            for outcome in &report.contracts {
+                let Some(error) = outcome.error else {
+                    continue;
+                };
+                let last_failure = lotto_draw_logic::wasm_contract::get_manager_last_failure(
+                    &mut client,
+                    outcome.contract_id,
+                )?;
+                if last_failure != Some(error) {
+                    failures.push((outcome.contract_id, error));
+                }
+            }
+
+            if nb_answered_requests == 0 && failures.is_empty() {
+                return Ok(report);
+            }
+
+            if nb_answered_requests < nb_read_requests {
+                // only the answered requests must be removed from the queue => use a new client.
+                // If no request is answered, only the errors are reported and the requests stay in the queue
+                let (mut new_client, _, _) = self.connect_manager()?;
+                for _ in 0..nb_answered_requests {
+                    let _: Option<LottoManagerRequestMessage> = new_client
+                        .pop()
+                        .log_err("answer_request: failed to read queue")?;
+                }
+                // read again the status and the draw number to include them in the where clause
+                let new_status = lotto_draw_logic::wasm_contract::get_manager_status(&mut new_client)?;
+                let new_draw_number =
+                    lotto_draw_logic::wasm_contract::get_manager_draw_number(&mut new_client)?;
+                if new_status != status || new_draw_number != draw_number {
+                    // the manager has been updated in the meantime, the requests will be answered later
+                    return Ok(report);
+                }
+                client = new_client;
+            }
+
+            for (request_index, response) in responses {
+                let encoded_response = Self::encode_reply(Some(request_index), response);
                 ink::env::debug_println!("Manager encoded response: {encoded_response:02x?}");
                 // Attach an action to the tx by:
-                client.action(Action::Reply(response.encode()));
-                let tx = WasmContract::maybe_submit_tx(client, &self.attest_key, sender_key.as_ref())?;
-                ink::env::debug_println!("tx: {tx:02x?}");
-                txs.push((0, tx));
-            } else {
-                txs.push((0, None));
+                client.action(Action::Reply(encoded_response));
+            }
+            if !failures.is_empty() {
+                ink::env::debug_println!("Report the errors: {failures:?}");
+                let response = LottoManagerResponseMessage::ActionFailed(failures);
+                client.action(Action::Reply(Self::encode_reply(None, response)));
             }
-            Ok(txs)
+            // the queue head is moved even if all requests are obsolete
+            let tx = WasmContract::maybe_submit_tx(client, &self.attest_key, sender_key.as_ref())?;
+            ink::env::debug_println!("tx: {tx:02x?}");
+            report.nb_requests_handled = nb_answered_requests as u32;
+            report.manager_tx = tx;
+            Ok(report)
+        }
+
+        /// Inspect the queue and the status of the manager and do whatever is due:
+        /// answer the pending requests, request to close the registrations or nothing
+        #[ink(message)]
+        pub fn tick(&self) -> Result<TickReport> {
+            let (mut client, manager_contract_id, sender_key) = self.connect_manager()?;
+
+            let status = lotto_draw_logic::wasm_contract::get_manager_status(&mut client)?;
+            let draw_number = lotto_draw_logic::wasm_contract::get_manager_draw_number(&mut client)?;
+            ink::env::debug_println!("manager status : {status:?} - draw_number : {draw_number:?}");
+
+            // answer the pending requests
+            let (queue_head, requests) = self.pop_requests(&mut client)?;
+            if !requests.is_empty() {
+                let report = self.inner_answer_requests(
+                    client,
+                    queue_head,
+                    requests,
+                    status.clone(),
+                    manager_contract_id,
+                    sender_key,
+                )?;
+                return Ok(TickReport {
+                    status,
+                    draw_number,
+                    action: TickAction::RequestAnswered(report),
+                });
+            }
+
+            // no request in the queue, close the registrations if it is due
+            if self.is_closing_due(&mut client, &status)? {
+                let tx = self.send_close_registrations()?;
+                return Ok(TickReport {
+                    status,
+                    draw_number,
+                    action: TickAction::CloseRegistrationsRequested(tx),
+                });
+            }
+
+            Ok(TickReport {
+                status,
+                draw_number,
+                action: TickAction::NothingToDo,
+            })
         }
 
         fn hash_input<T: scale::Encode>(
@@ -299,13 +623,14 @@ mod lotto_draw_multichain {
             &self,
             message: LottoManagerRequestMessage,
             manager_contract_id: WasmContractId
-        ) -> Result<(Option<LottoManagerResponseMessage>, Vec<(RegistrationContractId, Option<Vec<u8>>)>)> {
+        ) -> Result<(Option<LottoManagerResponseMessage>, Vec<ContractOutcome>)> {
             let response = match message {
-                LottoManagerRequestMessage::PropagateConfig(config, ref contract_ids) => {
-                    let (synchronized_contracts, txs) = self.inner_do_action(
-                        RequestForAction::SetConfigAndStart(config.clone(), 0),
+                LottoManagerRequestMessage::PropagateConfig(config, revenue_split, ticket_price, ref contract_ids) => {
+                    let outcomes = self.inner_do_action(
+                        RequestForAction::SetConfigAndStart(config.clone(), 0, revenue_split, ticket_price),
                         contract_ids,
-                    )?;
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
                     let response = if synchronized_contracts.is_empty(){
                         None
                     } else {
@@ -313,13 +638,14 @@ mod lotto_draw_multichain {
                         let hash = Self::hash_input(&config);
                         Some(LottoManagerResponseMessage::ConfigPropagated(synchronized_contracts, hash))
                     };
-                    (response, txs)
+                    (response, outcomes)
                 }
                 LottoManagerRequestMessage::OpenRegistrations(draw_number, ref contract_ids) => {
-                    let (synchronized_contracts, txs) = self.inner_do_action(
+                    let outcomes = self.inner_do_action(
                         RequestForAction::OpenRegistrations(draw_number),
                         contract_ids,
-                    )?;
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
                     let response = if synchronized_contracts.is_empty(){
                         None
                     } else {
@@ -328,28 +654,52 @@ mod lotto_draw_multichain {
                             synchronized_contracts,
                         ))
                     };
-                    (response, txs)
+                    (response, outcomes)
                 }
                 LottoManagerRequestMessage::CloseRegistrations(draw_number, ref contract_ids) => {
-                    let  (synchronized_contracts, txs) = self.inner_do_action(
+                    let mut outcomes = self.inner_do_action(
                         RequestForAction::CloseRegistrations(draw_number),
                         contract_ids,
-                    )?;
-                    let response = if synchronized_contracts.is_empty(){
+                    );
+                    // the registrations are closed, report the number of tickets recorded and the revenue
+                    let mut contracts_tickets = Vec::new();
+                    for outcome in outcomes.iter_mut().filter(|outcome| outcome.already_synchronized) {
+                        let tickets = self
+                            .get_registration_contract(&outcome.contract_id)
+                            .and_then(|contract| {
+                                let nb_tickets = contract.get_nb_tickets()?;
+                                let revenue = contract.get_revenue()?;
+                                Ok((nb_tickets, revenue))
+                            });
+                        match tickets {
+                            Ok((nb_tickets, revenue)) => {
+                                contracts_tickets.push((outcome.contract_id, nb_tickets, revenue))
+                            }
+                            Err(e) => {
+                                *outcome = ContractOutcome::failed(
+                                    outcome.contract_id,
+                                    outcome.action.clone(),
+                                    &e,
+                                )
+                            }
+                        }
+                    }
+                    let response = if contracts_tickets.is_empty(){
                         None
                     } else {
                         Some(LottoManagerResponseMessage::RegistrationsClosed(
                             draw_number,
-                            synchronized_contracts,
+                            contracts_tickets,
                         ))
                     };
-                    (response, txs)
+                    (response, outcomes)
                 }
                 LottoManagerRequestMessage::GenerateSalt(draw_number, ref contract_ids) => {
-                    let  (synchronized_contracts, txs) = self.inner_do_action(
+                    let outcomes = self.inner_do_action(
                         RequestForAction::GenerateSalt(draw_number),
                         contract_ids,
-                    )?;
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
                     let response = if synchronized_contracts.is_empty(){
                         None
                     } else {
@@ -371,7 +721,7 @@ mod lotto_draw_multichain {
                             ))
                         }
                     };
-                    (response, txs)
+                    (response, outcomes)
                 }
                 LottoManagerRequestMessage::DrawNumbers(draw_number, ref config, ref salt) => {
                     let numbers = self.inner_get_numbers(
@@ -391,7 +741,7 @@ mod lotto_draw_multichain {
                     let winners = indexer.query_winners(draw_number, numbers)?;
                     // encode and hash the input for verification by the manager
                     let hash = Self::hash_input(numbers);
-                    (Some(LottoManagerResponseMessage::Winners(draw_number, winners.0, winners.1, hash)), Vec::new())
+                    (Some(LottoManagerResponseMessage::Winners(draw_number, winners, hash)), Vec::new())
                 }
                 LottoManagerRequestMessage::PropagateResults(
                     draw_number,
@@ -399,14 +749,15 @@ mod lotto_draw_multichain {
                     has_winner,
                     ref contract_ids,
                 ) => {
-                    let  (synchronized_contracts, txs) = self.inner_do_action(
+                    let outcomes = self.inner_do_action(
                         RequestForAction::SetResults(
                             draw_number,
                             numbers.to_vec(),
                             has_winner,
                         ),
                         contract_ids,
-                    )?;
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
                     let response = if synchronized_contracts.is_empty(){
                         None
                     } else {
@@ -418,7 +769,64 @@ mod lotto_draw_multichain {
                             hash,
                         ))
                     };
-                    (response, txs)
+                    (response, outcomes)
+                }
+                LottoManagerRequestMessage::Stop(draw_number, ref contract_ids) => {
+                    let outcomes = self.inner_do_action(
+                        RequestForAction::Stop(draw_number),
+                        contract_ids,
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
+                    let response = if synchronized_contracts.is_empty(){
+                        None
+                    } else {
+                        Some(LottoManagerResponseMessage::Stopped(
+                            draw_number,
+                            synchronized_contracts,
+                        ))
+                    };
+                    (response, outcomes)
+                }
+                LottoManagerRequestMessage::SetPaused(paused, ref contract_ids) => {
+                    let outcomes = self.inner_set_paused(paused, contract_ids);
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
+                    let response = if synchronized_contracts.is_empty(){
+                        None
+                    } else {
+                        Some(LottoManagerResponseMessage::PausePropagated(
+                            paused,
+                            synchronized_contracts,
+                        ))
+                    };
+                    (response, outcomes)
+                }
+                LottoManagerRequestMessage::UpdateConfig(config, ref contract_ids) => {
+                    let outcomes = self.inner_update_config(&config, contract_ids);
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
+                    let response = if synchronized_contracts.is_empty(){
+                        None
+                    } else {
+                        // encode and hash the input for verification by the manager
+                        let hash = Self::hash_input(&config);
+                        Some(LottoManagerResponseMessage::ConfigUpdated(synchronized_contracts, hash))
+                    };
+                    (response, outcomes)
+                }
+                LottoManagerRequestMessage::CancelDraw(draw_number, ref contract_ids) => {
+                    let outcomes = self.inner_do_action(
+                        RequestForAction::CancelDraw(draw_number),
+                        contract_ids,
+                    );
+                    let synchronized_contracts = Self::synchronized_contracts(&outcomes);
+                    let response = if synchronized_contracts.is_empty(){
+                        None
+                    } else {
+                        Some(LottoManagerResponseMessage::DrawCancelled(
+                            draw_number,
+                            synchronized_contracts,
+                        ))
+                    };
+                    (response, outcomes)
                 }
             };
 
@@ -429,13 +837,12 @@ mod lotto_draw_multichain {
             &self,
             request: RequestForAction,
             contract_ids: &[RegistrationContractId],
-        ) -> Result<(Vec<RegistrationContractId>, Vec<(RegistrationContractId, Option<Vec<u8>>)>)> {
-            let mut synchronized_contracts = Vec::new();
-            let mut txs = Vec::new();
+        ) -> Vec<ContractOutcome> {
+            let mut outcomes = Vec::new();
 
             // get the status and draw number matching with this action
             let (target_draw_number, target_status) = match request {
-                RequestForAction::SetConfigAndStart(_, _) => {
+                RequestForAction::SetConfigAndStart(_, _, _, _) => {
                     (None, Some(RaffleRegistrationStatus::Started))
                 }
                 RequestForAction::OpenRegistrations(draw_number) => (
@@ -454,48 +861,146 @@ mod lotto_draw_multichain {
                     Some(draw_number),
                     Some(RaffleRegistrationStatus::ResultsReceived),
                 ),
+                RequestForAction::Stop(draw_number) => (
+                    Some(draw_number),
+                    Some(RaffleRegistrationStatus::Closed),
+                ),
+                RequestForAction::CancelDraw(draw_number) => (
+                    Some(draw_number),
+                    Some(RaffleRegistrationStatus::DrawCancelled),
+                ),
+                // these actions are not linked to the status, they are done by dedicated methods
+                RequestForAction::SetPaused(_) | RequestForAction::UpdateConfig(_) => (None, None),
             };
 
-            // iterate on contract_ids
+            // iterate on contract_ids, an error on a contract must not prevent to synchronize the other ones
             for contract_id in contract_ids {
-                // get the config linked to this contract
-                let contract_config = self
-                    .raffle_registrations
-                    .get(contract_id)
-                    .ok_or(ContractError::MissingRegistrationContract)?;
-                // build the object to reach this contract
-                let contract: Box<dyn RaffleRegistrationContract> = match contract_config {
-                    ContractConfig::Wasm(config) => {
-                        WasmContract::new(Some(config)).map(Box::new)?
-                    }
-                    ContractConfig::Evm(config) => EvmContract::new(Some(config)).map(Box::new)?,
-                };
                 // for the action SetConfigAndStart, we have to override the registration contract id
                 let request = match &request {
-                    RequestForAction::SetConfigAndStart(config, _) => {
-                        &RequestForAction::SetConfigAndStart(config.clone(), *contract_id)
+                    RequestForAction::SetConfigAndStart(config, _, revenue_split, ticket_price) => {
+                        RequestForAction::SetConfigAndStart(
+                            config.clone(),
+                            *contract_id,
+                            revenue_split.clone(),
+                            *ticket_price,
+                        )
                     }
-                    _ => &request,
+                    _ => request.clone(),
                 };
 
+                // build the object to reach this contract and
                 // check the status and draw number and do the action is the contract is not synchronized
-                let (sync, tx) = contract.do_action(
-                    target_draw_number,
-                    target_status,
-                    request.clone(),
-                    &self.attest_key,
-                )?;
-                if sync {
-                    // the contract is synchronized
-                    synchronized_contracts.push(*contract_id);
-                }
-                txs.push((*contract_id, tx));
+                let result = self.get_registration_contract(contract_id).and_then(|contract| {
+                    contract
+                        .do_action(
+                            target_draw_number,
+                            target_status,
+                            request.clone(),
+                            &self.attest_key,
+                        )
+                        .map_err(Into::into)
+                });
+                outcomes.push(Self::to_outcome(*contract_id, request, result));
+            }
+            outcomes
+        }
 
+        fn inner_set_paused(
+            &self,
+            paused: bool,
+            contract_ids: &[RegistrationContractId],
+        ) -> Vec<ContractOutcome> {
+            let mut outcomes = Vec::new();
+
+            // iterate on contract_ids, an error on a contract must not prevent to synchronize the other ones
+            for contract_id in contract_ids {
+                // build the object to reach this contract and
+                // check the flag and update it if the contract is not synchronized
+                let result = self
+                    .get_registration_contract(contract_id)
+                    .and_then(|contract| {
+                        contract.set_paused(paused, &self.attest_key).map_err(Into::into)
+                    });
+                outcomes.push(Self::to_outcome(
+                    *contract_id,
+                    RequestForAction::SetPaused(paused),
+                    result,
+                ));
             }
-            // return the list of synchronized contracts
-            Ok((synchronized_contracts, txs))
+            outcomes
+        }
+
+        fn inner_update_config(
+            &self,
+            config: &RaffleConfig,
+            contract_ids: &[RegistrationContractId],
+        ) -> Vec<ContractOutcome> {
+            let mut outcomes = Vec::new();
+
+            // iterate on contract_ids, an error on a contract must not prevent to synchronize the other ones
+            for contract_id in contract_ids {
+                // build the object to reach this contract and
+                // check the config and update it if the contract is not synchronized
+                let result = self
+                    .get_registration_contract(contract_id)
+                    .and_then(|contract| {
+                        contract
+                            .update_config(config.clone(), &self.attest_key)
+                            .map_err(Into::into)
+                    });
+                outcomes.push(Self::to_outcome(
+                    *contract_id,
+                    RequestForAction::UpdateConfig(config.clone()),
+                    result,
+                ));
+            }
+            outcomes
+        }
+
+        fn to_outcome(
+            contract_id: RegistrationContractId,
+            action: RequestForAction,
+            result: Result<(bool, Option<Vec<u8>>)>,
+        ) -> ContractOutcome {
+            match result {
+                Ok((already_synchronized, tx)) => ContractOutcome {
+                    contract_id,
+                    action,
+                    already_synchronized,
+                    tx,
+                    error: None,
+                },
+                Err(e) => ContractOutcome::failed(contract_id, action, &e),
+            }
+        }
+
+        /// return the list of synchronized contracts
+        fn synchronized_contracts(outcomes: &[ContractOutcome]) -> Vec<RegistrationContractId> {
+            outcomes
+                .iter()
+                .filter(|outcome| outcome.already_synchronized)
+                .map(|outcome| outcome.contract_id)
+                .collect()
         }
 
+        fn get_registration_contract(
+            &self,
+            contract_id: &RegistrationContractId,
+        ) -> Result<Box<dyn RaffleRegistrationContract>> {
+            // get the config linked to this contract
+            let contract_config = self
+                .raffle_registrations
+                .get(contract_id)
+                .ok_or(ContractError::MissingRegistrationContract)?;
+            // build the object to reach this contract
+            let contract: Box<dyn RaffleRegistrationContract> = match contract_config {
+                ContractConfig::Wasm(config) => {
+                    WasmContract::new(Some(config)).map(Box::new)?
+                }
+                ContractConfig::Evm(config) => EvmContract::new(Some(config)).map(Box::new)?,
+            };
+            Ok(contract)
+        }
 
         /// Send a request to Manager to close the registrations
         #[ink(message)]
@@ -508,15 +1013,100 @@ mod lotto_draw_multichain {
                 }
             };
             // send the request to the manager
-            client.action(Action::Reply(LottoManagerResponseMessage::CloseRegistrations().encode()));
+            client.action(Action::Reply(Self::encode_reply(
+                None,
+                LottoManagerResponseMessage::CloseRegistrations(),
+            )));
 
             let tx = WasmContract::maybe_submit_tx(client, &self.attest_key, sender_key.as_ref())?;
             ink::env::debug_println!("tx: {tx:02x?}");
             Ok(tx)
         }
 
-        /// Verify if the winning numbers for a raffle are valid (only for past raffles)
-        ///
+        /// Send a request to Manager to close the registrations only if the closing is allowed:
+        /// the registrations are open in all contracts and the block number and the timestamp are reached.
+        /// It can be called often (ie by a cron job) without wasting transactions.
+        #[ink(message)]
+        pub fn close_registrations_if_due(&self) -> Result<Option<Vec<u8>>> {
+            let config = self.ensure_client_configured()?;
+            let mut client = match config {
+                ContractConfig::Wasm(config) => WasmContract::connect(config)?,
+                ContractConfig::Evm(_config) => {
+                    return Err(ContractError::EvmRaffleManagerNotImplemented)
+                }
+            };
+
+            // the registrations are not open in all contracts while a request is in the queue
+            if client.pop().log_err("close registrations: failed to read queue")?.is_some() {
+                ink::env::debug_println!("request in the queue");
+                return Ok(None);
+            }
+
+            let status = lotto_draw_logic::wasm_contract::get_manager_status(&mut client)?;
+            if !self.is_closing_due(&mut client, &status)? {
+                return Ok(None);
+            }
+
+            // the queue has been read, use a new client to send the request
+            self.send_close_registrations()
+        }
+
+        /// Return true if the registrations can be closed, the queue must be empty
+        fn is_closing_due(
+            &self,
+            client: &mut InkRollupClient,
+            status: &Option<RaffleManagerStatus>,
+        ) -> Result<bool> {
+            // check the status of the manager
+            ink::env::debug_println!("manager status : {status:?}");
+            if !matches!(status, Some(RaffleManagerStatus::RegistrationsOpen)) {
+                return Ok(false);
+            }
+
+            // check the block number and the timestamp
+            let Some((next_block_number, next_timestamp)) =
+                lotto_draw_logic::wasm_contract::get_manager_next_closing_registrations(client)?
+            else {
+                return Ok(false);
+            };
+            let rpc = match self.ensure_client_configured()? {
+                ContractConfig::Wasm(config) => &config.rpc,
+                ContractConfig::Evm(_config) => {
+                    return Err(ContractError::EvmRaffleManagerNotImplemented)
+                }
+            };
+            let block_number = lotto_draw_logic::wasm_contract::get_current_block_number(rpc)?;
+            let timestamp = self.env().block_timestamp();
+            ink::env::debug_println!("block number : {block_number} / {next_block_number}");
+            ink::env::debug_println!("timestamp : {timestamp} / {next_timestamp}");
+
+            // when a schedule is set, only the timestamp is checked
+            if next_timestamp > 0 {
+                return Ok(timestamp >= next_timestamp);
+            }
+            Ok(block_number >= next_block_number)
+        }
+
+        fn send_close_registrations(&self) -> Result<Option<Vec<u8>>> {
+            let config = self.ensure_client_configured()?;
+            let (mut client, sender_key) = match config {
+                ContractConfig::Wasm(config) => (WasmContract::connect(config)?, config.sender_key),
+                ContractConfig::Evm(_config) => {
+                    return Err(ContractError::EvmRaffleManagerNotImplemented)
+                }
+            };
+            client.action(Action::Reply(Self::encode_reply(
+                None,
+                LottoManagerResponseMessage::CloseRegistrations(),
+            )));
+
+            let tx = WasmContract::maybe_submit_tx(client, &self.attest_key, sender_key.as_ref())?;
+            ink::env::debug_println!("tx: {tx:02x?}");
+            Ok(tx)
+        }
+
+        /// Verify if the winning numbers for a raffle are valid (only for past raffles).
+        /// The given config must be the one used for this raffle.
         #[ink(message)]
         pub fn verify_numbers(
             &self,
@@ -549,6 +1139,18 @@ mod lotto_draw_multichain {
                return Err(ContractError::UnauthorizedRaffle);
            }
 
+            // the config used for this draw must match the one given in parameter
+            let draw_config =
+                lotto_draw_logic::wasm_contract::get_manager_draw_config(&mut client, draw_number)?;
+            if let Some(draw_config) = draw_config {
+                if draw_config.nb_numbers != nb_numbers
+                    || draw_config.min_number != smallest_number
+                    || draw_config.max_number != biggest_number
+                {
+                    return Err(ContractError::DifferentConfig);
+                }
+            }
+
             let draw = Draw::new(nb_numbers, smallest_number, biggest_number)?;
             let result = draw.verify_numbers(contract_id, draw_number, salt, numbers)?;
             Ok(result)
@@ -638,14 +1240,14 @@ mod lotto_draw_multichain {
                     .expect("hex decode failed")
                     .try_into()
                     .expect("incorrect length");
-            let lotto_wasm_id: RegistrationContractId = get_env("LOTTO_WASM_ID").unwrap().parse().expect("u8 expected");
+            let lotto_wasm_id: RegistrationContractId = get_env("LOTTO_WASM_ID").unwrap().parse().expect("u128 expected");
             let lotto_wasm_contract_id: WasmContractId =
                 hex::decode(get_env("LOTTO_WASM_CONTRACT_ID").unwrap())
                     .expect("hex decode failed")
                     .try_into()
                     .expect("incorrect length");
 
-            let lotto_evm_1_id: Option<RegistrationContractId> = get_env("LOTTO_EVM_1_ID").map(|s| s.parse().expect("u8 expected"));
+            let lotto_evm_1_id: Option<RegistrationContractId> = get_env("LOTTO_EVM_1_ID").map(|s| s.parse().expect("u128 expected"));
             let lotto_evm_1_rpc = get_env("LOTTO_EVM_1_RPC");
             let lotto_evm_1_contract_id: Option<EvmContractId> = get_env("LOTTO_EVM_1_CONTRACT_ID")
                 .map(|s| hex::decode(s).expect("hex decode failed")
@@ -653,7 +1255,7 @@ mod lotto_draw_multichain {
                 .expect("incorrect length")
             );
 
-            let lotto_evm_2_id: Option<RegistrationContractId> = get_env("LOTTO_EVM_2_ID").map(|s| s.parse().expect("u8 expected"));
+            let lotto_evm_2_id: Option<RegistrationContractId> = get_env("LOTTO_EVM_2_ID").map(|s| s.parse().expect("u128 expected"));
             let lotto_evm_2_rpc = get_env("LOTTO_EVM_2_RPC");
             let lotto_evm_2_contract_id: Option<EvmContractId> = get_env("LOTTO_EVM_2_CONTRACT_ID")
                 .map(|s| hex::decode(s).expect("hex decode failed")
@@ -791,9 +1393,10 @@ mod lotto_draw_multichain {
                     .expect("Incorrect Length")),
             };
 
+            let registration_contract_id_10 = ShardedContractId::new(ChainFamily::Substrate, 2000, 10).to_id();
             lotto
                 .set_config_raffle_registrations(
-                    10,
+                    registration_contract_id_10,
                     Some(ContractConfig::Wasm(registration_contract_config_10.clone())),
                 )
                 .unwrap();
@@ -811,9 +1414,10 @@ mod lotto_draw_multichain {
                     .expect("Incorrect Length")),
             };
 
+            let registration_contract_id_11 = ShardedContractId::new(ChainFamily::Evm, 81, 11).to_id();
             lotto
                 .set_config_raffle_registrations(
-                    11,
+                    registration_contract_id_11,
                     Some(ContractConfig::Evm(registration_contract_config_11.clone())),
                 )
                 .unwrap();
@@ -828,7 +1432,7 @@ mod lotto_draw_multichain {
                 assert!(false);
             }
 
-            if let Some(ContractConfig::Wasm(config)) = lotto.get_config_raffle_registrations(10) {
+            if let Some(ContractConfig::Wasm(config)) = lotto.get_config_raffle_registrations(registration_contract_id_10) {
                 assert_eq!(config.rpc, registration_contract_config_10.rpc);
                 assert_eq!(config.pallet_id, registration_contract_config_10.pallet_id);
                 assert_eq!(config.call_id, registration_contract_config_10.call_id);
@@ -838,7 +1442,7 @@ mod lotto_draw_multichain {
                 assert!(false);
             }
 
-            if let Some(ContractConfig::Evm(config)) = lotto.get_config_raffle_registrations(11) {
+            if let Some(ContractConfig::Evm(config)) = lotto.get_config_raffle_registrations(registration_contract_id_11) {
                 assert_eq!(config.rpc, registration_contract_config_11.rpc);
                 assert_eq!(config.contract_id, registration_contract_config_11.contract_id);
                 assert_ne!(config.sender_key, registration_contract_config_11.sender_key);
@@ -846,7 +1450,67 @@ mod lotto_draw_multichain {
                 assert!(false);
             }
 
+            // the id must agree with the kind of contract
+            assert_eq!(
+                lotto.set_config_raffle_registrations(
+                    ShardedContractId::new(ChainFamily::Evm, 81, 12).to_id(),
+                    Some(ContractConfig::Wasm(registration_contract_config_10.clone())),
+                ),
+                Err(ContractError::IncorrectChainFamily)
+            );
+            assert_eq!(
+                lotto.set_config_raffle_registrations(
+                    0x0100_0100_0000_0000_0000_0000_0000_0012,
+                    Some(ContractConfig::Evm(registration_contract_config_11.clone())),
+                ),
+                Err(ContractError::RaffleDrawError(RaffleDrawError::InvalidRegistrationContractId))
+            );
 
+            // the legacy ids are accepted for any kind of contract
+            lotto
+                .set_config_raffle_registrations(
+                    12,
+                    Some(ContractConfig::Evm(registration_contract_config_11.clone())),
+                )
+                .unwrap();
+            lotto
+                .set_config_raffle_registrations(
+                    13,
+                    Some(ContractConfig::Wasm(registration_contract_config_10.clone())),
+                )
+                .unwrap();
+        }
+
+        #[ink::test]
+        fn test_batch_size() {
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let mut lotto = Lotto::default();
+            assert_eq!(lotto.get_batch_size(), 1);
+
+            lotto.set_batch_size(5).unwrap();
+            assert_eq!(lotto.get_batch_size(), 5);
+
+            assert_eq!(lotto.set_batch_size(0), Err(ContractError::IncorrectBatchSize));
+            assert_eq!(lotto.get_batch_size(), 5);
+        }
+
+        #[ink::test]
+        fn test_fault_isolation() {
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = Lotto::default();
+
+            // the contracts are not configured: all contracts are tried and the errors are reported
+            let outcomes = lotto.inner_set_paused(true, &[11, 10]);
+            assert_eq!(outcomes.len(), 2);
+            assert_eq!(outcomes[0].contract_id, 11);
+            assert_eq!(outcomes[0].action, RequestForAction::SetPaused(true));
+            assert!(!outcomes[0].already_synchronized);
+            assert_eq!(outcomes[0].error, Some(ActionFailure::MissingConfig));
+            assert_eq!(outcomes[1].contract_id, 10);
+            assert_eq!(outcomes[1].error, Some(ActionFailure::MissingConfig));
+            assert!(Lotto::synchronized_contracts(&outcomes).is_empty());
         }
 
         #[ink::test]
@@ -866,5 +1530,45 @@ mod lotto_draw_multichain {
             ink::env::debug_println!("answer request: {r:?}");
         }
 
+        #[ink::test]
+        #[ignore = "The target contract must be deployed on the Substrate node"]
+        fn get_manager_queue() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+
+            let r = lotto
+                .get_manager_queue(0, lotto_draw_logic::wasm_contract::MAX_QUEUE_PAGE)
+                .expect("failed to read the queue");
+            ink::env::debug_println!("queue: {r:?}");
+        }
+
+        #[ink::test]
+        #[ignore = "The target contract must be deployed on the Substrate node"]
+        fn tick() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+
+            let r = lotto.tick().expect("failed to tick");
+            ink::env::debug_println!("tick: {r:?}");
+        }
+
+        #[ink::test]
+        #[ignore = "The target contract must be deployed on the Substrate node and the registrations must be open"]
+        fn close_registrations_if_due() {
+            let _ = env_logger::try_init();
+            pink_extension_runtime::mock_ext::mock_all_ext();
+
+            let lotto = init_contract();
+
+            let r = lotto
+                .close_registrations_if_due()
+                .expect("failed to close the registrations");
+            ink::env::debug_println!("close registrations: {r:?}");
+        }
+
     }
 }
@@ -28,6 +28,24 @@ pub enum RaffleDrawError {
     AddOverFlow,
     SubOverFlow,
     DivByZero,
+    // error when parsing the accounts
+    UnsupportedAddress,
+    FailedToDecodeAddress,
+    InvalidRegistrationContractId,
+    PausedUnknown,
+    FailedToDecodePaused,
+    NbTicketsUnknown,
+    FailedToDecodeNbTickets,
+    NextClosingRegistrationsUnknown,
+    BlockNumberUnknown,
+    QueueHeadUnknown,
+    QueueTailUnknown,
+    ConfigUnknown,
+    FailedToDecodeConfig,
+    DrawConfigUnknown,
+    RevenueUnknown,
+    FailedToDecodeRevenue,
+    LastFailureUnknown,
 }
 
 impl From<phat_offchain_rollup::Error> for RaffleDrawError {
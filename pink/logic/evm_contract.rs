@@ -78,6 +78,66 @@ impl RaffleRegistrationContract for EvmContract {
 
         Ok((false, tx))
     }
+
+    fn set_paused(
+        &self,
+        paused: bool,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError> {
+        // connect to the contract
+        let mut client = self.connect()?;
+
+        let current_paused = get_paused(&mut client)?.unwrap_or_default();
+        if current_paused == paused {
+            // the contract is already synchronized
+            return Ok((true, None));
+        }
+
+        // synchronize the contract =>  Attach an action to the tx
+        let action = encode_request(&RequestForAction::SetPaused(paused))?;
+        client.action(Action::Reply(action));
+        // submit the transaction
+        let tx = maybe_submit_tx(client, attest_key, self.config.sender_key.as_ref())?;
+
+        Ok((false, tx))
+    }
+
+    fn update_config(
+        &self,
+        config: RaffleConfig,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError> {
+        // connect to the contract
+        let mut client = self.connect()?;
+
+        let current_config = get_config(&mut client)?;
+        if current_config.as_ref() == Some(&config) {
+            // the contract is already synchronized
+            return Ok((true, None));
+        }
+
+        // synchronize the contract =>  Attach an action to the tx
+        let action = encode_request(&RequestForAction::UpdateConfig(config))?;
+        client.action(Action::Reply(action));
+        // submit the transaction
+        let tx = maybe_submit_tx(client, attest_key, self.config.sender_key.as_ref())?;
+
+        Ok((false, tx))
+    }
+
+    fn get_nb_tickets(&self) -> Result<u32, RaffleDrawError> {
+        // connect to the contract
+        let mut client = self.connect()?;
+        let nb_tickets = get_nb_tickets(&mut client)?.unwrap_or_default();
+        Ok(nb_tickets)
+    }
+
+    fn get_revenue(&self) -> Result<Balance, RaffleDrawError> {
+        // connect to the contract
+        let mut client = self.connect()?;
+        let revenue = get_revenue(&mut client)?.unwrap_or_default();
+        Ok(revenue)
+    }
 }
 
 fn encode_request(request: &RequestForAction) -> Result<Vec<u8>, RaffleDrawError> {
@@ -88,18 +148,29 @@ fn encode_request(request: &RequestForAction) -> Result<Vec<u8>, RaffleDrawError
     const REQUEST_CLOSE_REGISTRATIONS: u8 = 2;
     const REQUEST_GENERATE_SALT: u8 = 3;
     const REQUEST_SET_RESULTS: u8 = 4;
+    const REQUEST_STOP: u8 = 5;
+    const REQUEST_CANCEL_DRAW: u8 = 6;
+    const REQUEST_SET_PAUSED: u8 = 7;
+    const REQUEST_UPDATE_CONFIG: u8 = 8;
 
     let encoded = match &request {
-        RequestForAction::SetConfigAndStart(config, contract_id) => {
+        RequestForAction::SetConfigAndStart(config, contract_id, revenue_split, ticket_price) => {
             let nb_numbers = config.nb_numbers as u128;
             let min_number = config.min_number as u128;
             let max_number = config.max_number as u128;
             let contract_id = *contract_id as u128;
+            let jackpot = revenue_split.jackpot as u128;
+            let treasury = revenue_split.treasury as u128;
+            let reserve = revenue_split.reserve as u128;
             let body = ethabi::encode(&[
                 Token::Uint(nb_numbers.into()),
                 Token::Uint(min_number.into()),
                 Token::Uint(max_number.into()),
                 Token::Uint(contract_id.into()),
+                Token::Uint(jackpot.into()),
+                Token::Uint(treasury.into()),
+                Token::Uint(reserve.into()),
+                Token::Uint((*ticket_price).into()),
             ]);
             ethabi::encode(&[Token::Uint(REQUEST_SET_CONFIG.into()), Token::Bytes(body)])
         }
@@ -140,6 +211,40 @@ fn encode_request(request: &RequestForAction) -> Result<Vec<u8>, RaffleDrawError
             ]);
             ethabi::encode(&[Token::Uint(REQUEST_SET_RESULTS.into()), Token::Bytes(body)])
         }
+        RequestForAction::Stop(draw_number) => {
+            let draw_number = *draw_number as u128;
+            let body = ethabi::encode(&[Token::Uint(draw_number.into())]);
+            ethabi::encode(&[Token::Uint(REQUEST_STOP.into()), Token::Bytes(body)])
+        }
+        RequestForAction::CancelDraw(draw_number) => {
+            let draw_number = *draw_number as u128;
+            let body = ethabi::encode(&[Token::Uint(draw_number.into())]);
+            ethabi::encode(&[
+                Token::Uint(REQUEST_CANCEL_DRAW.into()),
+                Token::Bytes(body),
+            ])
+        }
+        RequestForAction::SetPaused(paused) => {
+            let body = ethabi::encode(&[Token::Bool(*paused)]);
+            ethabi::encode(&[
+                Token::Uint(REQUEST_SET_PAUSED.into()),
+                Token::Bytes(body),
+            ])
+        }
+        RequestForAction::UpdateConfig(config) => {
+            let nb_numbers = config.nb_numbers as u128;
+            let min_number = config.min_number as u128;
+            let max_number = config.max_number as u128;
+            let body = ethabi::encode(&[
+                Token::Uint(nb_numbers.into()),
+                Token::Uint(min_number.into()),
+                Token::Uint(max_number.into()),
+            ]);
+            ethabi::encode(&[
+                Token::Uint(REQUEST_UPDATE_CONFIG.into()),
+                Token::Bytes(body),
+            ])
+        }
     };
     Ok(encoded)
 }
@@ -236,12 +341,142 @@ fn decode_status(raw: &[u8]) -> Result<RaffleRegistrationStatus, RaffleDrawError
         3 => RaffleRegistrationStatus::RegistrationsClosed,
         4 => RaffleRegistrationStatus::SaltGenerated,
         5 => RaffleRegistrationStatus::ResultsReceived,
+        6 => RaffleRegistrationStatus::Closed,
+        7 => RaffleRegistrationStatus::DrawCancelled,
         _ => return Err(FailedToDecodeStatus),
     };
 
     Ok(status)
 }
 
+fn get_paused(client: &mut EvmRollupClient) -> Result<Option<bool>, RaffleDrawError> {
+
+    let key  = hex::decode("5f706175736564")
+        .map_err(|_| FailedToDecodePaused)?;
+
+    let raw_value = client
+        .session()
+        .get(key.as_slice())
+        .log_err("Paused flag unknown in kv store")
+        .map_err(|_| PausedUnknown)?;
+
+    let result = match raw_value {
+        Some(raw) => Some(decode_paused(raw.as_slice())?),
+        None => None,
+    };
+
+    Ok(result)
+}
+
+fn decode_paused(raw: &[u8]) -> Result<bool, RaffleDrawError> {
+    let tokens = ethabi::decode(&[ParamType::Bool], raw)
+        .log_err("Fail to decode paused flag in kv store")
+        .map_err(|_| FailedToDecodePaused)?;
+    let [Token::Bool(paused)] = tokens.as_slice() else {
+        return Err(FailedToDecodePaused);
+    };
+    Ok(*paused)
+}
+
+fn get_nb_tickets(client: &mut EvmRollupClient) -> Result<Option<u32>, RaffleDrawError> {
+
+    let key  = hex::decode("5f6e625469636b657473")
+        .map_err(|_| FailedToDecodeNbTickets)?;
+
+    let raw_value = client
+        .session()
+        .get(key.as_slice())
+        .log_err("Number of tickets unknown in kv store")
+        .map_err(|_| NbTicketsUnknown)?;
+
+    let result = match raw_value {
+        Some(raw) => Some(decode_nb_tickets(raw.as_slice())?),
+        None => None,
+    };
+
+    Ok(result)
+}
+
+fn decode_nb_tickets(raw: &[u8]) -> Result<u32, RaffleDrawError> {
+    let tokens = ethabi::decode(&[ParamType::Uint(32)], raw)
+        .log_err("Fail to decode number of tickets in kv store")
+        .map_err(|_| FailedToDecodeNbTickets)?;
+    let [Token::Uint(nb_tickets)] = tokens.as_slice() else {
+        return Err(FailedToDecodeNbTickets);
+    };
+    // the number of tickets must fit in a u32
+    u32::try_from(*nb_tickets).map_err(|_| FailedToDecodeNbTickets)
+}
+
+fn get_revenue(client: &mut EvmRollupClient) -> Result<Option<Balance>, RaffleDrawError> {
+
+    let key  = hex::decode("5f726576656e7565")
+        .map_err(|_| FailedToDecodeRevenue)?;
+
+    let raw_value = client
+        .session()
+        .get(key.as_slice())
+        .log_err("Revenue unknown in kv store")
+        .map_err(|_| RevenueUnknown)?;
+
+    let result = match raw_value {
+        Some(raw) => Some(decode_revenue(raw.as_slice())?),
+        None => None,
+    };
+
+    Ok(result)
+}
+
+fn decode_revenue(raw: &[u8]) -> Result<Balance, RaffleDrawError> {
+    let tokens = ethabi::decode(&[ParamType::Uint(256)], raw)
+        .log_err("Fail to decode revenue in kv store")
+        .map_err(|_| FailedToDecodeRevenue)?;
+    let [Token::Uint(revenue)] = tokens.as_slice() else {
+        return Err(FailedToDecodeRevenue);
+    };
+    // the revenue must fit in a Balance
+    Balance::try_from(*revenue).map_err(|_| FailedToDecodeRevenue)
+}
+
+fn get_config(client: &mut EvmRollupClient) -> Result<Option<RaffleConfig>, RaffleDrawError> {
+
+    let key  = hex::decode("5f636f6e666967")
+        .map_err(|_| FailedToDecodeConfig)?;
+
+    let raw_value = client
+        .session()
+        .get(key.as_slice())
+        .log_err("Config unknown in kv store")
+        .map_err(|_| ConfigUnknown)?;
+
+    let result = match raw_value {
+        Some(raw) => Some(decode_config(raw.as_slice())?),
+        None => None,
+    };
+
+    Ok(result)
+}
+
+fn decode_config(raw: &[u8]) -> Result<RaffleConfig, RaffleDrawError> {
+    let tokens = ethabi::decode(
+        &[ParamType::Uint(8), ParamType::Uint(32), ParamType::Uint(32)],
+        raw,
+    )
+    .log_err("Fail to decode config in kv store")
+    .map_err(|_| FailedToDecodeConfig)?;
+    let [Token::Uint(nb_numbers), Token::Uint(min_number), Token::Uint(max_number)] =
+        tokens.as_slice()
+    else {
+        return Err(FailedToDecodeConfig);
+    };
+    // the values must fit in the types of the config
+    Ok(RaffleConfig {
+        nb_numbers: u8::try_from(*nb_numbers).map_err(|_| FailedToDecodeConfig)?,
+        min_number: Number::try_from(*min_number).map_err(|_| FailedToDecodeConfig)?,
+        max_number: Number::try_from(*max_number).map_err(|_| FailedToDecodeConfig)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,13 +495,18 @@ mod tests {
         };
 
         let registration_id = 33;
+        let revenue_split = RevenueSplit {
+            jackpot: 80,
+            treasury: 15,
+            reserve: 5,
+        };
 
-        let request = RequestForAction::SetConfigAndStart(config, registration_id);
+        let request = RequestForAction::SetConfigAndStart(config, registration_id, revenue_split, 100);
 
         let encoded_request = encode_request(&request).expect("Failed to encode request");
         ink::env::debug_println!("Encoded request: {encoded_request:02x?}");
 
-        let expected : Vec<u8> = hex::decode("0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000800000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000320000000000000000000000000000000000000000000000000000000000000021")
+        let expected : Vec<u8> = hex::decode("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000003200000000000000000000000000000000000000000000000000000000000000210000000000000000000000000000000000000000000000000000000000000050000000000000000000000000000000000000000000000000000000000000000f00000000000000000000000000000000000000000000000000000000000000050000000000000000000000000000000000000000000000000000000000000064")
             .expect("hex decode failed");
         assert_eq!(expected, encoded_request);
     }
@@ -328,6 +568,143 @@ mod tests {
         assert_eq!(expected, encoded_request);
     }
 
+    #[ink::test]
+    fn encode_request_stop() {
+        let draw_number = 11;
+
+        let request = RequestForAction::Stop(draw_number);
+
+        let encoded_request = encode_request(&request).expect("Failed to encode request");
+        ink::env::debug_println!("Encoded request: {encoded_request:02x?}");
+
+        let expected : Vec<u8> = hex::decode("000000000000000000000000000000000000000000000000000000000000000500000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000b")
+            .expect("hex decode failed");
+        assert_eq!(expected, encoded_request);
+    }
+
+    #[ink::test]
+    fn encode_request_cancel_draw() {
+        let draw_number = 11;
+
+        let request = RequestForAction::CancelDraw(draw_number);
+
+        let encoded_request = encode_request(&request).expect("Failed to encode request");
+        ink::env::debug_println!("Encoded request: {encoded_request:02x?}");
+
+        let expected : Vec<u8> = hex::decode("000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000b")
+            .expect("hex decode failed");
+        assert_eq!(expected, encoded_request);
+    }
+
+    #[ink::test]
+    fn encode_request_set_paused() {
+        let request = RequestForAction::SetPaused(true);
+
+        let encoded_request = encode_request(&request).expect("Failed to encode request");
+        ink::env::debug_println!("Encoded request: {encoded_request:02x?}");
+
+        let expected : Vec<u8> = hex::decode("0000000000000000000000000000000000000000000000000000000000000007000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000001")
+            .expect("hex decode failed");
+        assert_eq!(expected, encoded_request);
+    }
+
+    #[ink::test]
+    fn encode_request_update_config() {
+        let config = RaffleConfig {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
+
+        let request = RequestForAction::UpdateConfig(config);
+
+        let encoded_request = encode_request(&request).expect("Failed to encode request");
+        ink::env::debug_println!("Encoded request: {encoded_request:02x?}");
+
+        let expected : Vec<u8> = hex::decode("000000000000000000000000000000000000000000000000000000000000000800000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000060000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000032")
+            .expect("hex decode failed");
+        assert_eq!(expected, encoded_request);
+    }
+
+    #[ink::test]
+    fn decode_config() {
+        let raw: Vec<u8> =
+            hex::decode("000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000032")
+                .expect("hex decode failed");
+        let config = super::decode_config(raw.as_slice()).expect("Fail to decode config");
+        assert_eq!(
+            config,
+            RaffleConfig {
+                nb_numbers: 4,
+                min_number: 1,
+                max_number: 50,
+            }
+        );
+
+        // overflow
+        let raw: Vec<u8> =
+            hex::decode("000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000032")
+                .expect("hex decode failed");
+        assert_eq!(
+            super::decode_config(raw.as_slice()),
+            Err(FailedToDecodeConfig)
+        );
+        let raw: Vec<u8> =
+            hex::decode("000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000010000")
+                .expect("hex decode failed");
+        assert_eq!(
+            super::decode_config(raw.as_slice()),
+            Err(FailedToDecodeConfig)
+        );
+    }
+
+    #[ink::test]
+    fn decode_paused() {
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
+                .expect("hex decode failed");
+        assert_eq!(super::decode_paused(raw.as_slice()), Ok(true));
+
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000000000000000000000000000000000000")
+                .expect("hex decode failed");
+        assert_eq!(super::decode_paused(raw.as_slice()), Ok(false));
+    }
+
+    #[ink::test]
+    fn decode_nb_tickets() {
+        let raw: Vec<u8> =
+            hex::decode("000000000000000000000000000000000000000000000000000000000000002a")
+                .expect("hex decode failed");
+        assert_eq!(super::decode_nb_tickets(raw.as_slice()), Ok(42));
+
+        // overflow
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000000000000000000000000000100000000")
+                .expect("hex decode failed");
+        assert_eq!(
+            super::decode_nb_tickets(raw.as_slice()),
+            Err(FailedToDecodeNbTickets)
+        );
+    }
+
+    #[ink::test]
+    fn decode_revenue() {
+        let raw: Vec<u8> =
+            hex::decode("00000000000000000000000000000000000000000000000000000000000f4240")
+                .expect("hex decode failed");
+        assert_eq!(super::decode_revenue(raw.as_slice()), Ok(1_000_000));
+
+        // overflow
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000100000000000000000000000000000000")
+                .expect("hex decode failed");
+        assert_eq!(
+            super::decode_revenue(raw.as_slice()),
+            Err(FailedToDecodeRevenue)
+        );
+    }
+
     #[ink::test]
     fn decode_status() {
         let raw: Vec<u8> =
@@ -365,6 +742,18 @@ mod tests {
                 .expect("hex decode failed");
         let status = super::decode_status(raw.as_slice()).expect("Fail to decode status");
         assert_eq!(status, RaffleRegistrationStatus::ResultsReceived);
+
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000000000000000000000000000000000006")
+                .expect("hex decode failed");
+        let status = super::decode_status(raw.as_slice()).expect("Fail to decode status");
+        assert_eq!(status, RaffleRegistrationStatus::Closed);
+
+        let raw: Vec<u8> =
+            hex::decode("0000000000000000000000000000000000000000000000000000000000000007")
+                .expect("hex decode failed");
+        let status = super::decode_status(raw.as_slice()).expect("Fail to decode status");
+        assert_eq!(status, RaffleRegistrationStatus::DrawCancelled);
     }
 
     #[ink::test]
@@ -496,7 +885,12 @@ mod tests {
             min_number: 1,
             max_number: 5,
         };
-        let action = RequestForAction::SetConfigAndStart(config, 33);
+        let revenue_split = crate::types::RevenueSplit {
+            jackpot: 100,
+            treasury: 0,
+            reserve: 0,
+        };
+        let action = RequestForAction::SetConfigAndStart(config, 33, revenue_split, 0);
 
         test_do_action(
             expected_draw_number,
@@ -75,7 +75,7 @@ impl Indexer {
         &self,
         draw_number: DrawNumber,
         numbers: &Vec<Number>,
-    ) -> Result<(Vec<AccountId32>, Vec<AccountId20>), RaffleDrawError> {
+    ) -> Result<Vec<MultiAddress>, RaffleDrawError> {
         info!(
                 "Request received to get the winners for raffle id {draw_number} and numbers {numbers:?} "
             );
@@ -124,38 +124,15 @@ impl Indexer {
                 .0;
 
         // add the winners
-        let mut winners_substrate = Vec::new();
-        let mut winners_evm = Vec::new();
+        let mut winners = Vec::new();
         for w in result.data.participations.nodes.iter() {
             // build the accountId from the string address
-            match w.accountId.len() {
-                48 => { // accountId 32
-                    let account_id = sp_core::crypto::AccountId32::from_ss58check(w.accountId)
-                        .or(Err(InvalidSs58Address))?;
-                    let address_hex: AccountId32 = scale::Encode::encode(&account_id)
-                        .try_into()
-                        .or(Err(InvalidKeyLength))?;
-                    winners_substrate.push(address_hex);
-                }
-                42 => { // accountId 20
-                    // remove the prefix 0x
-                    let without_0x = w.accountId.get(2..).ok_or(InvalidKeyLength)?;
-                    let address_hex: AccountId20 = hex::decode(without_0x)
-                        .expect("hex decode failed")
-                        .try_into()
-                        .or(Err(InvalidKeyLength))?;
-                    winners_evm.push(address_hex);
-                }
-                _ => {
-                    error!("Not Supported address: {0:?}", w.accountId);
-                    return Err(InvalidKeyLength);
-                }
-            }
+            winners.push(parse_address(w.accountId)?);
         }
 
-        info!("Winners Substrate: {winners_substrate:02x?} - EVM: {winners_evm:02x?} ");
+        info!("Winners: {winners:02x?}");
 
-        Ok((winners_substrate, winners_evm))
+        Ok(winners)
     }
 
     pub fn query_salt(&self, draw_number: DrawNumber, registration_contract_id: RegistrationContractId) -> Result<Salt, RaffleDrawError> {
@@ -204,6 +181,50 @@ impl Indexer {
     }
 }
 
+/// Build the account from the address returned by the indexer
+/// - 0x + 40 hex chars: EVM account
+/// - 0x + 64 hex chars: Move account
+/// - SS58: Substrate account
+/// - bech32: Cosmos account
+/// - base58 of 32 bytes: ed25519 public key (ie: Solana)
+pub fn parse_address(address: &str) -> Result<MultiAddress, RaffleDrawError> {
+    if let Some(without_0x) = address.strip_prefix("0x") {
+        let address_hex = hex::decode(without_0x).or(Err(InvalidKeyLength))?;
+        return match address_hex.len() {
+            20 => Ok(MultiAddress::AccountId20(
+                address_hex.try_into().or(Err(InvalidKeyLength))?,
+            )),
+            32 => Ok(MultiAddress::Move(
+                address_hex.try_into().or(Err(InvalidKeyLength))?,
+            )),
+            _ => {
+                error!("Not Supported address: {0:?}", address);
+                Err(InvalidKeyLength)
+            }
+        };
+    }
+
+    if let Ok(account_id) = sp_core::crypto::AccountId32::from_ss58check(address) {
+        let address_hex: AccountId32 = scale::Encode::encode(&account_id)
+            .try_into()
+            .or(Err(InvalidKeyLength))?;
+        return Ok(MultiAddress::AccountId32(address_hex));
+    }
+
+    if let Ok((hrp, data)) = bech32::decode(address) {
+        return Ok(MultiAddress::Bech32(hrp.as_str().into(), data));
+    }
+
+    if let Ok(address_hex) = bs58::decode(address).into_vec() {
+        if let Ok(address_hex) = address_hex.try_into() {
+            return Ok(MultiAddress::Ed25519(address_hex));
+        }
+    }
+
+    error!("Not Supported address: {0:?}", address);
+    Err(UnsupportedAddress)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,8 +256,8 @@ mod tests {
 
         let indexer = new_indexer();
         let winners = indexer.query_winners(draw_num, &numbers).unwrap();
-        assert_eq!(1, winners.0.len());
-        assert_eq!(0, winners.1.len());
+        assert_eq!(1, winners.len());
+        assert!(matches!(winners[0], MultiAddress::AccountId32(_)));
     }
 
     #[ink::test]
@@ -248,8 +269,8 @@ mod tests {
 
         let indexer = new_indexer();
         let winners = indexer.query_winners(draw_num, &numbers).unwrap();
-        assert_eq!(0, winners.0.len());
-        assert_eq!(1, winners.1.len());
+        assert_eq!(1, winners.len());
+        assert!(matches!(winners[0], MultiAddress::AccountId20(_)));
     }
 
     #[ink::test]
@@ -261,8 +282,7 @@ mod tests {
 
         let indexer = new_indexer();
         let winners = indexer.query_winners(draw_num, &numbers).unwrap();
-        assert_eq!(0, winners.0.len());
-        assert_eq!(0, winners.1.len());
+        assert_eq!(0, winners.len());
     }
 
     #[ink::test]
@@ -276,4 +296,41 @@ mod tests {
         let result = indexer.query_winners(draw_num, &numbers);
         assert_eq!(Err(NoNumber), result);
     }
+
+    #[ink::test]
+    fn test_parse_addresses() {
+        // substrate
+        assert_eq!(
+            Ok(MultiAddress::AccountId32(hex_literal::hex!(
+                "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+            ))),
+            parse_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+        );
+        // evm
+        assert_eq!(
+            Ok(MultiAddress::AccountId20(hex_literal::hex!(
+                "f39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+            ))),
+            parse_address("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+        // move
+        assert_eq!(
+            Ok(MultiAddress::Move([1; 32])),
+            parse_address("0x0101010101010101010101010101010101010101010101010101010101010101")
+        );
+        // solana
+        assert_eq!(
+            Ok(MultiAddress::Ed25519([0; 32])),
+            parse_address("11111111111111111111111111111111")
+        );
+        // cosmos
+        let address = bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("cosmos").unwrap(), &[4; 20]).unwrap();
+        assert_eq!(
+            Ok(MultiAddress::Bech32("cosmos".into(), vec![4; 20])),
+            parse_address(&address)
+        );
+        // not supported
+        assert_eq!(Err(InvalidKeyLength), parse_address("0x0101"));
+        assert_eq!(Err(UnsupportedAddress), parse_address("not an address"));
+    }
 }
@@ -1,4 +1,5 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+pub mod contract_id;
 pub mod draw;
 pub mod error;
 pub mod evm_contract;
@@ -1,9 +1,13 @@
 extern crate alloc;
 
-use crate::types::{AccountId20, AccountId32, DrawNumber, Hash, Number, RaffleConfig, RegistrationContractId, Salt};
+use crate::types::{
+    Balance, DrawNumber, Hash, MultiAddress, Number, QueueIndex, RaffleConfig, RegistrationContractId,
+    RevenueSplit, Salt,
+};
 use alloc::vec::Vec;
 
-#[derive(scale::Encode, scale::Decode, Debug)]
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum RaffleManagerStatus {
     NotStarted,
     Started,
@@ -13,14 +17,17 @@ pub enum RaffleManagerStatus {
     WaitingResult,
     WaitingWinner,
     DrawFinished,
+    Closed,
+    DrawCancelled,
 }
 
 /// Message to synchronize the contracts, to request the lotto draw and get the list of winners.
 /// message pushed in the queue by this contract and read by the offchain rollup
 #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum LottoManagerRequestMessage {
-    /// request to propagate the config to all given contracts
-    PropagateConfig(RaffleConfig, Vec<RegistrationContractId>),
+    /// request to propagate the config, the revenue split and the ticket price to all given contracts
+    PropagateConfig(RaffleConfig, RevenueSplit, Balance, Vec<RegistrationContractId>),
     /// request to open the registrations to all given contracts
     OpenRegistrations(DrawNumber, Vec<RegistrationContractId>),
     /// request to close the registrations to all given contracts
@@ -38,6 +45,128 @@ pub enum LottoManagerRequestMessage {
         bool,
         Vec<RegistrationContractId>,
     ),
+    /// request to stop the workflow in all given contracts
+    Stop(DrawNumber, Vec<RegistrationContractId>),
+    /// request to cancel the draw in all given contracts
+    CancelDraw(DrawNumber, Vec<RegistrationContractId>),
+    /// request to pause (true) or unpause (false) the participations in all given contracts
+    SetPaused(bool, Vec<RegistrationContractId>),
+    /// request to update the config in all given contracts before opening the next draw
+    UpdateConfig(RaffleConfig, Vec<RegistrationContractId>),
+}
+
+impl LottoManagerRequestMessage {
+    /// Return true if the request is obsolete for the given status of the manager,
+    /// ie the request was still in the queue when the draw was cancelled or the lotto was closed
+    pub fn is_obsolete(&self, manager_status: &RaffleManagerStatus) -> bool {
+        match manager_status {
+            RaffleManagerStatus::DrawCancelled => matches!(
+                self,
+                LottoManagerRequestMessage::OpenRegistrations(_, _)
+                    | LottoManagerRequestMessage::CloseRegistrations(_, _)
+                    | LottoManagerRequestMessage::GenerateSalt(_, _)
+                    | LottoManagerRequestMessage::DrawNumbers(_, _, _)
+                    | LottoManagerRequestMessage::CheckWinners(_, _)
+                    | LottoManagerRequestMessage::PropagateResults(_, _, _, _)
+            ),
+            RaffleManagerStatus::Closed => !matches!(self, LottoManagerRequestMessage::Stop(_, _)),
+            _ => false,
+        }
+    }
+
+    /// Merge the given request in this one if both target the same phase for the same draw
+    /// (the lists of contracts are merged). Return false if the requests cannot be merged.
+    pub fn merge(&mut self, other: &Self) -> bool {
+        use LottoManagerRequestMessage::*;
+        let (contract_ids, other_contract_ids) = match (self, other) {
+            (PropagateConfig(c1, s1, p1, ids), PropagateConfig(c2, s2, p2, other_ids))
+                if *c1 == *c2 && *s1 == *s2 && *p1 == *p2 =>
+            {
+                (ids, other_ids)
+            }
+            (UpdateConfig(c1, ids), UpdateConfig(c2, other_ids)) if *c1 == *c2 => {
+                (ids, other_ids)
+            }
+            (OpenRegistrations(d1, ids), OpenRegistrations(d2, other_ids))
+            | (CloseRegistrations(d1, ids), CloseRegistrations(d2, other_ids))
+            | (GenerateSalt(d1, ids), GenerateSalt(d2, other_ids))
+            | (Stop(d1, ids), Stop(d2, other_ids))
+            | (CancelDraw(d1, ids), CancelDraw(d2, other_ids))
+                if *d1 == *d2 =>
+            {
+                (ids, other_ids)
+            }
+            (PropagateResults(d1, n1, w1, ids), PropagateResults(d2, n2, w2, other_ids))
+                if *d1 == *d2 && *n1 == *n2 && *w1 == *w2 =>
+            {
+                (ids, other_ids)
+            }
+            (SetPaused(p1, ids), SetPaused(p2, other_ids)) if *p1 == *p2 => (ids, other_ids),
+            // the same request sent twice
+            (request, other) => return *request == *other,
+        };
+        for contract_id in other_contract_ids {
+            if !contract_ids.contains(contract_id) {
+                contract_ids.push(*contract_id);
+            }
+        }
+        true
+    }
+}
+
+/// Collapse the consecutive requests targeting the same phase for the same draw.
+/// Return the merged requests with the number of requests read in the queue for each of them.
+pub fn merge_requests(
+    requests: Vec<LottoManagerRequestMessage>,
+) -> Vec<(LottoManagerRequestMessage, u32)> {
+    let mut merged_requests: Vec<(LottoManagerRequestMessage, u32)> = Vec::new();
+    for request in requests {
+        if let Some((last_request, nb_requests)) = merged_requests.last_mut() {
+            if last_request.merge(&request) {
+                *nb_requests += 1;
+                continue;
+            }
+        }
+        merged_requests.push((request, 1));
+    }
+    merged_requests
+}
+
+/// Kind of error occurred when the offchain rollup synchronizes a registration contract.
+/// New kinds must be added at the end to keep the SCALE encoding.
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ActionFailure {
+    /// the registration contract is not configured in the offchain rollup
+    MissingConfig,
+    /// the rpc call or the transaction failed
+    RpcError,
+    /// the status, the draw number or another value in the kv store cannot be read
+    InvalidState,
+    /// other error
+    Other,
+}
+
+/// Messages in the queue of the manager not processed yet
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct QueueView {
+    /// index of the first message not processed yet
+    pub head: QueueIndex,
+    /// index of the next message pushed in the queue
+    pub tail: QueueIndex,
+    /// messages between the head and the tail with their index
+    pub messages: Vec<(QueueIndex, LottoManagerRequestMessage)>,
+}
+
+/// Reply sent to the manager: the response with the index in the queue of the request answered.
+/// The manager rejects the replies for the requests already processed.
+#[derive(scale::Encode, scale::Decode)]
+pub struct LottoManagerReply {
+    /// index in the queue of the (last) request answered, None if the response does not answer a request
+    pub request_index: Option<QueueIndex>,
+    /// response
+    pub response: LottoManagerResponseMessage,
 }
 
 /// Offchain rollup response
@@ -53,8 +182,9 @@ pub enum LottoManagerResponseMessage {
     RegistrationsOpen(DrawNumber, Vec<RegistrationContractId>),
     /// The registration is closed for the given contract ids.
     /// arg1: draw number
-    /// arg2: list of contracts where the registration is closed
-    RegistrationsClosed(DrawNumber, Vec<RegistrationContractId>),
+    /// arg2: list of contracts where the registration is closed with the number of tickets recorded
+    /// and the amount paid for these tickets
+    RegistrationsClosed(DrawNumber, Vec<(RegistrationContractId, u32, Balance)>),
     /// The salt is generated for the given contract ids.
     /// arg1: draw number
     /// arg2: list of contracts where the salt is generated
@@ -66,10 +196,9 @@ pub enum LottoManagerResponseMessage {
     WinningNumbers(DrawNumber, Vec<Number>, Hash),
     /// Return the list of winners
     /// arg1: draw number
-    /// arg2: winners substrate
-    /// arg3: winners evm
-    /// arg4: hash of winning numbers
-    Winners(DrawNumber, Vec<AccountId32>, Vec<AccountId20>, Hash),
+    /// arg2: winners
+    /// arg3: hash of winning numbers
+    Winners(DrawNumber, Vec<MultiAddress>, Hash),
     /// The results are propagated to the given contract ids.
     /// arg1: draw number
     /// arg2: list of contracts where the results are propagated
@@ -77,4 +206,85 @@ pub enum LottoManagerResponseMessage {
     ResultsPropagated(DrawNumber, Vec<RegistrationContractId>, Hash),
     /// Request to close the registrations
     CloseRegistrations(),
+    /// The workflow is stopped for the given contract ids.
+    /// arg1: draw number
+    /// arg2: list of contracts where the workflow is stopped
+    Stopped(DrawNumber, Vec<RegistrationContractId>),
+    /// The draw is cancelled for the given contract ids.
+    /// arg1: draw number
+    /// arg2: list of contracts where the draw is cancelled
+    DrawCancelled(DrawNumber, Vec<RegistrationContractId>),
+    /// The participations are paused (or unpaused) for the given contract ids.
+    /// arg1: true if paused, false if unpaused
+    /// arg2: list of contracts where the flag is propagated
+    PausePropagated(bool, Vec<RegistrationContractId>),
+    /// The action failed for the given contract ids.
+    /// arg1: list of contracts where the action failed with the kind of error
+    ActionFailed(Vec<(RegistrationContractId, ActionFailure)>),
+    /// The new config is propagated to the given contract ids.
+    /// arg1: list of contracts where the config is updated
+    /// arg2: hash of config
+    ConfigUpdated(Vec<RegistrationContractId>, Hash),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn obsolete_requests() {
+        let open_registrations = LottoManagerRequestMessage::OpenRegistrations(11, Vec::new());
+        let cancel_draw = LottoManagerRequestMessage::CancelDraw(11, Vec::new());
+        let stop = LottoManagerRequestMessage::Stop(11, Vec::new());
+        let set_paused = LottoManagerRequestMessage::SetPaused(true, Vec::new());
+
+        assert!(!open_registrations.is_obsolete(&RaffleManagerStatus::RegistrationsOpen));
+        assert!(!cancel_draw.is_obsolete(&RaffleManagerStatus::RegistrationsOpen));
+
+        assert!(open_registrations.is_obsolete(&RaffleManagerStatus::DrawCancelled));
+        assert!(!cancel_draw.is_obsolete(&RaffleManagerStatus::DrawCancelled));
+        assert!(!stop.is_obsolete(&RaffleManagerStatus::DrawCancelled));
+        assert!(!set_paused.is_obsolete(&RaffleManagerStatus::DrawCancelled));
+
+        assert!(open_registrations.is_obsolete(&RaffleManagerStatus::Closed));
+        assert!(cancel_draw.is_obsolete(&RaffleManagerStatus::Closed));
+        assert!(!stop.is_obsolete(&RaffleManagerStatus::Closed));
+    }
+
+    #[ink::test]
+    fn merge_requests() {
+        let config = RaffleConfig {
+            nb_numbers: 4,
+            min_number: 1,
+            max_number: 50,
+        };
+        let requests = alloc::vec![
+            LottoManagerRequestMessage::OpenRegistrations(11, alloc::vec![1, 2, 3]),
+            LottoManagerRequestMessage::OpenRegistrations(11, alloc::vec![2, 3]),
+            LottoManagerRequestMessage::OpenRegistrations(11, alloc::vec![4]),
+            LottoManagerRequestMessage::SetPaused(true, alloc::vec![1]),
+            LottoManagerRequestMessage::SetPaused(false, alloc::vec![1]),
+            LottoManagerRequestMessage::SetPaused(false, alloc::vec![2]),
+            LottoManagerRequestMessage::CheckWinners(11, alloc::vec![1, 2]),
+            LottoManagerRequestMessage::CheckWinners(11, alloc::vec![1, 2]),
+            LottoManagerRequestMessage::CloseRegistrations(11, alloc::vec![1]),
+            LottoManagerRequestMessage::CloseRegistrations(12, alloc::vec![2]),
+            LottoManagerRequestMessage::UpdateConfig(config.clone(), alloc::vec![1]),
+            LottoManagerRequestMessage::UpdateConfig(config.clone(), alloc::vec![2]),
+        ];
+
+        let merged_requests = super::merge_requests(requests);
+        assert_eq!(
+            merged_requests,
+            alloc::vec![
+                (LottoManagerRequestMessage::OpenRegistrations(11, alloc::vec![1, 2, 3, 4]), 3),
+                (LottoManagerRequestMessage::SetPaused(true, alloc::vec![1]), 1),
+                (LottoManagerRequestMessage::SetPaused(false, alloc::vec![1, 2]), 2),
+                (LottoManagerRequestMessage::CheckWinners(11, alloc::vec![1, 2]), 2),
+                (LottoManagerRequestMessage::CloseRegistrations(11, alloc::vec![1]), 1),
+                (LottoManagerRequestMessage::CloseRegistrations(12, alloc::vec![2]), 1),
+                (LottoManagerRequestMessage::UpdateConfig(config, alloc::vec![1, 2]), 2),
+            ]
+        );
+    }
 }
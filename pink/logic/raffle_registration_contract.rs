@@ -1,7 +1,9 @@
 extern crate alloc;
 
 use crate::error::RaffleDrawError;
-use crate::types::{DrawNumber, Number, RaffleConfig, RegistrationContractId};
+use crate::types::{
+    Balance, DrawNumber, Number, RaffleConfig, RegistrationContractId, RevenueSplit,
+};
 use alloc::vec::Vec;
 
 #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Clone, Copy, Debug)]
@@ -12,14 +14,18 @@ pub enum RaffleRegistrationStatus {
     RegistrationsClosed,
     SaltGenerated,
     ResultsReceived,
+    Closed,
+    DrawCancelled,
 }
 
 
 /// Message sent by the offchain rollup to the Raffle Registration Contracts
-#[derive(scale::Encode, scale::Decode, Debug, Clone)]
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum RequestForAction {
-    /// update the config, set the registration contract id for this contract and start the workflow
-    SetConfigAndStart(RaffleConfig, RegistrationContractId),
+    /// update the config, set the registration contract id for this contract,
+    /// set the revenue split, the ticket price and start the workflow
+    SetConfigAndStart(RaffleConfig, RegistrationContractId, RevenueSplit, Balance),
     /// open the registrations for the given draw number
     OpenRegistrations(DrawNumber),
     /// close the registrations for the given draw number
@@ -28,6 +34,14 @@ pub enum RequestForAction {
     GenerateSalt(DrawNumber),
     /// set the results (winning numbers + true or false if we have a winner) for the given draw number
     SetResults(DrawNumber, Vec<Number>, bool),
+    /// stop the workflow, the draw in progress is cancelled
+    Stop(DrawNumber),
+    /// cancel the given draw
+    CancelDraw(DrawNumber),
+    /// pause (true) or unpause (false) the participations
+    SetPaused(bool),
+    /// update the config between two draws
+    UpdateConfig(RaffleConfig),
 }
 
 pub trait RaffleRegistrationContract {
@@ -38,4 +52,22 @@ pub trait RaffleRegistrationContract {
         action: RequestForAction,
         attest_key: &[u8; 32],
     ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError>;
+
+    fn set_paused(
+        &self,
+        paused: bool,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError>;
+
+    fn update_config(
+        &self,
+        config: RaffleConfig,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError>;
+
+    /// return the number of tickets recorded for the current draw
+    fn get_nb_tickets(&self) -> Result<u32, RaffleDrawError>;
+
+    /// return the amount paid for the tickets of the current draw
+    fn get_revenue(&self) -> Result<Balance, RaffleDrawError>;
 }
@@ -1,9 +1,17 @@
+use crate::error::RaffleDrawError::{self, *};
+use ethabi::{ParamType, Token};
 use ink::prelude::string::String;
 use ink::prelude::vec::Vec;
 
 pub type RegistrationContractId = u128;
 pub type DrawNumber = u32;
+pub type BlockNumber = u32;
+/// Timestamp in milliseconds since the unix epoch
+pub type Timestamp = u64;
+/// index of a request in the queue of the manager
+pub type QueueIndex = u32;
 pub type Number = u16;
+pub type Balance = u128;
 pub type WasmContractId = [u8; 32];
 pub type EvmContractId = [u8; 20];
 pub type AccountId32 = [u8; 32];
@@ -11,6 +19,84 @@ pub type AccountId20 = [u8; 20];
 pub type Hash = [u8; 32];
 pub type Salt = Vec<u8>;
 
+/// Account of a participant, whatever the ecosystem of the registration contract.
+/// New kinds of account must be added at the end to keep the SCALE encoding.
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Eq, PartialEq)]
+pub enum MultiAddress {
+    /// Substrate account (SS58 encoded)
+    AccountId32(AccountId32),
+    /// EVM account (0x prefixed hex encoded)
+    AccountId20(AccountId20),
+    /// Ed25519 public key not SS58 encoded (ie: Solana, base58 encoded)
+    Ed25519([u8; 32]),
+    /// Cosmos account (bech32 encoded)
+    /// arg1: human readable part
+    /// arg2: data
+    Bech32(String, Vec<u8>),
+    /// Move account (ie: Aptos, Sui, 0x prefixed hex encoded)
+    Move([u8; 32]),
+}
+
+const ADDRESS_ACCOUNT_ID_32: u8 = 0;
+const ADDRESS_ACCOUNT_ID_20: u8 = 1;
+const ADDRESS_ED25519: u8 = 2;
+const ADDRESS_BECH32: u8 = 3;
+const ADDRESS_MOVE: u8 = 4;
+
+impl MultiAddress {
+    /// ABI encoding: abi.encode(uint8 kind, bytes address)
+    /// For bech32 addresses, address = abi.encode(string hrp, bytes data)
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let (kind, address) = match self {
+            MultiAddress::AccountId32(address) => (ADDRESS_ACCOUNT_ID_32, address.to_vec()),
+            MultiAddress::AccountId20(address) => (ADDRESS_ACCOUNT_ID_20, address.to_vec()),
+            MultiAddress::Ed25519(address) => (ADDRESS_ED25519, address.to_vec()),
+            MultiAddress::Bech32(hrp, data) => (
+                ADDRESS_BECH32,
+                ethabi::encode(&[Token::String(hrp.clone()), Token::Bytes(data.clone())]),
+            ),
+            MultiAddress::Move(address) => (ADDRESS_MOVE, address.to_vec()),
+        };
+        ethabi::encode(&[Token::Uint(kind.into()), Token::Bytes(address)])
+    }
+
+    pub fn abi_decode(raw: &[u8]) -> Result<Self, RaffleDrawError> {
+        let tokens = ethabi::decode(&[ParamType::Uint(8), ParamType::Bytes], raw)
+            .map_err(|_| FailedToDecodeAddress)?;
+        let [Token::Uint(kind), Token::Bytes(address)] = tokens.as_slice() else {
+            return Err(FailedToDecodeAddress);
+        };
+        if *kind > u8::MAX.into() {
+            return Err(FailedToDecodeAddress);
+        }
+        let address = match kind.as_u32() as u8 {
+            ADDRESS_ACCOUNT_ID_32 => MultiAddress::AccountId32(
+                address.as_slice().try_into().map_err(|_| InvalidKeyLength)?,
+            ),
+            ADDRESS_ACCOUNT_ID_20 => MultiAddress::AccountId20(
+                address.as_slice().try_into().map_err(|_| InvalidKeyLength)?,
+            ),
+            ADDRESS_ED25519 => MultiAddress::Ed25519(
+                address.as_slice().try_into().map_err(|_| InvalidKeyLength)?,
+            ),
+            ADDRESS_BECH32 => {
+                let tokens =
+                    ethabi::decode(&[ParamType::String, ParamType::Bytes], address.as_slice())
+                        .map_err(|_| FailedToDecodeAddress)?;
+                let [Token::String(hrp), Token::Bytes(data)] = tokens.as_slice() else {
+                    return Err(FailedToDecodeAddress);
+                };
+                MultiAddress::Bech32(hrp.clone(), data.clone())
+            }
+            ADDRESS_MOVE => MultiAddress::Move(
+                address.as_slice().try_into().map_err(|_| InvalidKeyLength)?,
+            ),
+            _ => return Err(UnsupportedAddress),
+        };
+        Ok(address)
+    }
+}
+
 #[derive(scale::Encode, scale::Decode, Debug, Clone)]
 #[cfg_attr(
     feature = "std",
@@ -54,8 +140,60 @@ pub struct EvmContractConfig {
 }
 
 #[derive(scale::Encode, scale::Decode, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub struct RaffleConfig {
     pub nb_numbers: u8,
     pub min_number: Number,
     pub max_number: Number,
 }
+
+/// Split of the revenue of the tickets, in percent
+#[derive(scale::Encode, scale::Decode, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct RevenueSplit {
+    pub jackpot: u8,
+    pub treasury: u8,
+    pub reserve: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn abi_encode_decode_addresses() {
+        let addresses = vec![
+            MultiAddress::AccountId32([1; 32]),
+            MultiAddress::AccountId20([2; 20]),
+            MultiAddress::Ed25519([3; 32]),
+            MultiAddress::Bech32("cosmos".into(), vec![4; 20]),
+            MultiAddress::Move([5; 32]),
+        ];
+        for address in addresses {
+            let encoded = address.abi_encode();
+            assert_eq!(Ok(address), MultiAddress::abi_decode(&encoded));
+        }
+    }
+
+    #[ink::test]
+    fn abi_encode_account_id_20() {
+        let encoded = MultiAddress::AccountId20([2; 20]).abi_encode();
+        let expected = hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000001\
+            0000000000000000000000000000000000000000000000000000000000000040\
+            0000000000000000000000000000000000000000000000000000000000000014\
+            0202020202020202020202020202020202020202000000000000000000000000",
+        )
+        .expect("hex decode failed");
+        assert_eq!(expected, encoded);
+    }
+
+    #[ink::test]
+    fn abi_decode_unsupported_address() {
+        let encoded = ethabi::encode(&[Token::Uint(9.into()), Token::Bytes(vec![1; 32])]);
+        assert_eq!(Err(UnsupportedAddress), MultiAddress::abi_decode(&encoded));
+
+        let encoded = ethabi::encode(&[Token::Uint(0.into()), Token::Bytes(vec![1; 20])]);
+        assert_eq!(Err(InvalidKeyLength), MultiAddress::abi_decode(&encoded));
+    }
+}
@@ -3,14 +3,18 @@ extern crate alloc;
 use crate::error::RaffleDrawError::{self, *};
 use crate::types::*;
 use alloc::vec::Vec;
+use ink::prelude::string::String;
 use phat_offchain_rollup::clients::ink::{Action, InkRollupClient};
 use scale::Encode;
 
-use crate::raffle_manager_contract::RaffleManagerStatus;
+use crate::raffle_manager_contract::{
+    ActionFailure, LottoManagerRequestMessage, QueueView, RaffleManagerStatus,
+};
 use crate::raffle_registration_contract::{
     RaffleRegistrationContract, RaffleRegistrationStatus, RequestForAction,
 };
-use pink_extension::ResultExt;
+use pink_extension::{http_post, ResultExt};
+use serde::Deserialize;
 
 pub struct WasmContract {
     config: WasmContractConfig,
@@ -111,10 +115,91 @@ impl RaffleRegistrationContract for WasmContract {
         let tx = Self::maybe_submit_tx(client, attest_key, self.config.sender_key.as_ref())?;
         Ok((false, tx))
     }
+
+    fn set_paused(
+        &self,
+        paused: bool,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError> {
+        // connect to the contract
+        let mut client = Self::connect(&self.config)?;
+
+        let current_paused = get_paused(&mut client)?.unwrap_or_default();
+        ink::env::debug_println!("paused : {current_paused:?}");
+
+        if current_paused == paused {
+            // the contract is already synchronized
+            return Ok((true, None));
+        }
+
+        // synchronize the contract =>  Attach an action to the tx
+        let action = RequestForAction::SetPaused(paused);
+        client.action(Action::Reply(scale::Encode::encode(&action)));
+
+        // submit the transaction
+        let tx = Self::maybe_submit_tx(client, attest_key, self.config.sender_key.as_ref())?;
+        Ok((false, tx))
+    }
+
+    fn update_config(
+        &self,
+        config: RaffleConfig,
+        attest_key: &[u8; 32],
+    ) -> Result<(bool, Option<Vec<u8>>), RaffleDrawError> {
+        // connect to the contract
+        let mut client = Self::connect(&self.config)?;
+
+        let current_config = get_config(&mut client)?;
+        ink::env::debug_println!("config : {current_config:?}");
+
+        if current_config.as_ref() == Some(&config) {
+            // the contract is already synchronized
+            return Ok((true, None));
+        }
+
+        // synchronize the contract =>  Attach an action to the tx
+        let action = RequestForAction::UpdateConfig(config);
+        client.action(Action::Reply(scale::Encode::encode(&action)));
+
+        // submit the transaction
+        let tx = Self::maybe_submit_tx(client, attest_key, self.config.sender_key.as_ref())?;
+        Ok((false, tx))
+    }
+
+    fn get_nb_tickets(&self) -> Result<u32, RaffleDrawError> {
+        // connect to the contract
+        let mut client = Self::connect(&self.config)?;
+        let nb_tickets = get_nb_tickets(&mut client)?.unwrap_or_default();
+        ink::env::debug_println!("nb_tickets : {nb_tickets:?}");
+        Ok(nb_tickets)
+    }
+
+    fn get_revenue(&self) -> Result<Balance, RaffleDrawError> {
+        // connect to the contract
+        let mut client = Self::connect(&self.config)?;
+        let revenue = get_revenue(&mut client)?.unwrap_or_default();
+        ink::env::debug_println!("revenue : {revenue:?}");
+        Ok(revenue)
+    }
 }
 
 const DRAW_NUMBER: u32 = ink::selector_id!("DRAW_NUMBER");
 const STATUS: u32 = ink::selector_id!("STATUS");
+const PAUSED: u32 = ink::selector_id!("PAUSED");
+const NB_TICKETS: u32 = ink::selector_id!("NB_TICKETS");
+const CONFIG: u32 = ink::selector_id!("CONFIG");
+const REVENUE: u32 = ink::selector_id!("REVENUE");
+const DRAW_CONFIG: u32 = ink::selector_id!("DRAW_CONFIG");
+const NEXT_CLOSING_REGISTRATIONS: u32 = ink::selector_id!("NEXT_CLOSING_REGISTRATIONS");
+const NEXT_CLOSING_REGISTRATIONS_TIMESTAMP: u32 =
+    ink::selector_id!("NEXT_CLOSING_REGISTRATIONS_TIMESTAMP");
+const LAST_FAILURE: u32 = ink::selector_id!("LAST_FAILURE");
+/// keys used by the rollup anchor to store the queue (fixed size arrays to be encoded without length)
+const QUEUE_PREFIX: [u8; 2] = *b"q/";
+const QUEUE_HEAD: [u8; 7] = *b"q/_head";
+const QUEUE_TAIL: [u8; 7] = *b"q/_tail";
+/// maximum number of messages read in one call of `get_manager_queue`
+pub const MAX_QUEUE_PAGE: QueueIndex = 50;
 
 fn get_draw_number(client: &mut InkRollupClient) -> Result<Option<DrawNumber>, RaffleDrawError> {
     client
@@ -134,6 +219,45 @@ fn get_status(
         .map_err(|_| StatusUnknown)
 }
 
+fn get_paused(client: &mut InkRollupClient) -> Result<Option<bool>, RaffleDrawError> {
+    client
+        .get(&PAUSED)
+        .log_err("Paused flag unknown in kv store")
+        .map_err(|_| PausedUnknown)
+}
+
+fn get_nb_tickets(client: &mut InkRollupClient) -> Result<Option<u32>, RaffleDrawError> {
+    client
+        .get(&NB_TICKETS)
+        .log_err("Number of tickets unknown in kv store")
+        .map_err(|_| NbTicketsUnknown)
+}
+
+fn get_revenue(client: &mut InkRollupClient) -> Result<Option<Balance>, RaffleDrawError> {
+    client
+        .get(&REVENUE)
+        .log_err("Revenue unknown in kv store")
+        .map_err(|_| RevenueUnknown)
+}
+
+fn get_config(client: &mut InkRollupClient) -> Result<Option<RaffleConfig>, RaffleDrawError> {
+    client
+        .get(&CONFIG)
+        .log_err("Config unknown in kv store")
+        .map_err(|_| ConfigUnknown)
+}
+
+/// return the config used by the manager for the given draw
+pub fn get_manager_draw_config(
+    client: &mut InkRollupClient,
+    draw_number: DrawNumber,
+) -> Result<Option<RaffleConfig>, RaffleDrawError> {
+    client
+        .get(&(DRAW_CONFIG, draw_number))
+        .log_err("Draw config unknown in kv store")
+        .map_err(|_| DrawConfigUnknown)
+}
+
 pub fn get_manager_draw_number(client: &mut InkRollupClient) -> Result<Option<DrawNumber>, RaffleDrawError> {
     client
         .get(&DRAW_NUMBER)
@@ -141,6 +265,51 @@ pub fn get_manager_draw_number(client: &mut InkRollupClient) -> Result<Option<Dr
         .map_err(|_| DrawNumberUnknown)
 }
 
+/// return the index of the first request in the queue of the manager
+pub fn get_manager_queue_head(client: &mut InkRollupClient) -> Result<QueueIndex, RaffleDrawError> {
+    let queue_head: Option<QueueIndex> = client
+        .get(&QUEUE_HEAD)
+        .log_err("Queue head unknown in kv store")
+        .map_err(|_| QueueHeadUnknown)?;
+    Ok(queue_head.unwrap_or_default())
+}
+
+/// return the messages in the queue of the manager not processed yet,
+/// from the given index (or the head of the queue if greater).
+/// At most `limit` messages are read, the limit is capped to `MAX_QUEUE_PAGE`
+pub fn get_manager_queue(
+    client: &mut InkRollupClient,
+    from: QueueIndex,
+    limit: QueueIndex,
+) -> Result<QueueView, RaffleDrawError> {
+    let head = get_manager_queue_head(client)?;
+    let tail: Option<QueueIndex> = client
+        .get(&QUEUE_TAIL)
+        .log_err("Queue tail unknown in kv store")
+        .map_err(|_| QueueTailUnknown)?;
+    let tail = tail.unwrap_or_default();
+
+    let from = from.max(head);
+    let to = from.saturating_add(limit.min(MAX_QUEUE_PAGE)).min(tail);
+
+    let mut messages = Vec::new();
+    for index in from..to {
+        let message: Option<LottoManagerRequestMessage> = client
+            .get(&(QUEUE_PREFIX, index))
+            .log_err("Failed to read the message in the queue")
+            .map_err(|_| FailedToDecodeRequest)?;
+        if let Some(message) = message {
+            messages.push((index, message));
+        }
+    }
+
+    Ok(QueueView {
+        head,
+        tail,
+        messages,
+    })
+}
+
 pub fn get_manager_status(
     client: &mut InkRollupClient,
 ) -> Result<Option<RaffleManagerStatus>, RaffleDrawError> {
@@ -152,11 +321,74 @@ pub fn get_manager_status(
         .map_err(|_| StatusUnknown)
 }
 
+pub fn get_manager_next_closing_registrations(
+    client: &mut InkRollupClient,
+) -> Result<Option<(BlockNumber, Timestamp)>, RaffleDrawError> {
+    let block_number: Option<BlockNumber> = client
+        .get(&NEXT_CLOSING_REGISTRATIONS)
+        .log_err("Next closing registrations unknown in kv store")
+        .map_err(|_| NextClosingRegistrationsUnknown)?;
+    let timestamp: Option<Timestamp> = client
+        .get(&NEXT_CLOSING_REGISTRATIONS_TIMESTAMP)
+        .log_err("Next closing registrations timestamp unknown in kv store")
+        .map_err(|_| NextClosingRegistrationsUnknown)?;
+    Ok(block_number.map(|b| (b, timestamp.unwrap_or_default())))
+}
+
+/// return the last kind of failure reported to the manager for the given registration contract
+pub fn get_manager_last_failure(
+    client: &mut InkRollupClient,
+    registration_contract_id: RegistrationContractId,
+) -> Result<Option<ActionFailure>, RaffleDrawError> {
+    client
+        .get(&(LAST_FAILURE, registration_contract_id))
+        .log_err("Last failure unknown in kv store")
+        .map_err(|_| LastFailureUnknown)
+}
+
+/// DTO use for deserializing the json when querying the last header
+#[derive(Deserialize, Debug, PartialEq)]
+struct HeaderResponse<'a> {
+    #[serde(borrow)]
+    result: Header<'a>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Header<'a> {
+    number: &'a str,
+}
+
+/// Return the current block number of the chain exposed by the given rpc
+pub fn get_current_block_number(rpc: &str) -> Result<BlockNumber, RaffleDrawError> {
+    let headers = alloc::vec![
+        ("Content-Type".into(), "application/json".into()),
+        ("Accept".into(), "application/json".into())
+    ];
+    let body = r#"{"id":1,"jsonrpc":"2.0","method":"chain_getHeader","params":[]}"#;
+
+    let resp = http_post!(String::from(rpc), body, headers);
+    if resp.status_code != 200 {
+        ink::env::debug_println!("status code {}", resp.status_code);
+        return Err(HttpRequestFailed);
+    }
+
+    let response: HeaderResponse = serde_json_core::from_slice(resp.body.as_slice())
+        .or(Err(InvalidResponseBody))?
+        .0;
+    decode_block_number(response.result.number)
+}
+
+fn decode_block_number(number: &str) -> Result<BlockNumber, RaffleDrawError> {
+    let number = number.strip_prefix("0x").unwrap_or(number);
+    BlockNumber::from_str_radix(number, 16).map_err(|_| BlockNumberUnknown)
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::raffle_manager_contract::{LottoManagerRequestMessage, LottoManagerResponseMessage};
+    use crate::raffle_manager_contract::{
+        LottoManagerReply, LottoManagerRequestMessage, LottoManagerResponseMessage,
+    };
     use scale::{Decode, Encode};
 
     #[ink::test]
@@ -173,15 +405,41 @@ mod tests {
         let expected : Vec<u8> = hex::decode("04060000001004003100290010000000000000000000000000000000000000000000000000000000000000000000").expect("hex decode failed");
         assert_eq!(expected, encoded_response);
 
-        let response = LottoManagerResponseMessage::Winners(draw_number, vec![], vec![], hash);
+        let response = LottoManagerResponseMessage::Winners(draw_number, vec![], hash);
+        let encoded_response = response.encode();
+        let expected: Vec<u8> = hex::decode(
+            "0506000000000000000000000000000000000000000000000000000000000000000000000000",
+        )
+        .expect("hex decode failed");
+        assert_eq!(expected, encoded_response);
+
+        let winners = vec![MultiAddress::AccountId20([1; 20])];
+        let response = LottoManagerResponseMessage::Winners(draw_number, winners, hash);
         let encoded_response = response.encode();
         let expected: Vec<u8> = hex::decode(
-            "050600000000000000000000000000000000000000000000000000000000000000000000000000",
+            "0506000000040101010101010101010101010101010101010101010000000000000000000000000000000000000000000000000000000000000000",
         )
         .expect("hex decode failed");
         assert_eq!(expected, encoded_response);
     }
 
+    #[ink::test]
+    fn encode_reply() {
+        let reply = LottoManagerReply {
+            request_index: Some(3),
+            response: LottoManagerResponseMessage::CloseRegistrations(),
+        };
+        let expected: Vec<u8> = hex::decode("010300000007").expect("hex decode failed");
+        assert_eq!(expected, reply.encode());
+
+        let reply = LottoManagerReply {
+            request_index: None,
+            response: LottoManagerResponseMessage::CloseRegistrations(),
+        };
+        let expected: Vec<u8> = hex::decode("0007").expect("hex decode failed");
+        assert_eq!(expected, reply.encode());
+    }
+
     #[ink::test]
     fn encode_keys() {
         const QUEUE_PREFIX: &[u8] = b"q/";
@@ -189,14 +447,17 @@ mod tests {
         const QUEUE_HEAD_KEY: &[u8] = b"_head";
         let head_key = [QUEUE_PREFIX, QUEUE_HEAD_KEY].concat();
         ink::env::debug_println!("queue head key: {head_key:02x?}");
+        assert_eq!(head_key, QUEUE_HEAD.encode());
 
         const QUEUE_TAIL_KEY: &[u8] = b"_tail";
         let tail_key = [QUEUE_PREFIX, QUEUE_TAIL_KEY].concat();
         ink::env::debug_println!("queue tail key: {tail_key:02x?}");
+        assert_eq!(tail_key, QUEUE_TAIL.encode());
 
         let id: u32 = 11;
         let key = [QUEUE_PREFIX, &id.encode()].concat();
         ink::env::debug_println!("queue key: {key:02x?}");
+        assert_eq!(key, (super::QUEUE_PREFIX, id).encode());
 
         let key = &STATUS.encode();
         ink::env::debug_println!("status key: {key:02x?}");
@@ -211,14 +472,35 @@ mod tests {
         let nb_numbers = 4;
         let min_number = 1;
         let max_number = 50;
-        let request = RequestForAction::SetConfigAndStart(RaffleConfig{nb_numbers, min_number, max_number}, registration_contract_id);
+        let revenue_split = RevenueSplit{jackpot: 80, treasury: 15, reserve: 5};
+        let request = RequestForAction::SetConfigAndStart(RaffleConfig{nb_numbers, min_number, max_number}, registration_contract_id, revenue_split, 100);
         let encoded_request = request.encode();
-        let expected : Vec<u8> = hex::decode("0004010032000a000000000000000000000000000000").expect("hex decode failed");
+        let expected : Vec<u8> = hex::decode("0004010032000a000000000000000000000000000000500f0564000000000000000000000000000000").expect("hex decode failed");
 
         ink::env::debug_println!("encoded_request: {encoded_request:02x?}");
         assert_eq!(expected, encoded_request);
     }
 
+    #[ink::test]
+    fn encode_request_update_config() {
+        let nb_numbers = 4;
+        let min_number = 1;
+        let max_number = 50;
+        let request = RequestForAction::UpdateConfig(RaffleConfig{nb_numbers, min_number, max_number});
+        let encoded_request = request.encode();
+        let expected : Vec<u8> = hex::decode("080401003200").expect("hex decode failed");
+
+        ink::env::debug_println!("encoded_request: {encoded_request:02x?}");
+        assert_eq!(expected, encoded_request);
+    }
+
+    #[ink::test]
+    fn decode_block_number() {
+        assert_eq!(super::decode_block_number("0x5d6a1b"), Ok(6122011));
+        assert_eq!(super::decode_block_number("0x0"), Ok(0));
+        assert_eq!(super::decode_block_number("0xzz"), Err(BlockNumberUnknown));
+    }
+
    #[ink::test]
    fn decode_message() {
        let encoded_message: Vec<u8> =